@@ -0,0 +1,39 @@
+//! Times parsing the larger definition files under `tests/defs`
+//!
+//! Run with `cargo bench --bench parse`. Prints the fastest and the median of a number of runs
+//! for each file, with and without keeping trivia.
+
+use std::fs;
+use std::time::Instant;
+
+const FILES: &[&str] = &["html.webidl", "dom.webidl", "webgpu.webidl"];
+const RUNS: usize = 200;
+
+fn time(file: &str, what: &str, mut parse: impl FnMut()) {
+    parse();
+    let mut times: Vec<_> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            parse();
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    let (min, median) = (times[0], times[RUNS / 2]);
+    println!(
+        "{:<16} {:<18} min {:?}, median {:?}",
+        file, what, min, median
+    );
+}
+
+fn main() {
+    for file in FILES {
+        let content = fs::read_to_string(format!("tests/defs/{}", file)).unwrap();
+        time(file, "parse", || {
+            weedle::parse(&content).unwrap();
+        });
+        time(file, "parse_with_trivia", || {
+            weedle::parse_with_trivia(&content).unwrap();
+        });
+    }
+}
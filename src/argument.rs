@@ -1,6 +1,7 @@
 use crate::attribute::ExtendedAttributeList;
 use crate::common::{Default, Identifier, Punctuated};
 use crate::types::{AttributedType, Type};
+use crate::{IResult, Parse};
 
 /// Parses a list of argument. Ex: `double v1, double v2, double v3, optional double alpha`
 pub type ArgumentList<'a> = Punctuated<Argument<'a>, term!(,)>;
@@ -16,10 +17,7 @@ ast_types! {
             optional: Option<term!(optional)>,
             type_: AttributedType<'a>,
             identifier: Identifier<'a>,
-            default: Option<Default<'a>> = map!(
-                cond!(optional.is_some(), weedle!(Option<Default<'a>>)),
-                |default| default.unwrap_or(None)
-            ),
+            default: Option<Default<'a>> = call!(default_value, optional.is_some()),
         }),
         /// Parses `[attributes]? type... identifier`
         Variadic(struct VariadicArgument<'a> {
@@ -31,10 +29,20 @@ ast_types! {
     }
 }
 
+/// Parses the default value of an argument, which only optional arguments may have
+fn default_value(input: &str, optional: bool) -> IResult<&str, Option<Default<'_>>> {
+    if optional {
+        Option::<Default<'_>>::parse(input)
+    } else {
+        Ok((input, None))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::literal::{DecLit, DefaultValue, IntegerLit};
+    use crate::span::{Span, SpanlessEq};
     use crate::Parse;
 
     test!(should_parse_single_argument { "short a" =>
@@ -68,9 +76,10 @@ mod test {
         attributes.is_none();
         optional.is_some();
         identifier.0 == "a";
-        default == Some(Default {
+        default.spanless_eq(&Some(Default {
             assign: term!(=),
-            value: DefaultValue::Integer(IntegerLit::Dec(DecLit("5"))),
-        });
+            value: DefaultValue::Integer(IntegerLit::Dec(DecLit::new("5"))),
+            span: Span::default(),
+        }));
     });
 }
@@ -1,6 +1,10 @@
+use std::borrow::Cow;
+
 use crate::argument::ArgumentList;
 use crate::common::{Bracketed, Identifier, Parenthesized, Punctuated};
-use crate::literal::StringLit;
+use crate::literal::{IntegerLit, StringLit};
+use crate::print::{Printer, ToWebIdl};
+use crate::{Err, ErrorKind, IResult, Parse};
 
 /// Parses a list of attributes. Ex: `[ attribute1, attribute2 ]`
 pub type ExtendedAttributeList<'a> = Bracketed<Punctuated<ExtendedAttribute<'a>, term!(,)>>;
@@ -8,6 +12,12 @@ pub type ExtendedAttributeList<'a> = Bracketed<Punctuated<ExtendedAttribute<'a>,
 /// Matches comma separated identifier list
 pub type IdentifierList<'a> = Punctuated<Identifier<'a>, term!(,)>;
 
+/// Matches comma separated string list
+pub type StringList<'a> = Punctuated<StringLit<'a>, term!(,)>;
+
+/// Matches comma separated integer list
+pub type IntegerList<'a> = Punctuated<IntegerLit<'a>, term!(,)>;
+
 ast_types! {
     /// Parses on of the forms of attribute
     enum ExtendedAttribute<'a> {
@@ -36,43 +46,182 @@ ast_types! {
             assign: term!(=),
             list: Parenthesized<IdentifierList<'a>>,
         }),
+        /// Parses a string list. Ex: `Pref=(("a","b"))`
+        ///
+        /// (( )) means ( ) chars
+        StringList(struct ExtendedAttributeStringList<'a> {
+            identifier: Identifier<'a>,
+            assign: term!(=),
+            list: Parenthesized<StringList<'a>>,
+        }),
+        /// Parses an integer list. Ex: `Sizes=((1,2))`
+        ///
+        /// (( )) means ( ) chars
+        IntegerList(struct ExtendedAttributeIntegerList<'a> {
+            identifier: Identifier<'a>,
+            assign: term!(=),
+            list: Parenthesized<IntegerList<'a>>,
+        }),
         /// Parses an attribute with an identifier. Ex: `PutForwards=name`
-        #[derive(Copy)]
         Ident(struct ExtendedAttributeIdent<'a> {
             lhs_identifier: Identifier<'a>,
             assign: term!(=),
             rhs: IdentifierOrString<'a>,
         }),
+        /// Parses an attribute with an integer. Ex: `Priority=3`
+        Integer(struct ExtendedAttributeInteger<'a> {
+            lhs_identifier: Identifier<'a>,
+            assign: term!(=),
+            rhs: IntegerLit<'a> = call!(whole_integer),
+        }),
         /// Parses an attribute with a wildcard. Ex: `Exposed=*`
-        #[derive(Copy)]
         Wildcard(struct ExtendedAttributeWildCard<'a> {
             lhs_identifier: Identifier<'a>,
             assign: term!(=),
             rhs: term!(*),
         }),
+        /// Parses an attribute of any other shape, keeping the tokens after its name as they
+        /// are. Ex: `Version=1.5` or `Values=(a, "b", 3)`
+        Tokens(struct ExtendedAttributeTokens<'a> {
+            identifier: Identifier<'a>,
+            tokens: Vec<ExtendedAttributeToken<'a>> = call!(balanced_tokens),
+        }),
         /// Parses a plain attribute. Ex: `Replaceable`
-        #[derive(Copy)]
         NoArgs(struct ExtendedAttributeNoArgs<'a>(
             Identifier<'a>,
         )),
     }
 
     /// Parses `stringifier|static`
-    #[derive(Copy)]
     enum IdentifierOrString<'a> {
         Identifier(Identifier<'a>),
         String(StringLit<'a>),
     }
+
+    /// A token of an attribute of any other shape: a string, a run of identifier or number
+    /// characters, or any other single character. Ex: `"a"`, `-1.5` or `(`
+    struct ExtendedAttributeToken<'a>(
+        Cow<'a, str> = map!(expect!("token", ws!(raw_token)), Cow::Borrowed),
+    )
+}
+
+impl<'a> ToWebIdl for ExtendedAttributeToken<'a> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        printer.token(&self.0, self.2.as_deref());
+    }
+}
+
+/// An integer which is the whole value of the attribute, so `1.5` is left to
+/// `ExtendedAttributeTokens`
+fn whole_integer(input: &str) -> IResult<&str, IntegerLit<'_>> {
+    let (rest, integer) = IntegerLit::parse(input)?;
+    match rest.chars().next() {
+        Some(',') | Some(']') => Ok((rest, integer)),
+        _ => Err(Err::Error((input, ErrorKind::Verify))),
+    }
+}
+
+fn raw_token(input: &str) -> IResult<&str, &str> {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.';
+    let end = match input.chars().next() {
+        Some('"') => match input[1..].find('"') {
+            Some(end) => end + 2,
+            None => return Err(Err::Error((input, ErrorKind::Char))),
+        },
+        Some(c) if is_word(c) => input.find(|c| !is_word(c)).unwrap_or(input.len()),
+        Some(c) if !c.is_whitespace() => c.len_utf8(),
+        _ => return Err(Err::Error((input, ErrorKind::Eof))),
+    };
+    Ok((&input[end..], &input[..end]))
+}
+
+/// Parses the tokens up to the `,` or `]` ending the attribute. Brackets must be balanced.
+fn balanced_tokens(mut input: &str) -> IResult<&str, Vec<ExtendedAttributeToken<'_>>> {
+    let mut closers = Vec::new();
+    let mut tokens = Vec::new();
+    loop {
+        let (rest, token) = ExtendedAttributeToken::parse(input)?;
+        match &*token.0 {
+            "," | "]" if closers.is_empty() => break,
+            "(" => closers.push(")"),
+            "[" => closers.push("]"),
+            "{" => closers.push("}"),
+            ")" | "]" | "}" if closers.pop() != Some(&*token.0) => {
+                return Err(Err::Error((input, ErrorKind::Verify)));
+            }
+            _ => {}
+        }
+        tokens.push(token);
+        input = rest;
+    }
+    if tokens.is_empty() {
+        return Err(Err::Error((input, ErrorKind::Verify)));
+    }
+    Ok((input, tokens))
+}
+
+impl<'a> ExtendedAttributeList<'a> {
+    /// Returns the first attribute named `name`
+    pub fn find(&self, name: &str) -> Option<&ExtendedAttribute<'a>> {
+        self.body.list.iter().find(|a| a.identifier().0 == name)
+    }
+
+    /// Returns `true` if an attribute is named `name`, whatever its arguments
+    pub fn has(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    /// Identifier on the right hand side of the attribute named `name`. Ex: `name` for
+    /// `PutForwards=name`
+    pub fn ident(&self, name: &str) -> Option<&str> {
+        match self.find(name)? {
+            ExtendedAttribute::Ident(ExtendedAttributeIdent {
+                rhs: IdentifierOrString::Identifier(identifier),
+                ..
+            }) => Some(&identifier.0),
+            _ => None,
+        }
+    }
+
+    /// Identifiers on the right hand side of the attribute named `name`, which may be a
+    /// single identifier. Ex: `["Window", "Worker"]` for `Exposed=(Window,Worker)`
+    pub fn ident_list(&self, name: &str) -> Option<Vec<&str>> {
+        match self.find(name)? {
+            ExtendedAttribute::IdentList(attribute) => {
+                Some(attribute.list.body.list.iter().map(|i| &*i.0).collect())
+            }
+            _ => self.ident(name).map(|identifier| vec![identifier]),
+        }
+    }
+}
+
+impl<'a> ExtendedAttribute<'a> {
+    /// Name of the attribute. Ex: `Exposed` for `Exposed=Window`
+    pub fn identifier(&self) -> &Identifier<'a> {
+        match self {
+            ExtendedAttribute::ArgList(attribute) => &attribute.identifier,
+            ExtendedAttribute::NamedArgList(attribute) => &attribute.lhs_identifier,
+            ExtendedAttribute::IdentList(attribute) => &attribute.identifier,
+            ExtendedAttribute::StringList(attribute) => &attribute.identifier,
+            ExtendedAttribute::IntegerList(attribute) => &attribute.identifier,
+            ExtendedAttribute::Ident(attribute) => &attribute.lhs_identifier,
+            ExtendedAttribute::Integer(attribute) => &attribute.lhs_identifier,
+            ExtendedAttribute::Wildcard(attribute) => &attribute.lhs_identifier,
+            ExtendedAttribute::Tokens(attribute) => &attribute.identifier,
+            ExtendedAttribute::NoArgs(attribute) => &attribute.0,
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::span::SpanlessEq;
     use crate::Parse;
 
     test!(should_parse_attribute_no_args { "Replaceable" =>
         "";
-        ExtendedAttributeNoArgs => ExtendedAttributeNoArgs(Identifier("Replaceable"))
+        ExtendedAttributeNoArgs => ExtendedAttributeNoArgs::new(Identifier::new("Replaceable"))
     });
 
     test!(should_parse_attribute_arg_list { "Constructor(double x, double y)" =>
@@ -86,7 +235,7 @@ mod test {
         "";
         ExtendedAttributeIdent;
         lhs_identifier.0 == "PutForwards";
-        rhs == IdentifierOrString::Identifier(Identifier("name"));
+        rhs.spanless_eq(&IdentifierOrString::Identifier(Identifier::new("name")));
     });
 
     test!(should_parse_ident_list { "Exposed=(Window,Worker)" =>
@@ -103,4 +252,109 @@ mod test {
         rhs_identifier.0 == "Image";
         args.body.list.len() == 1;
     });
+
+    test!(should_parse_string_list { r#"Pref=("dom.a", "dom.b")"# =>
+        "";
+        ExtendedAttributeStringList;
+        identifier.0 == "Pref";
+        list.body.list.spanless_eq(&vec![StringLit::new("dom.a"), StringLit::new("dom.b")]);
+    });
+
+    test!(should_parse_integer_list { "Sizes=(1, 0x2, -3)" =>
+        "";
+        ExtendedAttributeIntegerList;
+        identifier.0 == "Sizes";
+        list.body.list.len() == 3;
+    });
+
+    test!(should_parse_integer { "Priority=3]" =>
+        "]";
+        ExtendedAttributeInteger;
+        lhs_identifier.0 == "Priority";
+        rhs.spanless_eq(&IntegerLit::Dec(crate::literal::DecLit::new("3")));
+    });
+
+    #[test]
+    fn should_parse_other_shapes_as_tokens() {
+        for (source, tokens) in &[
+            ("Version=1.5]", vec!["=", "1.5"]),
+            (
+                r#"Values=(a, "b", 3)]"#,
+                vec!["=", "(", "a", ",", "\"b\"", ",", "3", ")"],
+            ),
+            (
+                "Nested=(a, [b, c]), Next]",
+                vec!["=", "(", "a", ",", "[", "b", ",", "c", "]", ")"],
+            ),
+            ("Negative=-Infinity]", vec!["=", "-Infinity"]),
+        ] {
+            let (rem, parsed) = ExtendedAttribute::parse(source).unwrap();
+            match parsed {
+                ExtendedAttribute::Tokens(attribute) => {
+                    let texts: Vec<&str> = attribute.tokens.iter().map(|t| &*t.0).collect();
+                    assert_eq!(texts, *tokens, "{}", source);
+                }
+                other => panic!("{}: expected tokens, found {:?}", source, other),
+            }
+            assert!(rem.starts_with([',', ']']), "{}", source);
+        }
+    }
+
+    #[test]
+    fn should_keep_the_simplest_shape() {
+        let shape = |source| match ExtendedAttribute::parse(source).unwrap().1 {
+            ExtendedAttribute::IdentList(_) => "ident list",
+            ExtendedAttribute::StringList(_) => "string list",
+            ExtendedAttribute::IntegerList(_) => "integer list",
+            ExtendedAttribute::Ident(_) => "ident",
+            ExtendedAttribute::Integer(_) => "integer",
+            ExtendedAttribute::Tokens(_) => "tokens",
+            ExtendedAttribute::NoArgs(_) => "no args",
+            _ => "other",
+        };
+        assert_eq!(shape("Exposed=(Window,Worker)]"), "ident list");
+        assert_eq!(shape(r#"Foo=("a")]"#), "string list");
+        assert_eq!(shape("Foo=(1)]"), "integer list");
+        assert_eq!(shape(r#"Foo="a"]"#), "ident");
+        assert_eq!(shape("Foo=1]"), "integer");
+        assert_eq!(shape("Foo=1.5]"), "tokens");
+        assert_eq!(shape("Foo=(1, 2.5)]"), "tokens");
+        assert_eq!(shape("Foo]"), "no args");
+    }
+
+    #[test]
+    fn should_not_parse_unbalanced_tokens() {
+        for source in &["Foo=(a]", "Foo=(a}]", "Foo=a)]", "Foo=(a"] {
+            assert!(ExtendedAttributeList::parse(&format!("[{}]", source)).is_err());
+        }
+    }
+
+    #[test]
+    fn should_print_tokens_as_parsed() {
+        use crate::print::ToWebIdl;
+
+        let source = r#"[Pref=("a", "b"), Sizes=(1, 2), Priority=3, Values=(a, "b", 1.5)]"#;
+        let (_, parsed) = ExtendedAttributeList::parse(source).unwrap();
+        assert_eq!(parsed.to_webidl().trim_end(), source);
+    }
+
+    #[test]
+    fn should_find_attributes_by_name() {
+        let (_, list) = ExtendedAttributeList::parse(
+            "[Exposed=(Window,Worker), SecureContext, PutForwards=href, Global=Window]",
+        )
+        .unwrap();
+
+        assert!(list.has("SecureContext"));
+        assert!(!list.has("Replaceable"));
+        assert_eq!(
+            list.find("PutForwards").unwrap().identifier().0,
+            "PutForwards"
+        );
+        assert_eq!(list.ident("PutForwards"), Some("href"));
+        assert_eq!(list.ident("Exposed"), None);
+        assert_eq!(list.ident_list("Exposed"), Some(vec!["Window", "Worker"]));
+        assert_eq!(list.ident_list("Global"), Some(vec!["Window"]));
+        assert_eq!(list.ident_list("SecureContext"), None);
+    }
 }
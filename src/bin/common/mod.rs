@@ -0,0 +1,118 @@
+//! Code shared by the command-line tools
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use weedle::format::{format, FormatOptions, MemberOrder};
+use weedle::project::SourceMap;
+
+/// Formats the files named in `args`, the arguments after the command, in place, or stdin to
+/// stdout without files
+///
+/// `usage` is printed for `--help` and for arguments which are not understood.
+pub fn format_main(mut args: impl Iterator<Item = String>, usage: &str) {
+    let mut options = FormatOptions::default();
+    let mut check = false;
+    let mut paths = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--sort" => options.member_order = MemberOrder::Sorted,
+            "--max-width" => match args.next().and_then(|width| width.parse().ok()) {
+                Some(width) => options.max_width = width,
+                None => fail(usage),
+            },
+            "-h" | "--help" => {
+                println!("{}", usage);
+                return;
+            }
+            _ if arg.starts_with('-') => fail(usage),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if paths.is_empty() {
+        let source = read_stdin();
+        match format(&source, &options) {
+            Ok(formatted) if check => {
+                if formatted != source {
+                    println!("<stdin>");
+                    process::exit(1);
+                }
+            }
+            Ok(formatted) => {
+                let _ = io::stdout().write_all(formatted.as_bytes());
+            }
+            Err(err) => fail(&format!("<stdin>:{}:{}: {}", err.line, err.column, err)),
+        }
+        return;
+    }
+
+    let mut failed = false;
+    let mut unformatted = false;
+    let mut sources = SourceMap::new();
+    for path in &paths {
+        if let Err(err) = load(&mut sources, path) {
+            eprintln!("{}: {}", path.display(), err);
+            failed = true;
+        }
+    }
+    for (_, file) in sources.files() {
+        let formatted = match format(&file.text, &options) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                eprintln!("{}:{}:{}: {}", file.name, err.line, err.column, err);
+                failed = true;
+                continue;
+            }
+        };
+        if formatted == file.text {
+            continue;
+        }
+        if check {
+            println!("{}", file.name);
+            unformatted = true;
+            continue;
+        }
+        // Written back to the path it was read from, as its name may have lost non-UTF-8 bytes
+        let path = file.path.as_ref().expect("files are loaded from paths");
+        if let Err(err) = fs::write(path, formatted) {
+            eprintln!("{}: {}", file.name, err);
+            failed = true;
+        }
+    }
+
+    if failed {
+        process::exit(2);
+    }
+    if unformatted {
+        process::exit(1);
+    }
+}
+
+/// Reads the file at `path`, or the `.webidl` files below it if it is a directory
+pub fn load(sources: &mut SourceMap, path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        sources.load_dir(path)?;
+    } else {
+        sources.load(path)?;
+    }
+    Ok(())
+}
+
+/// Reads all of stdin, exiting if it cannot be read
+pub fn read_stdin() -> String {
+    let mut source = String::new();
+    if let Err(err) = io::stdin().read_to_string(&mut source) {
+        fail(&format!("<stdin>: {}", err));
+    }
+    source
+}
+
+pub fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
@@ -0,0 +1,19 @@
+//! Formats WebIDL files in place
+//!
+//! ```text
+//! weedle-fmt [--check] [--sort] [--max-width N] [PATH]...
+//! ```
+//!
+//! Directories are searched for `.webidl` files. Without paths, the source is read from stdin
+//! and written to stdout. With `--check`, files are left untouched and the ones which are not
+//! formatted are listed, exiting with status 1 if there are any.
+
+use std::env;
+
+mod common;
+
+const USAGE: &str = "usage: weedle-fmt [--check] [--sort] [--max-width N] [PATH]...";
+
+fn main() {
+    common::format_main(env::args().skip(1), USAGE);
+}
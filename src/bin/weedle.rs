@@ -0,0 +1,270 @@
+//! Checks, dumps, formats and counts WebIDL definitions
+//!
+//! ```text
+//! weedle check [--resolve] [PATH]...
+//! weedle dump [--format debug|json|webidl2] [PATH]...
+//! weedle fmt [--check] [--sort] [--max-width N] [PATH]...
+//! weedle stats [PATH]...
+//! ```
+//!
+//! Directories are searched for `.webidl` files. Without paths, the source is read from stdin.
+//!
+//! `check` reports syntax errors, the rules of the specification the definitions break and
+//! misused extended attributes, and with `--resolve` also names which none of the files define.
+//! It exits with status 1 if there are errors. `dump` prints the parsed definitions: a single
+//! source as a list, several as an object keyed by path in the JSON formats. `fmt` works like
+//! `weedle-fmt` and `stats` counts definitions by kind.
+//!
+//! Requires the `cli` feature.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::{env, process};
+
+use serde_json::Value;
+use weedle::diagnostic::{Diagnostic, Severity};
+use weedle::project::{FileError, Project, SourceFile, SourceMap};
+use weedle::span::Span;
+use weedle::{known_attribute, resolve, validate, webidl2, Definition};
+
+mod common;
+
+use common::fail;
+
+const USAGE: &str = "usage: weedle <check|dump|fmt|stats> [OPTION]... [PATH]...";
+const CHECK_USAGE: &str = "usage: weedle check [--resolve] [PATH]...";
+const DUMP_USAGE: &str = "usage: weedle dump [--format debug|json|webidl2] [PATH]...";
+const FMT_USAGE: &str = "usage: weedle fmt [--check] [--sort] [--max-width N] [PATH]...";
+const STATS_USAGE: &str = "usage: weedle stats [PATH]...";
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let command = args.next().unwrap_or_else(|| fail(USAGE));
+    match command.as_str() {
+        "check" => check(args),
+        "dump" => dump(args),
+        "fmt" => common::format_main(args, FMT_USAGE),
+        "stats" => stats(args),
+        "-h" | "--help" => println!("{}", USAGE),
+        _ => fail(USAGE),
+    }
+}
+
+fn check(args: impl Iterator<Item = String>) {
+    let mut resolve = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--resolve" => resolve = true,
+            "-h" | "--help" => return println!("{}", CHECK_USAGE),
+            _ if arg.starts_with('-') => fail(CHECK_USAGE),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let sources = read_sources(&paths);
+    let mut errors = 0;
+    let mut parsed = Vec::new();
+    for (file, source) in sources.files() {
+        match weedle::parse(&source.text) {
+            Ok(definitions) => parsed.push((file, definitions)),
+            Err(err) => {
+                eprintln!(
+                    "{}:{}:{}: error: {}\n",
+                    source.name, err.line, err.column, err
+                );
+                errors += 1;
+            }
+        }
+    }
+
+    let files: Vec<_> = parsed
+        .iter()
+        .map(|(_, definitions)| &definitions[..])
+        .collect();
+    let mut diagnostics = validate::validate(files.iter().copied());
+    diagnostics.extend(known_attribute::check(files.iter().copied()));
+    if resolve {
+        diagnostics.extend(resolve::resolve(files.iter().copied()).diagnostics);
+    }
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.file, diagnostic.span.start));
+
+    // Diagnostics count only the files which parsed
+    let ids: Vec<_> = parsed.iter().map(|(file, _)| *file).collect();
+    let mut warnings = 0;
+    for mut diagnostic in diagnostics {
+        diagnostic.file = ids[diagnostic.file];
+        for note in &mut diagnostic.notes {
+            note.file = ids[note.file];
+        }
+        eprintln!("{}\n", render(&diagnostic, &sources));
+        match diagnostic.severity {
+            Severity::Error => errors += 1,
+            Severity::Warning => warnings += 1,
+        }
+    }
+
+    if errors + warnings > 0 {
+        eprintln!("{} error(s), {} warning(s)", errors, warnings);
+    }
+    if errors > 0 {
+        process::exit(1);
+    }
+}
+
+/// Formats a diagnostic with the source line it points into, followed by its notes
+fn render(diagnostic: &Diagnostic, sources: &SourceMap) -> String {
+    let rendered = sources.display(diagnostic);
+    let (first, notes) = rendered.split_at(rendered.find('\n').unwrap_or(rendered.len()));
+    let mut out = format!("{}\n", first);
+    snippet(&mut out, sources.file(diagnostic.file), diagnostic.span);
+    out + notes
+}
+
+/// Writes the line `span` starts on, with carets under the part of it which is in `span`
+fn snippet(out: &mut String, source: &SourceFile, span: Span) {
+    let position = source.line_column(span.start);
+    let line = source.line(position.line).unwrap_or("");
+    let width = source
+        .source_text(span)
+        .lines()
+        .next()
+        .map_or(1, |text| text.chars().count().max(1));
+
+    let gutter = " ".repeat(position.line.to_string().len());
+    let indent: String = line
+        .chars()
+        .take(position.column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let _ = writeln!(out, "{} |", gutter);
+    let _ = writeln!(out, "{} | {}", position.line, line);
+    let _ = write!(out, "{} | {}{}", gutter, indent, "^".repeat(width));
+}
+
+fn dump(mut args: impl Iterator<Item = String>) {
+    let mut format = "debug".to_string();
+    let mut paths = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = args.next().unwrap_or_else(|| fail(DUMP_USAGE)),
+            "-h" | "--help" => return println!("{}", DUMP_USAGE),
+            _ if arg.starts_with('-') => fail(DUMP_USAGE),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    let to_json: fn(&[Definition]) -> Value = match format.as_str() {
+        "debug" => |_| Value::Null,
+        "json" => |definitions| serde_json::to_value(definitions).unwrap(),
+        "webidl2" => |definitions| webidl2::to_json(definitions),
+        _ => fail(DUMP_USAGE),
+    };
+
+    let sources = read_sources(&paths);
+    let project = parse_all(&sources);
+    let parsed: Vec<_> = project.files().enumerate().collect();
+    if format == "debug" {
+        for (file, definitions) in &parsed {
+            if parsed.len() > 1 {
+                println!("// {}", sources.file(*file).name);
+            }
+            println!("{:#?}", definitions);
+        }
+        return;
+    }
+
+    let json = match &parsed[..] {
+        [(_, definitions)] => to_json(definitions),
+        _ => Value::Object(
+            parsed
+                .iter()
+                .map(|(file, definitions)| (sources.file(*file).name.clone(), to_json(definitions)))
+                .collect(),
+        ),
+    };
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+fn stats(args: impl Iterator<Item = String>) {
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => return println!("{}", STATS_USAGE),
+            _ if arg.starts_with('-') => fail(STATS_USAGE),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let sources = read_sources(&paths);
+    let project = parse_all(&sources);
+    let parsed: Vec<_> = project.files().enumerate().collect();
+    let mut total = BTreeMap::new();
+    for (file, definitions) in &parsed {
+        let mut counts = BTreeMap::new();
+        for definition in *definitions {
+            *counts.entry(kind(definition)).or_insert(0) += 1;
+            *total.entry(kind(definition)).or_insert(0) += 1;
+        }
+        print_counts(&sources.file(*file).name, definitions.len(), &counts);
+    }
+    if parsed.len() > 1 {
+        print_counts("total", total.values().sum(), &total);
+    }
+}
+
+fn print_counts(name: &str, definitions: usize, counts: &BTreeMap<&str, usize>) {
+    println!("{}: {} definitions", name, definitions);
+    for (kind, count) in counts {
+        println!("  {:<24} {:>5}", kind, count);
+    }
+}
+
+fn kind(definition: &Definition) -> &'static str {
+    match definition {
+        Definition::Callback(_) => "callback",
+        Definition::CallbackInterface(_) => "callback interface",
+        Definition::Interface(_) => "interface",
+        Definition::InterfaceMixin(_) => "interface mixin",
+        Definition::Namespace(_) => "namespace",
+        Definition::Dictionary(_) => "dictionary",
+        Definition::PartialInterface(_) => "partial interface",
+        Definition::PartialInterfaceMixin(_) => "partial interface mixin",
+        Definition::PartialDictionary(_) => "partial dictionary",
+        Definition::PartialNamespace(_) => "partial namespace",
+        Definition::Enum(_) => "enum",
+        Definition::Typedef(_) => "typedef",
+        Definition::IncludesStatement(_) => "includes",
+        Definition::Implements(_) => "implements",
+    }
+}
+
+/// Reads the files named by `paths`, or stdin without paths, exiting if one cannot be read
+fn read_sources(paths: &[PathBuf]) -> SourceMap {
+    let mut sources = SourceMap::new();
+    if paths.is_empty() {
+        sources.add("<stdin>", common::read_stdin());
+    }
+    for path in paths {
+        if let Err(err) = common::load(&mut sources, path) {
+            fail(&format!("{}: {}", path.display(), err));
+        }
+    }
+    sources
+}
+
+/// Parses every source, exiting with status 1 after reporting the ones which do not parse
+fn parse_all(sources: &SourceMap) -> Project<'_> {
+    match Project::parse(sources) {
+        Ok(project) => project,
+        Err(errors) => {
+            for FileError { name, error, .. } in errors {
+                eprintln!(
+                    "{}:{}:{}: error: {}\n",
+                    name, error.line, error.column, error
+                );
+            }
+            process::exit(1);
+        }
+    }
+}
@@ -1,6 +1,12 @@
+use std::borrow::Cow;
+
 use crate::literal::DefaultValue;
+use crate::print::{Printer, ToWebIdl};
+use crate::span::{Span, SpanlessEq, Spanned};
 use crate::term;
-use crate::Parse;
+use crate::trivia::{TokenTrivia, Tokens};
+use crate::visit::{Accept, Visit, VisitMut, Walk};
+use crate::{recovery, state, IResult, IntoOwned, Parse};
 
 impl<'a, T: Parse<'a>> Parse<'a> for Option<T> {
     parser!(opt!(weedle!(T)));
@@ -11,8 +17,16 @@ impl<'a, T: Parse<'a>> Parse<'a> for Box<T> {
 }
 
 /// Parses `item1 item2 item3...`
+///
+/// While recovering, items which fail are skipped, see [`parse_recovering`](../fn.parse_recovering.html).
 impl<'a, T: Parse<'a>> Parse<'a> for Vec<T> {
-    parser!(many0!(weedle!(T)));
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let _guard = state::enter(input);
+        if state::recovering() {
+            return recovery::many0_recovering(input);
+        }
+        many0!(input, weedle!(T))
+    }
 }
 
 impl<'a, T: Parse<'a>, U: Parse<'a>> Parse<'a> for (T, U) {
@@ -25,9 +39,69 @@ impl<'a, T: Parse<'a>, U: Parse<'a>, V: Parse<'a>> Parse<'a> for (T, U, V) {
     ));
 }
 
+impl<'a> IntoOwned for Cow<'a, str> {
+    type Owned = Cow<'static, str>;
+
+    fn into_owned(self) -> Cow<'static, str> {
+        Cow::Owned(Cow::into_owned(self))
+    }
+}
+
+impl IntoOwned for bool {
+    type Owned = bool;
+
+    fn into_owned(self) -> Self::Owned {
+        self
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.map(IntoOwned::into_owned)
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Box<T> {
+    type Owned = Box<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        Box::new((*self).into_owned())
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(IntoOwned::into_owned).collect()
+    }
+}
+
+impl<T: IntoOwned, U: IntoOwned> IntoOwned for (T, U) {
+    type Owned = (T::Owned, U::Owned);
+
+    fn into_owned(self) -> Self::Owned {
+        (self.0.into_owned(), self.1.into_owned())
+    }
+}
+
+impl<T: IntoOwned, U: IntoOwned, V: IntoOwned> IntoOwned for (T, U, V) {
+    type Owned = (T::Owned, U::Owned, V::Owned);
+
+    fn into_owned(self) -> Self::Owned {
+        (
+            self.0.into_owned(),
+            self.1.into_owned(),
+            self.2.into_owned(),
+        )
+    }
+}
+
 ast_types! {
     /// Parses `( body )`
-    #[derive(Copy, Default)]
+    #[derive(Default)]
     struct Parenthesized<T> where [T: Parse<'a>] {
         open_paren: term::OpenParen,
         body: T,
@@ -35,7 +109,7 @@ ast_types! {
     }
 
     /// Parses `[ body ]`
-    #[derive(Copy, Default)]
+    #[derive(Default)]
     struct Bracketed<T> where [T: Parse<'a>] {
         open_bracket: term::OpenBracket,
         body: T,
@@ -43,7 +117,7 @@ ast_types! {
     }
 
     /// Parses `{ body }`
-    #[derive(Copy, Default)]
+    #[derive(Default)]
     struct Braced<T> where [T: Parse<'a>] {
         open_brace: term::OpenBrace,
         body: T,
@@ -51,54 +125,474 @@ ast_types! {
     }
 
     /// Parses `< body >`
-    #[derive(Copy, Default)]
+    #[derive(Default)]
     struct Generics<T> where [T: Parse<'a>] {
         open_angle: term::LessThan,
         body: T,
         close_angle: term::GreaterThan,
     }
 
-    /// Parses `(item1, item2, item3,...)?`
-    struct Punctuated<T, S> where [T: Parse<'a>, S: Parse<'a> + ::std::default::Default] {
-        list: Vec<T> = separated_list!(weedle!(S), weedle!(T)),
-        separator: S = marker,
-    }
-
-    /// Parses `item1, item2, item3, ...`
-    struct PunctuatedNonEmpty<T, S> where [T: Parse<'a>, S: Parse<'a> + ::std::default::Default] {
-        list: Vec<T> = terminated!(
-            separated_nonempty_list!(weedle!(S), weedle!(T)),
-            opt!(weedle!(S))
-        ),
-        separator: S = marker,
-    }
-
-    /// Represents an identifier
-    ///
-    /// Follows `/_?[A-Za-z][0-9A-Z_a-z-]*/`
-    #[derive(Copy)]
-    struct Identifier<'a>(
-        // See https://heycam.github.io/webidl/#idl-names for why the leading
-        // underscore is trimmed
-        &'a str = ws!(do_parse!(
-            opt!(char!('_')) >>
-            id: recognize!(do_parse!(
-                take_while1!(|c: char| c.is_ascii_alphabetic()) >>
-                take_while!(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-') >>
-                (())
-            )) >>
-            (id)
-        )),
-    )
-
     /// Parses rhs of an assignment expression. Ex: `= 45`
-    #[derive(Copy)]
     struct Default<'a> {
         assign: term!(=),
         value: DefaultValue<'a>,
     }
 }
 
+/// Parses `(item1, item2, item3,...)?`
+///
+/// Lists are written out rather than generated, as their items and separators are parsed
+/// together.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Punctuated<T, S> {
+    pub list: Vec<T>,
+    /// The separator after each item but the last, which lists built by hand may leave out.
+    /// Items without one are separated by `S::default()`.
+    pub separators: Vec<S>,
+    pub span: Span,
+}
+
+/// Parses `item1, item2, item3, ...`
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PunctuatedNonEmpty<T, S> {
+    pub list: Vec<T>,
+    /// Like [`Punctuated::separators`](struct.Punctuated.html#structfield.separators)
+    pub separators: Vec<S>,
+    pub trailing: Option<S>,
+    pub span: Span,
+}
+
+impl<T, S> Punctuated<T, S> {
+    /// Creates the list without a source location, separated by `S::default()`
+    pub fn new(list: Vec<T>) -> Self {
+        Punctuated {
+            list,
+            separators: Vec::new(),
+            span: Span::default(),
+        }
+    }
+}
+
+impl<T, S> PunctuatedNonEmpty<T, S> {
+    /// Creates the list without a source location, separated by `S::default()`
+    pub fn new(list: Vec<T>) -> Self {
+        PunctuatedNonEmpty {
+            list,
+            separators: Vec::new(),
+            trailing: None,
+            span: Span::default(),
+        }
+    }
+}
+
+/// Parses the items of a list and the separators between them. An empty list fails unless
+/// `empty` is set.
+fn separated<'a, T: Parse<'a>, S: Parse<'a>>(
+    input: &'a str,
+    empty: bool,
+) -> IResult<&'a str, (Vec<T>, Vec<S>)> {
+    let mut list = Vec::new();
+    let mut separators = Vec::new();
+    let mut rest = match T::parse(input) {
+        Ok((rest, item)) => {
+            list.push(item);
+            rest
+        }
+        Err(nom::Err::Error(_)) if empty => return Ok((input, (list, separators))),
+        Err(e) => return Err(e),
+    };
+    loop {
+        let (next, separator) = match S::parse(rest) {
+            Ok(parsed) => parsed,
+            Err(nom::Err::Error(_)) => break,
+            Err(e) => return Err(e),
+        };
+        match T::parse(next) {
+            Ok((next, item)) => {
+                separators.push(separator);
+                list.push(item);
+                rest = next;
+            }
+            Err(nom::Err::Error(_)) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((rest, (list, separators)))
+}
+
+impl<'a, T: Parse<'a>, S: Parse<'a>> Parse<'a> for Punctuated<T, S> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let guard = state::enter_node(input, "Punctuated");
+        let (rest, (list, separators)) = separated(input, true)?;
+        let span = guard.span(input, rest);
+        Ok((
+            rest,
+            Punctuated {
+                list,
+                separators,
+                span,
+            },
+        ))
+    }
+}
+
+impl<'a, T: Parse<'a>, S: Parse<'a>> Parse<'a> for PunctuatedNonEmpty<T, S> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let guard = state::enter_node(input, "PunctuatedNonEmpty");
+        let (rest, (list, separators)) = separated(input, false)?;
+        let (rest, trailing) = Option::<S>::parse(rest)?;
+        let span = guard.span(input, rest);
+        Ok((
+            rest,
+            PunctuatedNonEmpty {
+                list,
+                separators,
+                trailing,
+                span,
+            },
+        ))
+    }
+}
+
+impl<T, S> Spanned for Punctuated<T, S> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<T, S> Spanned for PunctuatedNonEmpty<T, S> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<T: IntoOwned, S: IntoOwned> IntoOwned for Punctuated<T, S> {
+    type Owned = Punctuated<T::Owned, S::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        Punctuated {
+            list: self.list.into_owned(),
+            separators: self.separators.into_owned(),
+            span: self.span,
+        }
+    }
+}
+
+impl<T: IntoOwned, S: IntoOwned> IntoOwned for PunctuatedNonEmpty<T, S> {
+    type Owned = PunctuatedNonEmpty<T::Owned, S::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        PunctuatedNonEmpty {
+            list: self.list.into_owned(),
+            separators: self.separators.into_owned(),
+            trailing: self.trailing.into_owned(),
+            span: self.span,
+        }
+    }
+}
+
+// Separators only differ in where they are, and lists built by hand may leave them out
+impl<T: SpanlessEq, S> SpanlessEq for Punctuated<T, S> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.list.spanless_eq(&other.list)
+    }
+}
+
+impl<T: SpanlessEq, S> SpanlessEq for PunctuatedNonEmpty<T, S> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.list.spanless_eq(&other.list) && self.trailing.is_some() == other.trailing.is_some()
+    }
+}
+
+// Lists have no visitor method of their own, and separators have no children
+impl<'a, T: Accept<'a> + 'a, S> Walk<'a> for Punctuated<T, S> {
+    fn walk<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        self.list.accept(visitor);
+    }
+    fn walk_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        self.list.accept_mut(visitor);
+    }
+}
+
+impl<'a, T: Accept<'a> + 'a, S> Walk<'a> for PunctuatedNonEmpty<T, S> {
+    fn walk<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        self.list.accept(visitor);
+    }
+    fn walk_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        self.list.accept_mut(visitor);
+    }
+}
+
+impl<'a, T: Accept<'a> + 'a, S> Accept<'a> for Punctuated<T, S> {
+    fn accept<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        self.walk(visitor);
+    }
+    fn accept_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        self.walk_mut(visitor);
+    }
+}
+
+impl<'a, T: Accept<'a> + 'a, S> Accept<'a> for PunctuatedNonEmpty<T, S> {
+    fn accept<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        self.walk(visitor);
+    }
+    fn accept_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        self.walk_mut(visitor);
+    }
+}
+
+/// Tokens of the items and separators in source order
+fn list_tokens<'t, T: Tokens, S: Tokens>(
+    list: &'t [T],
+    separators: &'t [S],
+    tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>,
+) {
+    for (i, item) in list.iter().enumerate() {
+        if i > 0 {
+            if let Some(separator) = separators.get(i - 1) {
+                separator.tokens(tokens);
+            }
+        }
+        item.tokens(tokens);
+    }
+}
+
+fn list_tokens_mut<'t, T: Tokens, S: Tokens>(
+    list: &'t mut [T],
+    separators: &'t mut [S],
+    tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>,
+) {
+    let mut separators = separators.iter_mut();
+    for (i, item) in list.iter_mut().enumerate() {
+        if i > 0 {
+            if let Some(separator) = separators.next() {
+                separator.tokens_mut(tokens);
+            }
+        }
+        item.tokens_mut(tokens);
+    }
+}
+
+impl<T: Tokens, S: Tokens> Tokens for Punctuated<T, S> {
+    fn tokens<'t>(&'t self, tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>) {
+        list_tokens(&self.list, &self.separators, tokens);
+    }
+
+    fn tokens_mut<'t>(&'t mut self, tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>) {
+        list_tokens_mut(&mut self.list, &mut self.separators, tokens);
+    }
+}
+
+impl<T: Tokens, S: Tokens> Tokens for PunctuatedNonEmpty<T, S> {
+    fn tokens<'t>(&'t self, tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>) {
+        list_tokens(&self.list, &self.separators, tokens);
+        self.trailing.tokens(tokens);
+    }
+
+    fn tokens_mut<'t>(&'t mut self, tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>) {
+        list_tokens_mut(&mut self.list, &mut self.separators, tokens);
+        self.trailing.tokens_mut(tokens);
+    }
+}
+
+/// Writes the items with the separators between them
+fn write_list<T: ToWebIdl, S: ToWebIdl + std::default::Default>(
+    list: &[T],
+    separators: &[S],
+    printer: &mut Printer,
+) {
+    for (i, item) in list.iter().enumerate() {
+        if i > 0 {
+            match separators.get(i - 1) {
+                Some(separator) => separator.write_webidl(printer),
+                None => S::default().write_webidl(printer),
+            }
+        }
+        item.write_webidl(printer);
+    }
+}
+
+impl<T: ToWebIdl, S: ToWebIdl + std::default::Default> ToWebIdl for Punctuated<T, S> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        write_list(&self.list, &self.separators, printer);
+    }
+}
+
+impl<T: ToWebIdl, S: ToWebIdl + std::default::Default> ToWebIdl for PunctuatedNonEmpty<T, S> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        write_list(&self.list, &self.separators, printer);
+        self.trailing.write_webidl(printer);
+    }
+}
+
+/// Represents an identifier
+///
+/// Follows `/_?[A-Za-z][0-9A-Z_a-z-]*/`
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Identifier<'a>(
+    // See https://heycam.github.io/webidl/#idl-names for why the leading
+    // underscore is trimmed
+    pub Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(skip))] pub Span,
+    /// Whether the identifier was escaped with a leading `_` in the source
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub bool,
+    #[cfg_attr(feature = "serde", serde(skip))] pub Option<Box<TokenTrivia>>,
+);
+
+impl<'a> Identifier<'a> {
+    /// Creates the identifier without a source location
+    pub fn new(name: impl Into<Cow<'a, str>>) -> Self {
+        Identifier(name.into(), Span::default(), false, None)
+    }
+}
+
+impl<'a> Parse<'a> for Identifier<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let _guard = state::enter(input);
+        let (rest, (escaped, name)) = expect!(
+            input,
+            "identifier",
+            ws!(do_parse!(
+                escaped: opt!(char!('_')) >>
+                name: recognize!(do_parse!(
+                    take_while1!(|c: char| c.is_ascii_alphabetic()) >>
+                    take_while!(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-') >>
+                    (())
+                )) >>
+                ((escaped.is_some(), name))
+            ))
+        )?;
+        let span = state::token_span(input, rest);
+        Ok((rest, Identifier(Cow::Borrowed(name), span, escaped, None)))
+    }
+}
+
+impl<'a> Spanned for Identifier<'a> {
+    fn span(&self) -> Span {
+        self.1
+    }
+}
+
+impl<'a> IntoOwned for Identifier<'a> {
+    type Owned = Identifier<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Identifier(IntoOwned::into_owned(self.0), self.1, self.2, self.3)
+    }
+}
+
+// `_Foo` and `Foo` name the same thing
+impl<'a> SpanlessEq for Identifier<'a> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+// Identifiers hold raw values and have no children
+impl<'a> Walk<'a> for Identifier<'a> {
+    fn walk<V: Visit<'a> + ?Sized>(&'a self, _: &mut V) {}
+    fn walk_mut<V: VisitMut<'a> + ?Sized>(&mut self, _: &mut V) {}
+}
+
+impl<'a> Tokens for Identifier<'a> {
+    fn tokens<'t>(&'t self, tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>) {
+        tokens.push((self.1, self.3.as_deref()));
+    }
+
+    fn tokens_mut<'t>(&'t mut self, tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>) {
+        tokens.push((self.1, &mut self.3));
+    }
+}
+
+impl<'a> ToWebIdl for Identifier<'a> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        printer.identifier(&self.0, self.2, self.3.as_deref());
+    }
+}
+
+// The delimiters are left out, so only the body is serialized
+#[cfg(feature = "serde")]
+macro_rules! serialize_body {
+    ($($name:ident { $open:ident, $close:ident })*) => {
+        $(
+            impl<T: serde::Serialize> serde::Serialize for $name<T> {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    self.body.serialize(serializer)
+                }
+            }
+
+            impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for $name<T> {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    Ok($name {
+                        $open: ::std::default::Default::default(),
+                        body: T::deserialize(deserializer)?,
+                        $close: ::std::default::Default::default(),
+                        span: Span::default(),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+serialize_body! {
+    Parenthesized { open_paren, close_paren }
+    Bracketed { open_bracket, close_bracket }
+    Braced { open_brace, close_brace }
+    Generics { open_angle, close_angle }
+}
+
+/// Serialized as a sequence of the items, without the separators
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, S> serde::Serialize for Punctuated<T, S> {
+    fn serialize<Z: serde::Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        self.list.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, S> serde::Deserialize<'de> for Punctuated<T, S> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Punctuated {
+            list: serde::Deserialize::deserialize(deserializer)?,
+            separators: Vec::new(),
+            span: Span::default(),
+        })
+    }
+}
+
+/// Serialized as a sequence of the items, without the separators
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, S> serde::Serialize for PunctuatedNonEmpty<T, S> {
+    fn serialize<Z: serde::Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        self.list.serialize(serializer)
+    }
+}
+
+/// Deserialized without a trailing separator, which is not serialized
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, S> serde::Deserialize<'de> for PunctuatedNonEmpty<T, S> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let list: Vec<T> = serde::Deserialize::deserialize(deserializer)?;
+        if list.is_empty() {
+            return Err(serde::de::Error::invalid_length(0, &"at least one item"));
+        }
+        Ok(PunctuatedNonEmpty {
+            list,
+            separators: Vec::new(),
+            trailing: None,
+            span: Span::default(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -155,8 +649,9 @@ mod test {
         Generics<(Identifier, term!(,), Identifier)> =>
             Generics {
                 open_angle: term!(<),
-                body: (Identifier("one"), term!(,), Identifier("two")),
+                body: (Identifier::new("one"), term!(,), Identifier::new("two")),
                 close_angle: term!(>),
+                span: crate::span::Span::default(),
             }
     });
 
@@ -0,0 +1,81 @@
+//! Problems found in definitions which parsed fine
+
+use std::fmt;
+
+use crate::span::Span;
+
+/// A problem found by analysing definitions, located in one of the analysed files
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Short identifier of the check which failed. Ex: `unknown-name`
+    pub rule: &'static str,
+    pub message: String,
+    /// Index of the file in the list of analysed files
+    pub file: usize,
+    pub span: Span,
+    /// Related locations, like the first definition of a duplicated name
+    pub notes: Vec<Note>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A location related to a diagnostic
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Note {
+    pub message: String,
+    /// Index of the file in the list of analysed files
+    pub file: usize,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    /// Creates an error without notes
+    pub fn error(rule: &'static str, message: String, file: usize, span: Span) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            rule,
+            message,
+            file,
+            span,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Creates a warning without notes
+    pub fn warning(rule: &'static str, message: String, file: usize, span: Span) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::error(rule, message, file, span)
+        }
+    }
+
+    /// Adds a note pointing at a related location
+    pub fn with_note(mut self, message: String, file: usize, span: Span) -> Self {
+        self.notes.push(Note {
+            message,
+            file,
+            span,
+        });
+        self
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.rule, self.message)
+    }
+}
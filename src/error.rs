@@ -0,0 +1,325 @@
+//! Errors reported when the input does not follow the WebIDL grammar
+
+use std::fmt;
+
+use crate::span::LineIndex;
+use crate::state::{self, Failure};
+use crate::whitespace::sp;
+use crate::{Definition, Err, ErrorKind, Parse};
+
+/// A parse error with its location, what was found there and what was expected instead
+///
+/// ### Example
+///
+/// ```
+/// use weedle::error::Reason;
+///
+/// let error = weedle::parse("
+///     dictionary A { long x; };
+///     dictionary B { long y };
+/// ").unwrap_err();
+///
+/// assert_eq!((error.line, error.column), (3, 27));
+/// assert_eq!(error.found.as_deref(), Some("}"));
+/// assert!(error.expected.contains(&"`;`".to_string()));
+/// assert_eq!(error.context, vec!["dictionary `B`", "dictionary member `y`"]);
+/// assert_eq!(
+///     error.reason,
+///     Reason::TrailingInput { offset: 35, line: 3, column: 5 }
+/// );
+///
+/// println!("{}", error);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Error {
+    /// Why parsing stopped
+    pub reason: Reason,
+    /// Byte offset of the offending token
+    pub offset: usize,
+    /// 1-based line of the offending token
+    pub line: usize,
+    /// 1-based column of the offending token, in characters
+    pub column: usize,
+    /// The offending token, `None` at the end of the input
+    pub found: Option<String>,
+    /// Descriptions of the tokens which would have been accepted, sorted
+    pub expected: Vec<String>,
+    /// The nodes that were being parsed, outermost first. Ex: ``dictionary `RequestInit` ``
+    pub context: Vec<String>,
+    source_line: String,
+}
+
+/// Why parsing stopped
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Reason {
+    /// The input did not follow the grammar
+    Syntax,
+    /// Some definitions were parsed, but the one starting at `offset` was not, so the input
+    /// from there on was left over. The error describes why that definition failed.
+    TrailingInput {
+        offset: usize,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl Error {
+    /// Builds an error for `source` from what the parser recorded during its last run
+    pub(crate) fn from_nom(source: &str, err: Err<(&str, ErrorKind)>) -> Error {
+        let offset = match err {
+            Err::Error((rest, _)) | Err::Failure((rest, _)) => source.len() - rest.len(),
+            Err::Incomplete(_) => source.len(),
+        };
+        Error::from_failure(source, state::take_failure(), offset)
+    }
+
+    pub(crate) fn from_failure(source: &str, failure: Option<Failure>, offset: usize) -> Error {
+        let failure = failure.unwrap_or_else(|| Failure {
+            offset,
+            ..Failure::default()
+        });
+        let index = LineIndex::new(source);
+        let position = index.line_column(source, failure.offset);
+        let line_start = index.line_start(position.line).unwrap_or(0);
+        let source_line = source[line_start..]
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_string();
+
+        // Only named nodes, definitions and members are worth mentioning; the rest are
+        // types, literals and punctuation
+        let context = failure
+            .frames
+            .iter()
+            .filter(|frame| {
+                frame.name.is_some()
+                    || frame.kind.ends_with("Definition")
+                    || frame.kind.ends_with("Member")
+            })
+            .map(|frame| {
+                let kind = describe_kind(frame.kind);
+                match frame.name {
+                    Some(name) => format!("{} `{}`", kind, name.source_text(source)),
+                    None => kind,
+                }
+            })
+            .collect();
+
+        Error {
+            reason: Reason::Syntax,
+            offset: failure.offset,
+            line: position.line,
+            column: position.column,
+            found: found_token(&source[failure.offset.min(source.len())..]),
+            expected: failure.expected.iter().map(|s| s.to_string()).collect(),
+            context,
+            source_line,
+        }
+    }
+
+    /// Builds an error for the input `Definitions` stopped at, by parsing the definition found
+    /// there once more to learn why it failed
+    pub(crate) fn from_trailing_input(source: &str, remaining: &str) -> Error {
+        let _guard = state::enter(source);
+        let definition = match sp(remaining) {
+            Ok((definition, _)) => definition,
+            Err(_) => remaining,
+        };
+        let offset = source.len() - definition.len();
+        let mut error = match Definition::parse(definition) {
+            Err(err) => Error::from_nom(source, err),
+            Ok(_) => Error::from_failure(source, None, offset),
+        };
+        let position = LineIndex::new(source).line_column(source, offset);
+        error.reason = Reason::TrailingInput {
+            offset,
+            line: position.line,
+            column: position.column,
+        };
+        error
+    }
+
+    /// The full line of source the error points into
+    pub fn source_line(&self) -> &str {
+        &self.source_line
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.expected.split_last() {
+            None => write!(f, "unexpected ")?,
+            Some((last, [])) => write!(f, "expected {}, found ", last)?,
+            Some((last, rest)) => {
+                write!(f, "expected one of {} or {}, found ", rest.join(", "), last)?
+            }
+        }
+        match self.found {
+            Some(ref found) => writeln!(f, "`{}`", found)?,
+            None => writeln!(f, "end of input")?,
+        }
+
+        let gutter = " ".repeat(self.line.to_string().len());
+        let indent: String = self
+            .source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(
+            self.found
+                .as_ref()
+                .map_or(1, |found| found.chars().count().max(1)),
+        );
+        writeln!(
+            f,
+            "{}--> line {}, column {}",
+            gutter, self.line, self.column
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{} | {}{}", gutter, indent, carets)?;
+        for context in &self.context {
+            write!(f, "\n{} = in {}", gutter, context)?;
+        }
+        if let Reason::TrailingInput { line, column, .. } = self.reason {
+            write!(
+                f,
+                "\n{} = note: the definition at line {}, column {} could not be parsed",
+                gutter, line, column
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Turns a type name like `DictionaryMember` or `DictionaryDefinition` into `dictionary member`
+/// or `dictionary`
+fn describe_kind(kind: &str) -> String {
+    let kind = kind.trim_end_matches("Definition");
+    let mut description = String::new();
+    for c in kind.chars() {
+        if c.is_ascii_uppercase() && !description.is_empty() {
+            description.push(' ');
+        }
+        description.push(c.to_ascii_lowercase());
+    }
+    description
+}
+
+/// Extracts the token at the start of `input`
+fn found_token(input: &str) -> Option<String> {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.';
+    let first = input.chars().next()?;
+    let len = if is_word(first) && !input.starts_with("...") {
+        input.find(|c| !is_word(c)).unwrap_or(input.len())
+    } else if first == '"' {
+        input[1..].find('"').map_or(input.len(), |end| end + 2)
+    } else if input.starts_with("...") {
+        3
+    } else {
+        first.len_utf8()
+    };
+    Some(input[..len].to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Definition, Parse};
+
+    fn parse_error(source: &str) -> Error {
+        let _tracking = state::track_failures();
+        match Definition::parse(source) {
+            Err(err) => Error::from_nom(source, err),
+            Ok(_) => panic!("parsing should fail"),
+        }
+    }
+
+    #[test]
+    fn should_report_position_and_context() {
+        let error = parse_error("dictionary RequestInit {\n    long foo = ;\n};");
+
+        assert_eq!(error.offset, 40);
+        assert_eq!((error.line, error.column), (2, 16));
+        assert_eq!(error.found.as_deref(), Some(";"));
+        assert!(error.expected.contains(&"integer".to_string()));
+        assert!(error.expected.contains(&"`null`".to_string()));
+        assert_eq!(
+            error.context,
+            vec!["dictionary `RequestInit`", "dictionary member `foo`"]
+        );
+        assert_eq!(error.source_line(), "    long foo = ;");
+    }
+
+    #[test]
+    fn should_report_end_of_input() {
+        let error = parse_error("interface Foo {");
+
+        assert_eq!(error.found, None);
+        assert!(error.expected.contains(&"`}`".to_string()));
+    }
+
+    #[test]
+    fn should_render_snippet() {
+        let error = parse_error("enum E { \"a\" \"b\" };");
+
+        assert_eq!(
+            error.to_string(),
+            "expected one of `,` or `}`, found `\"b\"`\n \
+             --> line 1, column 14\n  \
+             |\n\
+             1 | enum E { \"a\" \"b\" };\n  \
+             |              ^^^\n  \
+             = in enum `E`"
+        );
+    }
+
+    #[test]
+    fn should_report_trailing_input() {
+        let source = "interface A {};\n\n  dictionary B { long x };";
+        let error = crate::parse(source).unwrap_err();
+
+        assert_eq!(
+            error.reason,
+            Reason::TrailingInput {
+                offset: 19,
+                line: 3,
+                column: 3
+            }
+        );
+        assert_eq!((error.line, error.column), (3, 25));
+        assert_eq!(error.found.as_deref(), Some("}"));
+        assert!(error
+            .to_string()
+            .ends_with("= note: the definition at line 3, column 3 could not be parsed"));
+    }
+
+    #[test]
+    fn should_report_trailing_input_at_start() {
+        let error = crate::parse("  garbage;").unwrap_err();
+
+        assert_eq!(
+            error.reason,
+            Reason::TrailingInput {
+                offset: 2,
+                line: 1,
+                column: 3
+            }
+        );
+        assert_eq!(error.found.as_deref(), Some(";"));
+        assert!(error.expected.contains(&"`includes`".to_string()));
+    }
+
+    #[test]
+    fn should_describe_kinds() {
+        assert_eq!(describe_kind("DictionaryDefinition"), "dictionary");
+        assert_eq!(
+            describe_kind("AttributeInterfaceMember"),
+            "attribute interface member"
+        );
+    }
+}
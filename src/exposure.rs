@@ -0,0 +1,516 @@
+//! Globals in which interfaces, namespaces and their members are exposed
+//!
+//! Interfaces declaring `[Global]` give names to the globals of a set of definitions.
+//! [`Exposures`](struct.Exposures.html) works out the [exposure
+//! set](https://webidl.spec.whatwg.org/#dfn-exposure-set) of interfaces, namespaces and their
+//! members from `[Exposed]`, which names globals or is `*` for all of them, and whether
+//! `[SecureContext]` restricts them to secure contexts. Members without `[Exposed]` inherit
+//! the exposure of the partial definition or mixin declaring them and of their interface.
+//!
+//! Interfaces and namespaces without `[Exposed]` are exposed in `Window`, as in earlier versions
+//! of the specification.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::exposure::Exposures;
+//! use weedle::semantic::Model;
+//!
+//! let parsed = weedle::parse("
+//!     [Global=Window, Exposed=Window] interface Window {};
+//!     [Global=(Worker,DedicatedWorker), Exposed=DedicatedWorker]
+//!     interface DedicatedWorkerGlobalScope {};
+//!     [Exposed=(Window,Worker)] interface Crypto {
+//!         [SecureContext] readonly attribute SubtleCrypto subtle;
+//!         [Exposed=Window] undefined reseed();
+//!     };
+//! ").unwrap();
+//! let model = Model::new(&parsed);
+//! let exposures = Exposures::new(&model);
+//!
+//! let crypto = exposures.interface("Crypto").unwrap();
+//! assert!(crypto.is_exposed_in("DedicatedWorkerGlobalScope"));
+//!
+//! let members = exposures.interface_members("Crypto");
+//! assert!(members[0].1.secure_context);
+//! assert!(!members[1].1.is_exposed_in("DedicatedWorkerGlobalScope"));
+//!
+//! let worker = exposures.filter(&parsed, "Worker");
+//! assert_eq!(worker.len(), 2);
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::attribute::ExtendedAttributeList;
+use crate::known_attribute::{Exposure, KnownAttribute};
+use crate::namespace::NamespaceMember;
+use crate::semantic::{InterfaceMemberRef, Located, Member, Model, Origin, OriginKind};
+use crate::span::Spanned;
+use crate::{Definition, Definitions};
+
+/// Where a definition or member is exposed
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExposureSet<'a> {
+    /// Names of the `[Global]` interfaces
+    pub globals: BTreeSet<&'a str>,
+    /// Set by `[SecureContext]` on the construct or on one containing it
+    pub secure_context: bool,
+}
+
+impl<'a> ExposureSet<'a> {
+    /// Returns `true` if exposed in the global interface named `interface`
+    pub fn is_exposed_in(&self, interface: &str) -> bool {
+        self.globals.contains(interface)
+    }
+}
+
+/// Exposure of the definitions of a [`Model`](../semantic/struct.Model.html)
+#[derive(Clone, Debug)]
+pub struct Exposures<'m, 'a> {
+    model: &'m Model<'a>,
+    /// Global interfaces by global name
+    global_names: BTreeMap<&'a str, BTreeSet<&'a str>>,
+}
+
+impl<'m, 'a> Exposures<'m, 'a> {
+    pub fn new(model: &'m Model<'a>) -> Self {
+        let mut global_names: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for interface in model.interfaces.values() {
+            let attributes = interface
+                .definition
+                .as_ref()
+                .and_then(|d| d.node.attributes.as_ref());
+            let global = match attributes.and_then(|a| a.find("Global")) {
+                Some(global) => global,
+                None => continue,
+            };
+            let names = match KnownAttribute::parse(global) {
+                Ok(KnownAttribute::Global(names)) if names.is_empty() => vec![interface.name],
+                Ok(KnownAttribute::Global(names)) => names,
+                _ => continue,
+            };
+            for name in names {
+                global_names.entry(name).or_default().insert(interface.name);
+            }
+        }
+        Exposures {
+            model,
+            global_names,
+        }
+    }
+
+    /// The `[Global]` interfaces with the global name `name`
+    pub fn globals(&self, name: &str) -> BTreeSet<&'a str> {
+        self.global_names.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Exposure of an interface, `None` if there is no interface definition named `name`
+    pub fn interface(&self, name: &str) -> Option<ExposureSet<'a>> {
+        let definition = self.model.interfaces.get(name)?.definition.as_ref()?;
+        Some(self.definition(definition.node.attributes.as_ref()))
+    }
+
+    /// Exposure of a callback interface, `None` if there is none named `name`
+    pub fn callback_interface(&self, name: &str) -> Option<ExposureSet<'a>> {
+        let callback = self.model.callback_interfaces.get(name)?;
+        Some(self.definition(callback.node.attributes.as_ref()))
+    }
+
+    /// Exposure of a namespace, `None` if there is no namespace definition named `name`
+    pub fn namespace(&self, name: &str) -> Option<ExposureSet<'a>> {
+        let definition = self.model.namespaces.get(name)?.definition.as_ref()?;
+        Some(self.definition(definition.node.attributes.as_ref()))
+    }
+
+    /// Merged members of an interface, mixin members included, with their exposure
+    pub fn interface_members(
+        &self,
+        name: &str,
+    ) -> Vec<(Member<'a, InterfaceMemberRef<'a>>, ExposureSet<'a>)> {
+        let (interface, set) = match (self.model.interfaces.get(name), self.interface(name)) {
+            (Some(interface), Some(set)) => (interface, set),
+            _ => return Vec::new(),
+        };
+        interface
+            .members
+            .iter()
+            .map(|member| {
+                let mut exposure = set.clone();
+                match member.origin.kind {
+                    OriginKind::Definition => {}
+                    OriginKind::Partial => {
+                        let partial = find_partial(&interface.partials, &member.origin);
+                        exposure =
+                            self.narrow(exposure, partial.and_then(|p| p.attributes.as_ref()));
+                    }
+                    OriginKind::Mixin { name, partial } => {
+                        if let Some(mixin) = self.model.mixins.get(name) {
+                            let definition = mixin.definition.as_ref();
+                            let attributes = definition.and_then(|d| d.node.attributes.as_ref());
+                            exposure = self.narrow(exposure, attributes);
+                            if partial {
+                                let partial = find_partial(&mixin.partials, &member.origin);
+                                let attributes = partial.and_then(|p| p.attributes.as_ref());
+                                exposure = self.narrow(exposure, attributes);
+                            }
+                        }
+                    }
+                }
+                (*member, self.narrow(exposure, member.member.attributes()))
+            })
+            .collect()
+    }
+
+    /// Merged members of a namespace with their exposure
+    pub fn namespace_members(
+        &self,
+        name: &str,
+    ) -> Vec<(Member<'a, &'a NamespaceMember<'a>>, ExposureSet<'a>)> {
+        let (namespace, set) = match (self.model.namespaces.get(name), self.namespace(name)) {
+            (Some(namespace), Some(set)) => (namespace, set),
+            _ => return Vec::new(),
+        };
+        namespace
+            .members
+            .iter()
+            .map(|member| {
+                let mut exposure = set.clone();
+                if member.origin.kind == OriginKind::Partial {
+                    let partial = find_partial(&namespace.partials, &member.origin);
+                    exposure = self.narrow(exposure, partial.and_then(|p| p.attributes.as_ref()));
+                }
+                let attributes = match member.member {
+                    NamespaceMember::Const(m) => m.attributes.as_ref(),
+                    NamespaceMember::Attribute(m) => m.attributes.as_ref(),
+                    NamespaceMember::Operation(m) => m.attributes.as_ref(),
+                };
+                (*member, self.narrow(exposure, attributes))
+            })
+            .collect()
+    }
+
+    /// Copies the definitions of one file which are exposed in a global of the global name
+    /// `global`, dropping members which are not
+    ///
+    /// Dictionaries, enums, typedefs and callbacks have no exposure and are always kept.
+    /// Mixins are kept with the members exposed through at least one including interface.
+    pub fn filter(&self, definitions: &[Definition<'a>], global: &str) -> Definitions<'a> {
+        let targets = self.globals(global);
+        let exposed = |set: &ExposureSet<'a>| set.globals.iter().any(|g| targets.contains(g));
+        let mut filtered = Vec::new();
+
+        for definition in definitions {
+            let mut definition = definition.clone();
+            let keep = match &mut definition {
+                Definition::Interface(d) => match self.interface(&d.identifier.0) {
+                    Some(set) => {
+                        d.members.body.retain(|m| {
+                            let member = InterfaceMemberRef::Interface(m);
+                            exposed(&self.narrow(set.clone(), member.attributes()))
+                        });
+                        exposed(&set)
+                    }
+                    None => false,
+                },
+                Definition::PartialInterface(d) => match self.interface(&d.identifier.0) {
+                    Some(set) => {
+                        let set = self.narrow(set, d.attributes.as_ref());
+                        d.members.body.retain(|m| {
+                            let member = InterfaceMemberRef::Interface(m);
+                            exposed(&self.narrow(set.clone(), member.attributes()))
+                        });
+                        exposed(&set)
+                    }
+                    None => false,
+                },
+                Definition::CallbackInterface(d) => match &d.attributes {
+                    Some(attributes) if attributes.has("Exposed") => {
+                        exposed(&self.definition(Some(attributes)))
+                    }
+                    _ => true,
+                },
+                Definition::Namespace(d) => match self.namespace(&d.identifier.0) {
+                    Some(set) => {
+                        d.members.body.retain(|m| {
+                            exposed(&self.narrow(set.clone(), namespace_attributes(m)))
+                        });
+                        exposed(&set)
+                    }
+                    None => false,
+                },
+                Definition::PartialNamespace(d) => match self.namespace(&d.identifier.0) {
+                    Some(set) => {
+                        let set = self.narrow(set, d.attributes.as_ref());
+                        d.members.body.retain(|m| {
+                            exposed(&self.narrow(set.clone(), namespace_attributes(m)))
+                        });
+                        exposed(&set)
+                    }
+                    None => false,
+                },
+                Definition::InterfaceMixin(d) => {
+                    let hosts = self.mixin_exposures(&d.identifier.0, d.attributes.as_ref());
+                    d.members.body.retain(|m| {
+                        let member = InterfaceMemberRef::Mixin(m);
+                        hosts
+                            .iter()
+                            .any(|host| exposed(&self.narrow(host.clone(), member.attributes())))
+                    });
+                    hosts.iter().any(&exposed)
+                }
+                Definition::PartialInterfaceMixin(d) => {
+                    let hosts: Vec<_> = self
+                        .mixin_exposures(&d.identifier.0, None)
+                        .into_iter()
+                        .map(|host| self.narrow(host, d.attributes.as_ref()))
+                        .collect();
+                    d.members.body.retain(|m| {
+                        let member = InterfaceMemberRef::Mixin(m);
+                        hosts
+                            .iter()
+                            .any(|host| exposed(&self.narrow(host.clone(), member.attributes())))
+                    });
+                    hosts.iter().any(&exposed)
+                }
+                Definition::IncludesStatement(d) => self
+                    .interface(&d.lhs_identifier.0)
+                    .is_some_and(|set| exposed(&set)),
+                Definition::Callback(_)
+                | Definition::Dictionary(_)
+                | Definition::PartialDictionary(_)
+                | Definition::Enum(_)
+                | Definition::Typedef(_)
+                | Definition::Implements(_) => true,
+            };
+            if keep {
+                filtered.push(definition);
+            }
+        }
+        filtered
+    }
+
+    /// Exposure of a mixin's definition in each interface including it
+    ///
+    /// `attributes` are those of the mixin definition, `None` to look them up.
+    fn mixin_exposures<'b>(
+        &self,
+        name: &str,
+        attributes: Option<&'b ExtendedAttributeList<'b>>,
+    ) -> Vec<ExposureSet<'a>> {
+        let mixin = self.model.mixins.get(name);
+        let attributes = attributes.or_else(|| {
+            mixin
+                .and_then(|m| m.definition.as_ref())
+                .and_then(|d| d.node.attributes.as_ref())
+        });
+        self.model
+            .interfaces
+            .values()
+            .filter(|i| i.includes.iter().any(|s| s.node.rhs_identifier.0 == name))
+            .filter_map(|i| self.interface(i.name))
+            .map(|set| self.narrow(set, attributes))
+            .collect()
+    }
+
+    /// Exposure of an interface or namespace definition from its own attributes
+    fn definition<'b>(&self, attributes: Option<&'b ExtendedAttributeList<'b>>) -> ExposureSet<'a> {
+        ExposureSet {
+            globals: attributes
+                .and_then(|a| self.exposed(a))
+                .unwrap_or_else(|| self.globals("Window")),
+            secure_context: attributes.is_some_and(|a| a.has("SecureContext")),
+        }
+    }
+
+    /// Restricts the exposure of a containing construct by the attributes of a contained one
+    fn narrow<'b>(
+        &self,
+        mut set: ExposureSet<'a>,
+        attributes: Option<&'b ExtendedAttributeList<'b>>,
+    ) -> ExposureSet<'a> {
+        if let Some(attributes) = attributes {
+            if let Some(globals) = self.exposed(attributes) {
+                set.globals = set.globals.intersection(&globals).copied().collect();
+            }
+            set.secure_context |= attributes.has("SecureContext");
+        }
+        set
+    }
+
+    /// Global interfaces named by `[Exposed]`, `None` without a well formed `[Exposed]`
+    fn exposed<'b>(&self, attributes: &'b ExtendedAttributeList<'b>) -> Option<BTreeSet<&'a str>> {
+        match KnownAttribute::parse(attributes.find("Exposed")?) {
+            Ok(KnownAttribute::Exposed(Exposure::All)) => {
+                Some(self.global_names.values().flatten().copied().collect())
+            }
+            Ok(KnownAttribute::Exposed(Exposure::Globals(names))) => {
+                Some(names.iter().flat_map(|name| self.globals(name)).collect())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The partial definition a member was declared in
+fn find_partial<'a, T: Spanned>(partials: &[Located<&'a T>], origin: &Origin) -> Option<&'a T> {
+    partials
+        .iter()
+        .find(|p| p.file == origin.file && p.node.span().start == origin.span.start)
+        .map(|p| p.node)
+}
+
+fn namespace_attributes<'a>(
+    member: &'a NamespaceMember<'a>,
+) -> Option<&'a ExtendedAttributeList<'a>> {
+    match member {
+        NamespaceMember::Const(m) => m.attributes.as_ref(),
+        NamespaceMember::Attribute(m) => m.attributes.as_ref(),
+        NamespaceMember::Operation(m) => m.attributes.as_ref(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SOURCE: &str = "
+        [Global=Window, Exposed=Window] interface Window {};
+        [Global=(Worker,DedicatedWorker), Exposed=DedicatedWorker]
+        interface DedicatedWorkerGlobalScope {};
+        [Global=(Worker,ServiceWorker), Exposed=ServiceWorker]
+        interface ServiceWorkerGlobalScope {};
+        [Global=(Worklet,AudioWorklet), Exposed=AudioWorklet]
+        interface AudioWorkletGlobalScope {};
+
+        [Exposed=*] interface Event {
+            readonly attribute DOMString type;
+            [Exposed=(Window,ServiceWorker)] readonly attribute boolean trusted;
+        };
+        [Exposed=Worker, SecureContext] partial interface Event {
+            undefined waitUntil(Promise<any> f);
+        };
+        [Exposed=Window] interface mixin Timers { long setTimeout(); };
+        [Exposed=(Window,Worker)] interface Scope {};
+        Scope includes Timers;
+        [Exposed=Worker] namespace Console { undefined log(); };
+        dictionary Init {};
+    ";
+
+    fn names(set: &ExposureSet) -> Vec<&'static str> {
+        let mut names: Vec<_> = set
+            .globals
+            .iter()
+            .map(|g| match *g {
+                "Window" => "W",
+                "DedicatedWorkerGlobalScope" => "D",
+                "ServiceWorkerGlobalScope" => "S",
+                "AudioWorkletGlobalScope" => "A",
+                _ => "?",
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn should_compute_exposure_sets() {
+        let parsed = crate::parse(SOURCE).unwrap();
+        let model = Model::new(&parsed);
+        let exposures = Exposures::new(&model);
+
+        assert_eq!(
+            exposures.globals("Worker").into_iter().collect::<Vec<_>>(),
+            ["DedicatedWorkerGlobalScope", "ServiceWorkerGlobalScope"]
+        );
+        assert_eq!(
+            names(&exposures.interface("Event").unwrap()),
+            ["A", "D", "S", "W"]
+        );
+        assert_eq!(names(&exposures.namespace("Console").unwrap()), ["D", "S"]);
+
+        let members = exposures.interface_members("Event");
+        let members: Vec<_> = members
+            .iter()
+            .map(|(m, set)| {
+                (
+                    m.member.identifier().unwrap(),
+                    names(set),
+                    set.secure_context,
+                )
+            })
+            .collect();
+        assert_eq!(
+            members,
+            [
+                ("type", vec!["A", "D", "S", "W"], false),
+                ("trusted", vec!["S", "W"], false),
+                ("waitUntil", vec!["D", "S"], true),
+            ]
+        );
+
+        let members = exposures.interface_members("Scope");
+        assert_eq!(names(&members[0].1), ["W"]);
+        let members = exposures.namespace_members("Console");
+        assert_eq!(names(&members[0].1), ["D", "S"]);
+    }
+
+    #[test]
+    fn should_filter_definitions_by_global() {
+        let parsed = crate::parse(SOURCE).unwrap();
+        let model = Model::new(&parsed);
+        let exposures = Exposures::new(&model);
+
+        let identifiers = |definitions: &[Definition]| -> Vec<String> {
+            definitions
+                .iter()
+                .map(|d| match d {
+                    Definition::Interface(d) => {
+                        format!("{}:{}", d.identifier.0, d.members.body.len())
+                    }
+                    Definition::PartialInterface(d) => format!("partial {}", d.identifier.0),
+                    Definition::InterfaceMixin(d) => {
+                        format!("{}:{}", d.identifier.0, d.members.body.len())
+                    }
+                    Definition::Namespace(d) => d.identifier.0.to_string(),
+                    Definition::Dictionary(d) => d.identifier.0.to_string(),
+                    Definition::IncludesStatement(d) => format!("{} includes", d.lhs_identifier.0),
+                    _ => unreachable!(),
+                })
+                .collect()
+        };
+
+        let worker = exposures.filter(&parsed, "Worker");
+        assert_eq!(
+            identifiers(&worker),
+            [
+                "DedicatedWorkerGlobalScope:0",
+                "ServiceWorkerGlobalScope:0",
+                "Event:2",
+                "partial Event",
+                "Scope:0",
+                "Scope includes",
+                "Console",
+                "Init",
+            ]
+        );
+
+        let worklet = exposures.filter(&parsed, "AudioWorklet");
+        assert_eq!(
+            identifiers(&worklet),
+            ["AudioWorkletGlobalScope:0", "Event:1", "Init"]
+        );
+
+        let window = exposures.filter(&parsed, "Window");
+        assert_eq!(
+            identifiers(&window),
+            [
+                "Window:0",
+                "Event:2",
+                "Timers:1",
+                "Scope:0",
+                "Scope includes",
+                "Init"
+            ]
+        );
+    }
+}
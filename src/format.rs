@@ -0,0 +1,289 @@
+//! Formatting WebIDL sources in one canonical style
+//!
+//! Definitions are printed like [`ToWebIdl`](../print/trait.ToWebIdl.html) does: four spaces
+//! of indentation, one member per line and normalized spacing, e.g. in extended attribute
+//! lists. Comments are kept, and argument lists of lines longer than
+//! [`max_width`](struct.FormatOptions.html#structfield.max_width) are split into one argument
+//! per line. The `weedle-fmt` binary applies this to files.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::format::{format, FormatOptions, MemberOrder};
+//!
+//! let source = "
+//! dictionary   Options{
+//!   // Defaults to 0
+//!   long   b;
+//!   required   DOMString a;};
+//! ";
+//! let options = FormatOptions {
+//!     member_order: MemberOrder::Sorted,
+//!     ..FormatOptions::default()
+//! };
+//!
+//! assert_eq!(
+//!     format(source, &options).unwrap(),
+//!     "dictionary Options {
+//!     required DOMString a;
+//!     // Defaults to 0
+//!     long b;
+//! };
+//! "
+//! );
+//! ```
+
+use crate::dictionary::DictionaryMember;
+use crate::interface::InterfaceMember;
+use crate::mixin::MixinMember;
+use crate::namespace::NamespaceMember;
+use crate::print::{Printer, ToWebIdl};
+use crate::{Definition, Definitions, Error};
+
+/// How to format a source
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FormatOptions {
+    /// Lines with an argument list which are longer than this many characters get one argument
+    /// per line
+    pub max_width: usize,
+    pub member_order: MemberOrder,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            max_width: 100,
+            member_order: MemberOrder::Preserve,
+        }
+    }
+}
+
+/// Order of the members of interfaces, mixins, namespaces and dictionaries
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemberOrder {
+    /// Keep members in source order
+    Preserve,
+    /// Group members by kind, in the order constants, constructors, attributes, operations
+    /// and then the rest, and sort each group by name. Overloads keep their order.
+    Sorted,
+}
+
+/// Parses and formats `source`, keeping its comments
+// Errors are only built once per parse, so their size does not matter
+#[allow(clippy::result_large_err)]
+pub fn format(source: &str, options: &FormatOptions) -> Result<String, Error> {
+    let (mut definitions, end) = crate::parse_with_trivia(source)?;
+    if options.member_order == MemberOrder::Sorted {
+        sort_members(&mut definitions);
+    }
+    let mut printer = Printer::with_comments().max_width(options.max_width);
+    definitions.write_webidl(&mut printer);
+    printer.trivia(end);
+    Ok(printer.finish())
+}
+
+/// Formats definitions, which may have been built or changed after parsing
+pub fn format_definitions(definitions: &Definitions<'_>, options: &FormatOptions) -> String {
+    let mut printer = Printer::new().max_width(options.max_width);
+    if options.member_order == MemberOrder::Sorted {
+        let mut definitions = definitions.clone();
+        sort_members(&mut definitions);
+        definitions.write_webidl(&mut printer);
+    } else {
+        definitions.write_webidl(&mut printer);
+    }
+    printer.finish()
+}
+
+/// Sorts the members of every definition as described by
+/// [`MemberOrder::Sorted`](enum.MemberOrder.html#variant.Sorted)
+pub fn sort_members(definitions: &mut Definitions<'_>) {
+    for definition in definitions {
+        match definition {
+            Definition::CallbackInterface(d) => sort_interface_members(&mut d.members.body),
+            Definition::Interface(d) => sort_interface_members(&mut d.members.body),
+            Definition::PartialInterface(d) => sort_interface_members(&mut d.members.body),
+            Definition::InterfaceMixin(d) => sort_mixin_members(&mut d.members.body),
+            Definition::PartialInterfaceMixin(d) => sort_mixin_members(&mut d.members.body),
+            Definition::Namespace(d) => sort_namespace_members(&mut d.members.body),
+            Definition::PartialNamespace(d) => sort_namespace_members(&mut d.members.body),
+            Definition::Dictionary(d) => sort_dictionary_members(&mut d.members.body),
+            Definition::PartialDictionary(d) => sort_dictionary_members(&mut d.members.body),
+            Definition::Callback(_)
+            | Definition::Enum(_)
+            | Definition::Typedef(_)
+            | Definition::IncludesStatement(_)
+            | Definition::Implements(_) => {}
+        }
+    }
+}
+
+fn sort_dictionary_members(members: &mut [DictionaryMember<'_>]) {
+    members.sort_by(|a, b| a.identifier.0.cmp(&b.identifier.0));
+}
+
+fn sort_interface_members(members: &mut [InterfaceMember<'_>]) {
+    fn key<'m>(member: &'m InterfaceMember<'_>) -> (u8, &'m str) {
+        match member {
+            InterfaceMember::Const(m) => (0, &m.identifier.0),
+            InterfaceMember::Constructor(_) => (1, ""),
+            InterfaceMember::Attribute(m) => (2, &m.identifier.0),
+            InterfaceMember::Operation(m) => (3, m.identifier.as_ref().map_or("", |id| &id.0)),
+            InterfaceMember::Stringifier(_)
+            | InterfaceMember::Jsonifier(_)
+            | InterfaceMember::Serializer(_) => (4, ""),
+            InterfaceMember::Iterable(_)
+            | InterfaceMember::AsyncIterable(_)
+            | InterfaceMember::Maplike(_)
+            | InterfaceMember::Setlike(_) => (5, ""),
+        }
+    }
+    members.sort_by(|a, b| key(a).cmp(&key(b)));
+}
+
+fn sort_mixin_members(members: &mut [MixinMember<'_>]) {
+    fn key<'m>(member: &'m MixinMember<'_>) -> (u8, &'m str) {
+        match member {
+            MixinMember::Const(m) => (0, &m.identifier.0),
+            MixinMember::Attribute(m) => (2, &m.identifier.0),
+            MixinMember::Operation(m) => (3, m.identifier.as_ref().map_or("", |id| &id.0)),
+            MixinMember::Stringifier(_) => (4, ""),
+        }
+    }
+    members.sort_by(|a, b| key(a).cmp(&key(b)));
+}
+
+fn sort_namespace_members(members: &mut [NamespaceMember<'_>]) {
+    fn key<'m>(member: &'m NamespaceMember<'_>) -> (u8, &'m str) {
+        match member {
+            NamespaceMember::Const(m) => (0, &m.identifier.0),
+            NamespaceMember::Attribute(m) => (2, &m.identifier.0),
+            NamespaceMember::Operation(m) => (3, m.identifier.as_ref().map_or("", |id| &id.0)),
+        }
+    }
+    members.sort_by(|a, b| key(a).cmp(&key(b)));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn format_default(source: &str) -> String {
+        format(source, &FormatOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn should_normalize_layout() {
+        assert_eq!(
+            format_default(
+                "[ Exposed=( Window,Worker ) ,SecureContext]interface Foo:Bar{attribute long x;\
+                 undefined f ( long a,long b ) ;};enum E{\"a\",\"b\"};"
+            ),
+            "[Exposed=(Window, Worker), SecureContext]\n\
+             interface Foo : Bar {\n    \
+             attribute long x;\n    \
+             undefined f(long a, long b);\n\
+             };\n\
+             \n\
+             enum E {\n    \
+             \"a\", \"b\"\n\
+             };\n"
+        );
+    }
+
+    #[test]
+    fn should_keep_comments_and_blank_lines() {
+        let source = "// Header
+
+/* Doc */
+interface Foo { // Opening
+    attribute long x; // Trailing
+
+
+    // Second group
+    attribute long y;
+    // Dangling
+};
+// Footer
+";
+        assert_eq!(
+            format_default(source),
+            "// Header
+
+/* Doc */
+interface Foo { // Opening
+    attribute long x; // Trailing
+
+    // Second group
+    attribute long y;
+    // Dangling
+};
+// Footer
+"
+        );
+    }
+
+    #[test]
+    fn should_break_after_line_comments() {
+        assert_eq!(
+            format_default("typedef (long // first\n or DOMString) T;"),
+            "typedef (long // first\n    or DOMString) T;\n"
+        );
+    }
+
+    #[test]
+    fn should_wrap_long_argument_lists() {
+        let options = FormatOptions {
+            max_width: 40,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format(
+                "interface A { undefined f(long first, optional (long or DOMString) second); \
+                 undefined g(long x); };",
+                &options
+            )
+            .unwrap(),
+            "interface A {\n    \
+             undefined f(\n        \
+             long first,\n        \
+             optional (long or DOMString) second\n    \
+             );\n    \
+             undefined g(long x);\n\
+             };\n"
+        );
+    }
+
+    #[test]
+    fn should_sort_members() {
+        let (_, parsed) = crate::Parse::parse(
+            "interface A { undefined b(); attribute long z; undefined a(long x); \
+             undefined a(); const long C = 1; constructor(); iterable<long>; };",
+        )
+        .unwrap();
+        let options = FormatOptions {
+            member_order: MemberOrder::Sorted,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format_definitions(&vec![parsed], &options),
+            "interface A {\n    \
+             const long C = 1;\n    \
+             constructor();\n    \
+             attribute long z;\n    \
+             undefined a(long x);\n    \
+             undefined a();\n    \
+             undefined b();\n    \
+             iterable<long>;\n\
+             };\n"
+        );
+    }
+
+    #[test]
+    fn should_be_idempotent() {
+        let source =
+            "interface A { // a\n  attribute long x; /* b */ };\n\n\n// c\ntypedef long L;";
+        let once = format_default(source);
+        assert_eq!(format_default(&once), once);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::argument::ArgumentList;
 use crate::attribute::ExtendedAttributeList;
-use crate::common::{Generics, Identifier, Parenthesized};
+use crate::common::{Braced, Bracketed, Generics, Identifier, Parenthesized, Punctuated};
 use crate::literal::ConstValue;
 use crate::types::{AttributedType, ConstType, ReturnType};
 
@@ -9,7 +9,6 @@ pub type InterfaceMembers<'a> = Vec<InterfaceMember<'a>>;
 
 ast_types! {
     /// Parses inheritance clause `: identifier`
-    #[derive(Copy)]
     struct Inheritance<'a> {
         colon: term!(:),
         identifier: Identifier<'a>,
@@ -75,20 +74,20 @@ ast_types! {
                 semi_colon: term!(;),
             }),
         }),
-        /// Parses an async iterable declaration `[attributes]? async (iterable<attributedtype> | iterable<attributedtype, attributedtype>) (( args ))? ;`
+        /// Parses an async iterable declaration `[attributes]? async_iterable (<attributedtype> | <attributedtype, attributedtype>) (( args ))? ;`
         AsyncIterable(enum AsyncIterableInterfaceMember<'a> {
-            /// Parses an async iterable declaration `[attributes]? async iterable<attributedtype> (( args ))? ;`
+            /// Parses an async iterable declaration `[attributes]? async_iterable<attributedtype> (( args ))? ;`
             Single(struct SingleTypedAsyncIterable<'a> {
                 attributes: Option<ExtendedAttributeList<'a>>,
-                async_iterable: (term!(async), term!(iterable)),
+                async_iterable: AsyncIterableKeyword,
                 generics: Generics<AttributedType<'a>>,
                 args: Option<Parenthesized<ArgumentList<'a>>>,
                 semi_colon: term!(;),
             }),
-            /// Parses an async iterable declaration `[attributes]? async iterable<attributedtype, attributedtype> (( args ))? ;`
+            /// Parses an async iterable declaration `[attributes]? async_iterable<attributedtype, attributedtype> (( args ))? ;`
             Double(struct DoubleTypedAsyncIterable<'a> {
                 attributes: Option<ExtendedAttributeList<'a>>,
-                async_iterable: (term!(async), term!(iterable)),
+                async_iterable: AsyncIterableKeyword,
                 generics: Generics<(AttributedType<'a>, term!(,), AttributedType<'a>)>,
                 args: Option<Parenthesized<ArgumentList<'a>>>,
                 semi_colon: term!(;),
@@ -116,10 +115,36 @@ ast_types! {
             stringifier: term!(stringifier),
             semi_colon: term!(;),
         }),
+        /// Parses `jsonifier;` of the Gecko and Chromium dialects
+        #[derive(Default)]
+        Jsonifier(struct JsonifierMember<'a> {
+            attributes: Option<ExtendedAttributeList<'a>>,
+            jsonifier: term!(jsonifier),
+            semi_colon: term!(;),
+        }),
+        /// Parses `serializer (= pattern)?;` of the Gecko and Chromium dialects
+        Serializer(struct SerializerMember<'a> {
+            attributes: Option<ExtendedAttributeList<'a>>,
+            serializer: term!(serializer),
+            pattern: Option<(term!(=), SerializationPattern<'a>)>,
+            semi_colon: term!(;),
+        }),
+    }
+
+    /// Parses `{ identifiers }`, `[ identifiers ]` or `identifier` after `serializer =`
+    enum SerializationPattern<'a> {
+        Map(Braced<Punctuated<Identifier<'a>, term!(,)>>),
+        List(Bracketed<Punctuated<Identifier<'a>, term!(,)>>),
+        Identifier(Identifier<'a>),
+    }
+
+    /// Parses `async_iterable`, or the legacy `async iterable` it replaced
+    enum AsyncIterableKeyword {
+        AsyncIterable(term!(async_iterable)),
+        Legacy((term!(async), term!(iterable))),
     }
 
-    /// Parses one of the special keyword `getter|setter|deleter`
-    #[derive(Copy)]
+    /// Parses one of the special keyword `getter|setter|deleter`, or the legacy `legacycaller`
     enum Special {
         Getter(term!(getter)),
         Setter(term!(setter)),
@@ -128,7 +153,6 @@ ast_types! {
     }
 
     /// Parses `stringifier|inherit|static`
-    #[derive(Copy)]
     enum StringifierOrInheritOrStatic {
         Stringifier(term!(stringifier)),
         Inherit(term!(inherit)),
@@ -136,16 +160,30 @@ ast_types! {
     }
 
     /// Parses `stringifier|static`
-    #[derive(Copy)]
     enum StringifierOrStatic {
         Stringifier(term!(stringifier)),
         Static(term!(static)),
     }
 }
 
+impl AsyncIterableKeyword {
+    /// Returns `true` if written as the legacy `async iterable`
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, AsyncIterableKeyword::Legacy(_))
+    }
+}
+
+impl Special {
+    /// Returns `true` for the legacy `legacycaller`
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, Special::LegacyCaller(_))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::span::SpanlessEq;
     use crate::Parse;
 
     test!(should_parse_stringifier_member { "stringifier;" =>
@@ -153,6 +191,31 @@ mod test {
         StringifierMember;
     });
 
+    #[test]
+    fn should_parse_dialect_members() {
+        use crate::{Dialect, ParseOptions};
+
+        assert!(JsonifierMember::parse("jsonifier;").is_err());
+
+        let _options = crate::state::use_options(ParseOptions::new().dialect(Dialect::Chromium));
+        let (rest, member) = JsonifierMember::parse("[Default] jsonifier;").unwrap();
+        assert_eq!(rest, "");
+        assert!(member.attributes.is_some());
+
+        let (_, member) = SerializerMember::parse("serializer;").unwrap();
+        assert!(member.pattern.is_none());
+        let (_, member) = SerializerMember::parse("serializer = { attribute, inherit };").unwrap();
+        assert!(matches!(
+            member.pattern,
+            Some((_, SerializationPattern::Map(_)))
+        ));
+    }
+
+    test!(should_parse_serialization_pattern_list { "[name, getter]" =>
+        "";
+        SerializationPattern;
+    });
+
     test!(should_parse_stringifier_or_static { "static" =>
         "";
         StringifierOrStatic;
@@ -167,21 +230,21 @@ mod test {
         "";
         SetlikeInterfaceMember;
         attributes.is_none();
-        readonly == Some(term!(readonly));
+        readonly.spanless_eq(&Some(term!(readonly)));
     });
 
     test!(should_parse_maplike_interface_member { "readonly maplike<long, short>;" =>
         "";
         MaplikeInterfaceMember;
         attributes.is_none();
-        readonly == Some(term!(readonly));
+        readonly.spanless_eq(&Some(term!(readonly)));
     });
 
     test!(should_parse_attribute_interface_member { "readonly attribute unsigned long width;" =>
         "";
         AttributeInterfaceMember;
         attributes.is_none();
-        readonly == Some(term!(readonly));
+        readonly.spanless_eq(&Some(term!(readonly)));
         identifier.0 == "width";
     });
 
@@ -223,6 +286,28 @@ mod test {
         SingleTypedAsyncIterable;
         attributes.is_none();
         args.is_some();
+        async_iterable.is_legacy();
+    });
+
+    test!(should_parse_single_typed_async_iterable_keyword { "async_iterable<long>;" =>
+        "";
+        SingleTypedAsyncIterable;
+        attributes.is_none();
+        args.is_none();
+        async_iterable.spanless_eq(&AsyncIterableKeyword::AsyncIterable(term!(async_iterable)));
+    });
+
+    test!(should_parse_double_typed_async_iterable_keyword { "async_iterable<DOMString, long>(long a);" =>
+        "";
+        DoubleTypedAsyncIterable;
+        args.is_some();
+        async_iterable.spanless_eq(&AsyncIterableKeyword::AsyncIterable(term!(async_iterable)));
+    });
+
+    test!(should_parse_legacy_caller_special { "legacycaller" =>
+        "";
+        Special;
+        is_legacy();
     });
 
     test!(should_parse_constructor_interface_member { "constructor(long a);" =>
@@ -246,4 +331,11 @@ mod test {
         attributes.is_none();
         identifier.0 == "name";
     });
+
+    #[test]
+    fn should_parse_bigint_const_member() {
+        let (rest, member) = ConstMember::parse("const bigint MAX = 9007199254740991;").unwrap();
+        assert_eq!(rest, "");
+        assert!(matches!(member.const_type, ConstType::Bigint(_)));
+    }
 }
@@ -0,0 +1,609 @@
+//! Standard extended attributes
+//!
+//! [`KnownAttribute`](enum.KnownAttribute.html) gives a typed value to each extended attribute
+//! defined by the WebIDL and HTML specifications, and tells the constructs it can annotate.
+//! Attributes which were renamed by the specification are recognised by their former names as
+//! well. Ex: `[TreatNullAs=EmptyString]` for `[LegacyNullToEmptyString]`
+//!
+//! [`check`](fn.check.html) reports attributes which are unknown, have the wrong form or
+//! annotate the wrong construct.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::known_attribute::{Exposure, KnownAttribute, Placement};
+//! use weedle::Definition;
+//!
+//! let parsed = weedle::parse("[Exposed=(Window,Worker)] interface Request {};").unwrap();
+//! let attributes = match &parsed[0] {
+//!     Definition::Interface(interface) => interface.attributes.as_ref().unwrap(),
+//!     _ => unreachable!(),
+//! };
+//!
+//! let exposed = KnownAttribute::parse(attributes.find("Exposed").unwrap()).unwrap();
+//! assert_eq!(exposed, KnownAttribute::Exposed(Exposure::Globals(vec!["Window", "Worker"])));
+//! assert!(exposed.allowed_on(Placement::Interface));
+//! assert!(!exposed.allowed_on(Placement::Argument));
+//! ```
+
+use std::fmt;
+
+use crate::argument::{ArgumentList, SingleArgument, VariadicArgument};
+use crate::attribute::{ExtendedAttribute, ExtendedAttributeList, IdentifierOrString};
+use crate::diagnostic::Diagnostic;
+use crate::dictionary::DictionaryMember;
+use crate::interface::{
+    AsyncIterableInterfaceMember, AttributeInterfaceMember, ConstMember,
+    ConstructorInterfaceMember, IterableInterfaceMember, JsonifierMember, MaplikeInterfaceMember,
+    OperationInterfaceMember, SerializerMember, SetlikeInterfaceMember, StringifierMember,
+};
+use crate::mixin::{AttributeMixinMember, OperationMixinMember};
+use crate::namespace::{AttributeNamespaceMember, ConstNamespaceMember, OperationNamespaceMember};
+use crate::span::Spanned;
+use crate::types::{AttributedNonAnyType, AttributedType};
+use crate::visit::{Accept, Visit, Walk};
+use crate::{
+    CallbackDefinition, CallbackInterfaceDefinition, Definition, DictionaryDefinition,
+    EnumDefinition, ImplementsDefinition, IncludesStatementDefinition, InterfaceDefinition,
+    InterfaceMixinDefinition, NamespaceDefinition, PartialDictionaryDefinition,
+    PartialInterfaceDefinition, PartialInterfaceMixinDefinition, PartialNamespaceDefinition,
+    TypedefDefinition,
+};
+
+/// A standard extended attribute with its arguments
+#[derive(Clone, Debug, PartialEq)]
+pub enum KnownAttribute<'a> {
+    AllowResizable,
+    AllowShared,
+    CEReactions,
+    Clamp,
+    /// The former syntax of constructors. Ex: `[Constructor(DOMString url)]`
+    Constructor(Option<&'a ArgumentList<'a>>),
+    CrossOriginIsolated,
+    Default,
+    EnforceRange,
+    Exposed(Exposure<'a>),
+    /// Names of the global, empty for `[Global]`, which uses the name of the interface
+    Global(Vec<&'a str>),
+    HTMLConstructor,
+    LegacyFactoryFunction {
+        name: &'a str,
+        arguments: &'a ArgumentList<'a>,
+    },
+    LegacyLenientSetter,
+    LegacyLenientThis,
+    LegacyNamespace(&'a str),
+    LegacyNoInterfaceObject,
+    LegacyNullToEmptyString,
+    LegacyOverrideBuiltIns,
+    LegacySerializable,
+    LegacyTreatNonObjectAsNull,
+    LegacyUnenumerableNamedProperties,
+    LegacyUnforgeable,
+    LegacyWindowAlias(Vec<&'a str>),
+    NewObject,
+    PutForwards(&'a str),
+    Replaceable,
+    SameObject,
+    SecureContext,
+    Serializable,
+    StringContext(&'a str),
+    Transferable,
+    Unscopable,
+}
+
+/// The globals named by `[Exposed]`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Exposure<'a> {
+    /// `Exposed=*`
+    All,
+    /// `Exposed=Window` or `Exposed=(Window,Worker)`
+    Globals(Vec<&'a str>),
+}
+
+/// Constructs an extended attribute can annotate, partial definitions included
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Placement {
+    Interface,
+    CallbackInterface,
+    InterfaceMixin,
+    Namespace,
+    Dictionary,
+    Enum,
+    Typedef,
+    Callback,
+    /// `includes` and `implements` statements
+    Includes,
+    Constant,
+    Attribute,
+    Operation,
+    Constructor,
+    /// `iterable`, `async iterable`, `maplike` and `setlike` declarations
+    Iterable,
+    Argument,
+    DictionaryMember,
+    Type,
+}
+
+/// Reasons an extended attribute has no typed value
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttributeError<'a> {
+    Unknown(&'a str),
+    /// The attribute is known, but its arguments have the wrong form
+    Malformed {
+        name: &'a str,
+        /// Description of the accepted forms. Ex: `an identifier`
+        expected: &'static str,
+    },
+}
+
+impl<'a> fmt::Display for AttributeError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttributeError::Unknown(name) => write!(f, "unknown extended attribute `{}`", name),
+            AttributeError::Malformed { name, expected } => {
+                write!(f, "`[{}]` takes {}", name, expected)
+            }
+        }
+    }
+}
+
+/// Identifier on the right hand side, if that is the form of the attribute
+fn ident<'a>(attribute: &'a ExtendedAttribute<'a>) -> Option<&'a str> {
+    match attribute {
+        ExtendedAttribute::Ident(a) => match &a.rhs {
+            IdentifierOrString::Identifier(identifier) => Some(&identifier.0),
+            IdentifierOrString::String(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Identifiers on the right hand side, if that is the form of the attribute
+fn ident_list<'a>(attribute: &'a ExtendedAttribute<'a>) -> Option<Vec<&'a str>> {
+    match attribute {
+        ExtendedAttribute::IdentList(a) => Some(a.list.body.list.iter().map(|i| &*i.0).collect()),
+        _ => ident(attribute).map(|identifier| vec![identifier]),
+    }
+}
+
+impl<'a> KnownAttribute<'a> {
+    /// Gives a typed value to a parsed attribute
+    pub fn parse(attribute: &'a ExtendedAttribute<'a>) -> Result<Self, AttributeError<'a>> {
+        use self::KnownAttribute::*;
+
+        let name = &*attribute.identifier().0;
+        let malformed = |expected| AttributeError::Malformed { name, expected };
+        let no_args = |value| match attribute {
+            ExtendedAttribute::NoArgs(_) => Ok(value),
+            _ => Err(malformed("no arguments")),
+        };
+        let identifier = |value: fn(&'a str) -> Self| match ident(attribute) {
+            Some(identifier) => Ok(value(identifier)),
+            None => Err(malformed("an identifier")),
+        };
+        let identifiers = |value: fn(Vec<&'a str>) -> Self| match ident_list(attribute) {
+            Some(identifiers) => Ok(value(identifiers)),
+            None => Err(malformed("an identifier or an identifier list")),
+        };
+
+        match name {
+            "AllowResizable" => no_args(AllowResizable),
+            "AllowShared" => no_args(AllowShared),
+            "CEReactions" => no_args(CEReactions),
+            "Clamp" => no_args(Clamp),
+            "Constructor" => match attribute {
+                ExtendedAttribute::NoArgs(_) => Ok(Constructor(None)),
+                ExtendedAttribute::ArgList(a) => Ok(Constructor(Some(&a.args.body))),
+                _ => Err(malformed("no arguments or an argument list")),
+            },
+            "CrossOriginIsolated" => no_args(CrossOriginIsolated),
+            "Default" => no_args(Default),
+            "EnforceRange" => no_args(EnforceRange),
+            "Exposed" => match attribute {
+                ExtendedAttribute::Wildcard(_) => Ok(Exposed(Exposure::All)),
+                _ => match ident_list(attribute) {
+                    Some(globals) => Ok(Exposed(Exposure::Globals(globals))),
+                    None => Err(malformed("`*`, an identifier or an identifier list")),
+                },
+            },
+            "Global" => match attribute {
+                ExtendedAttribute::NoArgs(_) => Ok(Global(Vec::new())),
+                _ => identifiers(Global),
+            },
+            "HTMLConstructor" => no_args(HTMLConstructor),
+            "LegacyFactoryFunction" | "NamedConstructor" => match attribute {
+                ExtendedAttribute::NamedArgList(a) => Ok(LegacyFactoryFunction {
+                    name: &a.rhs_identifier.0,
+                    arguments: &a.args.body,
+                }),
+                _ => Err(malformed("a named argument list")),
+            },
+            "LegacyLenientSetter" | "LenientSetter" => no_args(LegacyLenientSetter),
+            "LegacyLenientThis" | "LenientThis" => no_args(LegacyLenientThis),
+            "LegacyNamespace" => identifier(LegacyNamespace),
+            "LegacyNoInterfaceObject" | "NoInterfaceObject" => no_args(LegacyNoInterfaceObject),
+            "LegacyNullToEmptyString" => no_args(LegacyNullToEmptyString),
+            "TreatNullAs" => match ident(attribute) {
+                Some("EmptyString") => Ok(LegacyNullToEmptyString),
+                _ => Err(malformed("`EmptyString`")),
+            },
+            "LegacyOverrideBuiltIns" | "OverrideBuiltins" => no_args(LegacyOverrideBuiltIns),
+            "LegacySerializable" => no_args(LegacySerializable),
+            "LegacyTreatNonObjectAsNull" | "TreatNonObjectAsNull" => {
+                no_args(LegacyTreatNonObjectAsNull)
+            }
+            "LegacyUnenumerableNamedProperties" => no_args(LegacyUnenumerableNamedProperties),
+            "LegacyUnforgeable" | "Unforgeable" => no_args(LegacyUnforgeable),
+            "LegacyWindowAlias" => identifiers(LegacyWindowAlias),
+            "NewObject" => no_args(NewObject),
+            "PutForwards" => identifier(PutForwards),
+            "Replaceable" => no_args(Replaceable),
+            "SameObject" => no_args(SameObject),
+            "SecureContext" => no_args(SecureContext),
+            "Serializable" => no_args(Serializable),
+            "StringContext" => identifier(StringContext),
+            "Transferable" => no_args(Transferable),
+            "Unscopable" => no_args(Unscopable),
+            _ => Err(AttributeError::Unknown(name)),
+        }
+    }
+
+    /// Name of the attribute as the specification spells it today
+    pub fn name(&self) -> &'static str {
+        use self::KnownAttribute::*;
+
+        match self {
+            AllowResizable => "AllowResizable",
+            AllowShared => "AllowShared",
+            CEReactions => "CEReactions",
+            Clamp => "Clamp",
+            Constructor(_) => "Constructor",
+            CrossOriginIsolated => "CrossOriginIsolated",
+            Default => "Default",
+            EnforceRange => "EnforceRange",
+            Exposed(_) => "Exposed",
+            Global(_) => "Global",
+            HTMLConstructor => "HTMLConstructor",
+            LegacyFactoryFunction { .. } => "LegacyFactoryFunction",
+            LegacyLenientSetter => "LegacyLenientSetter",
+            LegacyLenientThis => "LegacyLenientThis",
+            LegacyNamespace(_) => "LegacyNamespace",
+            LegacyNoInterfaceObject => "LegacyNoInterfaceObject",
+            LegacyNullToEmptyString => "LegacyNullToEmptyString",
+            LegacyOverrideBuiltIns => "LegacyOverrideBuiltIns",
+            LegacySerializable => "LegacySerializable",
+            LegacyTreatNonObjectAsNull => "LegacyTreatNonObjectAsNull",
+            LegacyUnenumerableNamedProperties => "LegacyUnenumerableNamedProperties",
+            LegacyUnforgeable => "LegacyUnforgeable",
+            LegacyWindowAlias(_) => "LegacyWindowAlias",
+            NewObject => "NewObject",
+            PutForwards(_) => "PutForwards",
+            Replaceable => "Replaceable",
+            SameObject => "SameObject",
+            SecureContext => "SecureContext",
+            Serializable => "Serializable",
+            StringContext(_) => "StringContext",
+            Transferable => "Transferable",
+            Unscopable => "Unscopable",
+        }
+    }
+
+    /// Constructs the attribute can annotate
+    pub fn placements(&self) -> &'static [Placement] {
+        use self::KnownAttribute::*;
+        use self::Placement as P;
+
+        const CONVERSION: &[Placement] = &[P::Type, P::Argument, P::DictionaryMember];
+        const INTERFACE: &[Placement] = &[P::Interface];
+        const ATTRIBUTE: &[Placement] = &[P::Attribute];
+        const CONTEXT: &[Placement] = &[
+            P::Interface,
+            P::InterfaceMixin,
+            P::Namespace,
+            P::Constant,
+            P::Attribute,
+            P::Operation,
+        ];
+
+        match self {
+            AllowResizable
+            | AllowShared
+            | Clamp
+            | EnforceRange
+            | LegacyNullToEmptyString
+            | StringContext(_) => CONVERSION,
+            CEReactions => &[P::Attribute, P::Operation],
+            Constructor(_)
+            | Global(_)
+            | LegacyFactoryFunction { .. }
+            | LegacyNamespace(_)
+            | LegacyNoInterfaceObject
+            | LegacyOverrideBuiltIns
+            | LegacySerializable
+            | LegacyUnenumerableNamedProperties
+            | LegacyWindowAlias(_)
+            | Serializable
+            | Transferable => INTERFACE,
+            CrossOriginIsolated | SecureContext => CONTEXT,
+            Default | NewObject => &[P::Operation],
+            Exposed(_) => &[
+                P::Interface,
+                P::CallbackInterface,
+                P::InterfaceMixin,
+                P::Namespace,
+                P::Constant,
+                P::Attribute,
+                P::Operation,
+            ],
+            // Annotated interfaces before constructors were declared as members
+            HTMLConstructor => &[P::Constructor, P::Interface],
+            LegacyLenientSetter | LegacyLenientThis | PutForwards(_) | Replaceable | SameObject => {
+                ATTRIBUTE
+            }
+            LegacyTreatNonObjectAsNull => &[P::Callback],
+            LegacyUnforgeable | Unscopable => &[P::Attribute, P::Operation],
+        }
+    }
+
+    /// Returns `true` if the attribute can annotate the construct
+    pub fn allowed_on(&self, placement: Placement) -> bool {
+        self.placements().contains(&placement)
+    }
+}
+
+/// Checks the extended attributes of every definition, member, argument and type
+///
+/// Unknown attributes are reported as `unknown-attribute` warnings, known ones with the wrong
+/// form as `malformed-attribute` errors, and known ones on a construct they cannot annotate
+/// as `misplaced-attribute` errors. Diagnostics refer to files by their index in `files`.
+pub fn check<'a, I: IntoIterator<Item = &'a [Definition<'a>]>>(files: I) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        file: 0,
+        diagnostics: Vec::new(),
+    };
+    for (file, definitions) in files.into_iter().enumerate() {
+        checker.file = file;
+        for definition in definitions {
+            definition.accept(&mut checker);
+        }
+    }
+    checker.diagnostics
+}
+
+struct Checker {
+    file: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker {
+    fn check(&mut self, attributes: &Option<ExtendedAttributeList>, placement: Placement) {
+        let attributes = match attributes {
+            Some(attributes) => attributes,
+            None => return,
+        };
+        for attribute in &attributes.body.list {
+            let span = attribute.span();
+            let diagnostic = match KnownAttribute::parse(attribute) {
+                Ok(known) if known.allowed_on(placement) => continue,
+                Ok(known) => Diagnostic::error(
+                    "misplaced-attribute",
+                    format!(
+                        "`[{}]` cannot annotate {}",
+                        known.name(),
+                        placement.describe()
+                    ),
+                    self.file,
+                    span,
+                ),
+                Err(err @ AttributeError::Unknown(_)) => {
+                    Diagnostic::warning("unknown-attribute", err.to_string(), self.file, span)
+                }
+                Err(err) => {
+                    Diagnostic::error("malformed-attribute", err.to_string(), self.file, span)
+                }
+            };
+            self.diagnostics.push(diagnostic);
+        }
+    }
+}
+
+impl Placement {
+    /// Describes the construct with its article. Ex: `an interface`
+    pub fn describe(self) -> &'static str {
+        match self {
+            Placement::Interface => "an interface",
+            Placement::CallbackInterface => "a callback interface",
+            Placement::InterfaceMixin => "an interface mixin",
+            Placement::Namespace => "a namespace",
+            Placement::Dictionary => "a dictionary",
+            Placement::Enum => "an enum",
+            Placement::Typedef => "a typedef",
+            Placement::Callback => "a callback function",
+            Placement::Includes => "an includes statement",
+            Placement::Constant => "a constant",
+            Placement::Attribute => "an attribute",
+            Placement::Operation => "an operation",
+            Placement::Constructor => "a constructor",
+            Placement::Iterable => "an iterable, maplike or setlike declaration",
+            Placement::Argument => "an argument",
+            Placement::DictionaryMember => "a dictionary member",
+            Placement::Type => "a type",
+        }
+    }
+}
+
+/// Implements visitor methods which check the attributes of a node
+macro_rules! check_attributes {
+    ($($method:ident: $node:ty => $placement:ident,)*) => {
+        impl<'a> Visit<'a> for Checker {
+            $(
+                fn $method(&mut self, node: &'a $node) {
+                    self.check(&node.attributes, Placement::$placement);
+                    node.walk(self);
+                }
+            )*
+
+            fn visit_iterable_interface_member(&mut self, node: &'a IterableInterfaceMember<'a>) {
+                match node {
+                    IterableInterfaceMember::Single(m) => {
+                        self.check(&m.attributes, Placement::Iterable)
+                    }
+                    IterableInterfaceMember::Double(m) => {
+                        self.check(&m.attributes, Placement::Iterable)
+                    }
+                }
+                node.walk(self);
+            }
+
+            fn visit_async_iterable_interface_member(
+                &mut self,
+                node: &'a AsyncIterableInterfaceMember<'a>,
+            ) {
+                match node {
+                    AsyncIterableInterfaceMember::Single(m) => {
+                        self.check(&m.attributes, Placement::Iterable)
+                    }
+                    AsyncIterableInterfaceMember::Double(m) => {
+                        self.check(&m.attributes, Placement::Iterable)
+                    }
+                }
+                node.walk(self);
+            }
+        }
+    };
+}
+
+check_attributes! {
+    visit_interface_definition: InterfaceDefinition<'a> => Interface,
+    visit_partial_interface_definition: PartialInterfaceDefinition<'a> => Interface,
+    visit_callback_interface_definition: CallbackInterfaceDefinition<'a> => CallbackInterface,
+    visit_interface_mixin_definition: InterfaceMixinDefinition<'a> => InterfaceMixin,
+    visit_partial_interface_mixin_definition: PartialInterfaceMixinDefinition<'a> => InterfaceMixin,
+    visit_namespace_definition: NamespaceDefinition<'a> => Namespace,
+    visit_partial_namespace_definition: PartialNamespaceDefinition<'a> => Namespace,
+    visit_dictionary_definition: DictionaryDefinition<'a> => Dictionary,
+    visit_partial_dictionary_definition: PartialDictionaryDefinition<'a> => Dictionary,
+    visit_enum_definition: EnumDefinition<'a> => Enum,
+    visit_typedef_definition: TypedefDefinition<'a> => Typedef,
+    visit_callback_definition: CallbackDefinition<'a> => Callback,
+    visit_includes_statement_definition: IncludesStatementDefinition<'a> => Includes,
+    visit_implements_definition: ImplementsDefinition<'a> => Includes,
+    visit_const_member: ConstMember<'a> => Constant,
+    visit_const_namespace_member: ConstNamespaceMember<'a> => Constant,
+    visit_attribute_interface_member: AttributeInterfaceMember<'a> => Attribute,
+    visit_attribute_mixin_member: AttributeMixinMember<'a> => Attribute,
+    visit_attribute_namespace_member: AttributeNamespaceMember<'a> => Attribute,
+    visit_operation_interface_member: OperationInterfaceMember<'a> => Operation,
+    visit_operation_mixin_member: OperationMixinMember<'a> => Operation,
+    visit_operation_namespace_member: OperationNamespaceMember<'a> => Operation,
+    visit_stringifier_member: StringifierMember<'a> => Operation,
+    visit_jsonifier_member: JsonifierMember<'a> => Operation,
+    visit_serializer_member: SerializerMember<'a> => Operation,
+    visit_constructor_interface_member: ConstructorInterfaceMember<'a> => Constructor,
+    visit_maplike_interface_member: MaplikeInterfaceMember<'a> => Iterable,
+    visit_setlike_interface_member: SetlikeInterfaceMember<'a> => Iterable,
+    visit_single_argument: SingleArgument<'a> => Argument,
+    visit_variadic_argument: VariadicArgument<'a> => Argument,
+    visit_dictionary_member: DictionaryMember<'a> => DictionaryMember,
+    visit_attributed_type: AttributedType<'a> => Type,
+    visit_attributed_non_any_type: AttributedNonAnyType<'a> => Type,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Parse;
+
+    fn parse(source: &'static str) -> Result<KnownAttribute<'static>, AttributeError<'static>> {
+        let (_, attribute) = ExtendedAttribute::parse(source).unwrap();
+        KnownAttribute::parse(Box::leak(Box::new(attribute)))
+    }
+
+    #[test]
+    fn should_parse_typed_values() {
+        assert_eq!(
+            parse("Exposed=*"),
+            Ok(KnownAttribute::Exposed(Exposure::All))
+        );
+        assert_eq!(
+            parse("Exposed=Window"),
+            Ok(KnownAttribute::Exposed(Exposure::Globals(vec!["Window"])))
+        );
+        assert_eq!(
+            parse("Global=(Worker,DedicatedWorker)"),
+            Ok(KnownAttribute::Global(vec!["Worker", "DedicatedWorker"]))
+        );
+        assert_eq!(
+            parse("PutForwards=href"),
+            Ok(KnownAttribute::PutForwards("href"))
+        );
+        assert_eq!(
+            parse("TreatNullAs=EmptyString"),
+            Ok(KnownAttribute::LegacyNullToEmptyString)
+        );
+        match parse("LegacyFactoryFunction=Image(optional unsigned long width)") {
+            Ok(KnownAttribute::LegacyFactoryFunction { name, arguments }) => {
+                assert_eq!(name, "Image");
+                assert_eq!(arguments.list.len(), 1);
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_reject_unknown_and_malformed_attributes() {
+        assert_eq!(parse("Throws"), Err(AttributeError::Unknown("Throws")));
+        assert_eq!(
+            parse("Clamp=x").unwrap_err().to_string(),
+            "`[Clamp]` takes no arguments"
+        );
+        assert_eq!(
+            parse("PutForwards").unwrap_err().to_string(),
+            "`[PutForwards]` takes an identifier"
+        );
+    }
+
+    #[test]
+    fn should_allow_legacy_serializable_on_interfaces_only() {
+        let legacy_serializable = parse("LegacySerializable").unwrap();
+        assert_eq!(legacy_serializable, KnownAttribute::LegacySerializable);
+        assert!(legacy_serializable.allowed_on(Placement::Interface));
+        assert!(!legacy_serializable.allowed_on(Placement::Dictionary));
+        assert!(!legacy_serializable.allowed_on(Placement::Attribute));
+        assert_eq!(
+            parse("LegacySerializable=x").unwrap_err().to_string(),
+            "`[LegacySerializable]` takes no arguments"
+        );
+    }
+
+    #[test]
+    fn should_check_placements() {
+        let parsed = crate::parse(
+            "
+            [Exposed=Window, SecureContext, Replaceable]
+            interface Document {
+                [CEReactions, PutForwards=value] readonly attribute DOMString title;
+                undefined write([Clamp] long x, [SameObject] DOMString text);
+                [Throws] attribute [EnforceRange] long count;
+                [Exposed] constructor();
+            };
+            ",
+        )
+        .unwrap();
+        let messages: Vec<_> = check(vec![&parsed[..]])
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "error[misplaced-attribute]: `[Replaceable]` cannot annotate an interface",
+                "error[misplaced-attribute]: `[SameObject]` cannot annotate an argument",
+                "warning[unknown-attribute]: unknown extended attribute `Throws`",
+                "error[malformed-attribute]: `[Exposed]` takes `*`, an identifier or an \
+                 identifier list",
+            ]
+        );
+    }
+}
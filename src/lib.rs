@@ -16,32 +16,59 @@
 //! println!("{:?}", parsed);
 //! ```
 //!
+//! Every node records where it was parsed from, see the [`span`](span/index.html) module.
+//! Parsed definitions can be printed back to WebIDL, see the [`print`](print/index.html) module.
+//! Comments and whitespace can be kept as well, see the [`trivia`](trivia/index.html) module.
+//! Definitions borrow their text from the source, and [`IntoOwned`](trait.IntoOwned.html) turns
+//! them into `'static` ones which do not.
+//! Sources can be formatted in a canonical style, see the [`format`](format/index.html) module.
+//! Nodes can be traversed with visitors, see the [`visit`](visit/index.html) module.
+//! Standard extended attributes can be read as typed values, see the
+//! [`known_attribute`](known_attribute/index.html) module.
+//! The globals definitions are exposed in can be worked out, see the [`exposure`](exposure/index.html)
+//! module.
+//! Partials, mixins and files can be merged into one model, see the [`semantic`](semantic/index.html)
+//! module.
+//! References between definitions can be checked, see the [`resolve`](resolve/index.html) module.
+//! Typedefs can be expanded and unions flattened, see the [`normalize`](normalize/index.html) module.
+//! Overloaded operations can be expanded for dispatch, see the [`overload`](overload/index.html)
+//! module.
+//! Many files can be loaded and located as `file:line:column`, see the [`project`](project/index.html)
+//! module.
+//! Definitions can be checked against the rules of the specification, see the
+//! [`validate`](validate/index.html) module.
+//!
+//! With the `webidl2` feature definitions can be converted to and from the JSON of the
+//! webidl2.js library, see the [`webidl2`](webidl2/index.html) module.
+//!
+//! With the `serde` feature every node implements `serde::Serialize` and `serde::Deserialize`.
+//! Enums are tagged with the name of their variant, `Punctuated` lists and delimited nodes
+//! serialize as their contents and nullable types as `{ "type": ..., "nullable": ... }`. Source
+//! locations, separators, delimiters and the `_` escaping identifiers are left out, so
+//! deserialized nodes have empty spans and equal the parsed ones as
+//! [`SpanlessEq`](span/trait.SpanlessEq.html), apart from trailing commas.
+//!
 //! Note:
-//! This parser follows the grammar given at [WebIDL](https://heycam.github.io/webidl).
+//! This parser follows the grammar given at [WebIDL](https://heycam.github.io/webidl). Forms
+//! since removed from it, like `async iterable` and `implements`, are accepted too unless
+//! [`Grammar::Strict`](enum.Grammar.html) is asked for. The member keywords of browser engines
+//! are accepted with a [`Dialect`](enum.Dialect.html), see
+//! [`parse_with_options`](fn.parse_with_options.html). Their extended attributes are accepted
+//! in every dialect.
 //!
 //! If any flaws found when parsing string with a valid grammar, create an issue.
 
 // need a higher recusion limit for macros
 #![recursion_limit = "128"]
 
-#[macro_use(
-    alt,
-    cond,
-    do_parse,
-    map,
-    many0,
-    opt,
-    recognize,
-    separated_list,
-    separated_nonempty_list,
-    terminated
-)]
+#[macro_use(alt, call, do_parse, map, many0, opt, recognize)]
 extern crate nom;
 
 use self::argument::ArgumentList;
 use self::attribute::ExtendedAttributeList;
 use self::common::{Braced, Identifier, Parenthesized, PunctuatedNonEmpty};
 use self::dictionary::DictionaryMembers;
+pub use self::error::Error;
 use self::interface::{Inheritance, InterfaceMembers};
 use self::literal::StringLit;
 use self::mixin::MixinMembers;
@@ -58,15 +85,36 @@ pub mod term;
 pub mod argument;
 pub mod attribute;
 pub mod common;
+pub mod diagnostic;
 pub mod dictionary;
+pub mod error;
+pub mod exposure;
+pub mod format;
 pub mod interface;
+pub mod known_attribute;
 pub mod literal;
 pub mod mixin;
 pub mod namespace;
+pub mod normalize;
+pub mod overload;
+pub mod print;
+pub mod project;
+mod recovery;
+pub mod resolve;
+pub mod semantic;
+pub mod span;
+mod state;
+pub mod trivia;
 pub mod types;
+pub mod validate;
+pub mod visit;
+#[cfg(feature = "webidl2")]
+pub mod webidl2;
 
 /// A convenient parse function
 ///
+/// Fails with an [`Error`](struct.Error.html) if any part of `raw` is not a valid definition.
+///
 /// ### Example
 ///
 /// ```
@@ -80,19 +128,260 @@ pub mod types;
 ///
 /// println!("{:?}", parsed);
 /// ```
-pub fn parse(raw: &str) -> Result<Definitions<'_>, Err<(&str, ErrorKind)>> {
-    let (remaining, parsed) = Definitions::parse(raw)?;
-    assert!(
-        remaining.is_empty(),
-        "There is redundant raw data after parsing"
-    );
+// Errors are only built once per parse, so their size does not matter
+#[allow(clippy::result_large_err)]
+pub fn parse(raw: &str) -> Result<Definitions<'_>, Error> {
+    let _guard = state::enter(raw);
+    match parse_definitions(raw) {
+        Ok(parsed) => Ok(parsed),
+        // Failures are only tracked once there is an error to describe, as tracking them
+        // slows the parser down
+        Err(_) => {
+            let _tracking = state::track_failures();
+            parse_definitions(raw)
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_definitions(raw: &str) -> Result<Definitions<'_>, Error> {
+    let (remaining, parsed) = Definitions::parse(raw).map_err(|err| Error::from_nom(raw, err))?;
+    // Only a source without definitions can have trivia left over
+    if !state::skip_trivia(remaining).is_empty() {
+        return Err(Error::from_trailing_input(raw, remaining));
+    }
     Ok(parsed)
 }
 
+/// Like [`parse`](fn.parse.html), but also keeps the whitespace and comments of `raw` on the
+/// tokens of the definitions
+///
+/// The trivia after the line of the last token belongs to no token and is returned next to the
+/// definitions. It is all of `raw` if there are none. See the [`trivia`](trivia/index.html)
+/// module for what can be done with the trivia.
+// Errors are only built once per parse, so their size does not matter
+#[allow(clippy::result_large_err)]
+pub fn parse_with_trivia(raw: &str) -> Result<(Definitions<'_>, &str), Error> {
+    let mut parsed = parse(raw)?;
+    let end = trivia::attach(raw, &mut parsed);
+    Ok((parsed, end))
+}
+
+/// Parses as much of `raw` as possible
+///
+/// Unlike [`parse`](fn.parse.html), a definition or member which cannot be parsed does not
+/// stop the parser. It is skipped up to the `;` ending it and reported as an error, and parsing
+/// continues after it. Returns every definition that could be parsed and the errors in the
+/// order they appear in `raw`.
+///
+/// ### Example
+///
+/// ```
+/// let (parsed, errors) = weedle::parse_recovering("
+///     interface Window {
+///         readonly attribute Storage sessionStorage;
+///         attribute long = 5;
+///     };
+///     dictionary;
+///     enum Direction { \"up\", \"down\" };
+/// ");
+///
+/// assert_eq!(parsed.len(), 2);
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!((errors[0].line, errors[0].column), (4, 24));
+/// assert_eq!((errors[1].line, errors[1].column), (6, 15));
+/// ```
+pub fn parse_recovering(raw: &str) -> (Definitions<'_>, Vec<Error>) {
+    let _guard = state::enter(raw);
+    let _tracking = state::track_failures();
+    let _recovering = state::start_recovering();
+    let mut definitions = Vec::new();
+    let mut input = raw;
+    loop {
+        let next = state::skip_trivia(input);
+        if next.is_empty() {
+            break;
+        }
+
+        state::reset_failure();
+        let recovered = state::recovered_len();
+        match Definition::parse(next) {
+            Ok((rest, definition)) => {
+                definitions.push(definition);
+                input = rest;
+            }
+            Err(_) => {
+                // Anything recovered inside the definition was thrown away with it
+                state::truncate_recovered(recovered);
+                state::record_recovered(recovery::failure_at(next));
+                input = recovery::skip_statement(next, false);
+            }
+        }
+    }
+
+    let mut failures = state::take_recovered();
+    failures.sort_by_key(|failure| failure.offset);
+    let errors = failures
+        .into_iter()
+        .map(|failure| {
+            let offset = failure.offset;
+            Error::from_failure(raw, Some(failure), offset)
+        })
+        .collect();
+    (definitions, errors)
+}
+
+/// Which revision of the WebIDL grammar the parser accepts
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Grammar {
+    /// The current grammar and the legacy forms it replaced: `async iterable<...>` declarations,
+    /// `implements` statements and `legacycaller` operations
+    #[default]
+    Compatible,
+    /// Only the current grammar, so legacy forms are syntax errors
+    Strict,
+}
+
+/// Syntax of a browser engine accepted on top of the WebIDL grammar
+///
+/// Dialects only add member and statement keywords. Extended attributes are not gated by the
+/// dialect: the grammar lets them hold any balanced tokens, so the attributes of every engine,
+/// like `[ChromeOnly]`, `[Func="..."]` or `[RuntimeEnabled=Foo]`, parse in every dialect.
+/// Telling them apart is left to [`known_attribute`](known_attribute/index.html), which knows
+/// only the standard ones.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Dialect {
+    /// No extensions
+    #[default]
+    Standard,
+    /// Gecko's IDL: `jsonifier;` and `serializer` members, and `legacycaller` operations even
+    /// with [`Grammar::Strict`](enum.Grammar.html)
+    Gecko,
+    /// Chromium's IDL: `jsonifier;` and `serializer` members, and `implements` statements even
+    /// with [`Grammar::Strict`](enum.Grammar.html)
+    Chromium,
+}
+
+impl Dialect {
+    /// Returns `true` if the dialect keeps `keyword`, which the standard grammar lacks or has
+    /// removed
+    fn has_keyword(self, keyword: &str) -> bool {
+        match self {
+            Dialect::Standard => false,
+            Dialect::Gecko => matches!(keyword, "jsonifier" | "serializer" | "legacycaller"),
+            Dialect::Chromium => matches!(keyword, "jsonifier" | "serializer" | "implements"),
+        }
+    }
+}
+
+/// What [`parse_with_options`](fn.parse_with_options.html) accepts
+///
+/// ### Example
+///
+/// ```
+/// use weedle::{Dialect, Grammar, ParseOptions};
+///
+/// let options = ParseOptions::new()
+///     .grammar(Grammar::Strict)
+///     .dialect(Dialect::Chromium);
+/// assert_eq!(options.dialect, Dialect::Chromium);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ParseOptions {
+    pub grammar: Grammar,
+    pub dialect: Dialect,
+}
+
+impl ParseOptions {
+    /// The current grammar and its legacy forms, without extensions
+    pub fn new() -> Self {
+        ParseOptions::default()
+    }
+
+    pub fn grammar(mut self, grammar: Grammar) -> Self {
+        self.grammar = grammar;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+/// Like [`parse`](fn.parse.html), but accepts only the forms allowed by `options`
+///
+/// Nodes record which form they were written in, e.g.
+/// [`AsyncIterableKeyword`](interface/enum.AsyncIterableKeyword.html).
+///
+/// ### Example
+///
+/// ```
+/// use weedle::{Dialect, ParseOptions};
+///
+/// let source = "interface Point { jsonifier; };";
+/// assert!(weedle::parse(source).is_err());
+///
+/// let options = ParseOptions::new().dialect(Dialect::Gecko);
+/// assert!(weedle::parse_with_options(source, options).is_ok());
+/// ```
+// Errors are only built once per parse, so their size does not matter
+#[allow(clippy::result_large_err)]
+pub fn parse_with_options(raw: &str, options: ParseOptions) -> Result<Definitions<'_>, Error> {
+    let _guard = state::enter(raw);
+    let _options = state::use_options(options);
+    parse(raw)
+}
+
+/// Like [`parse`](fn.parse.html), but accepts only the forms allowed by `grammar`
+///
+/// ### Example
+///
+/// ```
+/// use weedle::Grammar;
+///
+/// let source = "Window implements WindowOrWorkerGlobalScope;";
+/// assert!(weedle::parse_with_grammar(source, Grammar::Compatible).is_ok());
+///
+/// let error = weedle::parse_with_grammar(source, Grammar::Strict).unwrap_err();
+/// assert_eq!(error.found.as_deref(), Some("implements"));
+/// assert_eq!(error.expected, vec!["`includes`"]);
+/// ```
+// Errors are only built once per parse, so their size does not matter
+#[allow(clippy::result_large_err)]
+pub fn parse_with_grammar(raw: &str, grammar: Grammar) -> Result<Definitions<'_>, Error> {
+    parse_with_options(raw, ParseOptions::new().grammar(grammar))
+}
+
 pub trait Parse<'a>: Sized {
     fn parse(input: &'a str) -> IResult<&'a str, Self>;
 }
 
+/// Converts a node into one which owns its text and does not borrow the source
+///
+/// Nodes hold their identifiers and literals as `Cow<'a, str>`. Parsing borrows them from the
+/// source, while `into_owned` copies them so the node can outlive it, be cached or be sent to
+/// another thread.
+///
+/// ### Example
+///
+/// ```
+/// use weedle::{Definitions, IntoOwned};
+///
+/// fn load() -> Definitions<'static> {
+///     let source = String::from("interface Window {};");
+///     weedle::parse(&source).unwrap().into_owned()
+/// }
+///
+/// let definitions = load();
+/// assert_eq!(definitions.len(), 1);
+/// ```
+pub trait IntoOwned {
+    type Owned: 'static;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
 /// Parses WebIDL definitions. It is the root struct for a complete WebIDL definition.
 ///
 /// ### Example
@@ -230,7 +519,8 @@ ast_types! {
             rhs_identifier: Identifier<'a>,
             semi_colon: term!(;),
         }),
-        /// Parses `[attributes]? identifier implements identifier;`
+        /// Parses the legacy `[attributes]? identifier implements identifier;`, which was replaced
+        /// by `includes`
         Implements(struct ImplementsDefinition<'a> {
             attributes: Option<ExtendedAttributeList<'a>>,
             lhs_identifier: Identifier<'a>,
@@ -247,6 +537,7 @@ pub type EnumValueList<'a> = PunctuatedNonEmpty<StringLit<'a>, term!(,)>;
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::print::ToWebIdl;
 
     test!(should_parse_includes_statement { "first includes second;" =>
         "";
@@ -422,4 +713,180 @@ mod test {
         "";
         CallbackDefinition;
     });
+
+    #[test]
+    fn should_own_definitions_after_the_source_is_dropped() {
+        let source = String::from("[Exposed=Window] interface Node { const short A = 0x1; };");
+        let borrowed = parse(&source).unwrap();
+        let owned: Definitions<'static> = parse(&source).unwrap().into_owned();
+        assert_eq!(owned, borrowed);
+        drop(borrowed);
+        drop(source);
+
+        let printed = std::thread::spawn(move || owned.to_webidl())
+            .join()
+            .unwrap();
+        assert_eq!(
+            printed,
+            "[Exposed=Window]\ninterface Node {\n    const short A = 0x1;\n};\n"
+        );
+    }
+
+    #[test]
+    fn should_build_nodes_from_owned_strings() {
+        let name = String::from("Node");
+        let typedef = TypedefDefinition {
+            attributes: None,
+            typedef: term!(typedef),
+            type_: Parse::parse("(long or DOMString)").unwrap().1,
+            identifier: Identifier::new(format!("{}Id", name)),
+            semi_colon: term!(;),
+            span: span::Span::default(),
+        };
+        drop(name);
+        assert_eq!(typedef.to_webidl(), "typedef (long or DOMString) NodeId;\n");
+    }
+
+    #[test]
+    fn should_accept_legacy_forms_unless_strict() {
+        for source in &[
+            "interface A { async iterable<long>; };",
+            "interface A { legacycaller (Element or HTMLCollection)? item(DOMString name); };",
+            "A implements B;",
+        ] {
+            assert!(parse_with_grammar(source, Grammar::Compatible).is_ok());
+            assert!(parse_with_grammar(source, Grammar::Strict).is_err());
+            // The grammar only lasts for the one parse
+            assert!(parse(source).is_ok());
+        }
+
+        let error = parse_with_grammar("A implements B;", Grammar::Strict).unwrap_err();
+        assert_eq!(error.found.as_deref(), Some("implements"));
+        assert_eq!(error.expected, vec!["`includes`"]);
+    }
+
+    #[test]
+    fn should_accept_current_forms_when_strict() {
+        let source = "
+            interface A {
+                async_iterable<DOMString, long>(optional long async);
+                getter long (long index);
+            };
+            A includes B;
+        ";
+        let parsed = parse_with_grammar(source, Grammar::Strict).unwrap();
+        assert_eq!(parsed, parse(source).unwrap());
+    }
+
+    #[test]
+    fn should_accept_members_of_dialects() {
+        let source = "interface A { jsonifier; serializer = { attribute }; };";
+        assert!(parse(source).is_err());
+        for dialect in &[Dialect::Gecko, Dialect::Chromium] {
+            let options = ParseOptions::new().dialect(*dialect);
+            assert!(parse_with_options(source, options).is_ok());
+        }
+        assert!(parse(source).is_err());
+
+        // Still names outside of members
+        let source = "interface A { attribute DOMString serializer; undefined jsonifier(); };";
+        assert!(parse(source).is_ok());
+    }
+
+    #[test]
+    fn should_accept_extended_attributes_of_engines_in_every_dialect() {
+        let source = r#"
+            [ChromeOnly, Func="IsChrome", RuntimeEnabled=Foo, Custom=("A", "B")]
+            interface A {};
+        "#;
+        let standard = parse(source).unwrap();
+        for dialect in &[Dialect::Gecko, Dialect::Chromium] {
+            let options = ParseOptions::new().dialect(*dialect);
+            assert_eq!(parse_with_options(source, options).unwrap(), standard);
+        }
+    }
+
+    #[test]
+    fn should_keep_legacy_forms_of_dialects_when_strict() {
+        let caller =
+            "interface A { legacycaller (Element or HTMLCollection)? item(DOMString name); };";
+        let implements = "A implements B;";
+        let strict = ParseOptions::new().grammar(Grammar::Strict);
+
+        let gecko = strict.dialect(Dialect::Gecko);
+        assert!(parse_with_options(caller, gecko).is_ok());
+        assert!(parse_with_options(implements, gecko).is_err());
+
+        let chromium = strict.dialect(Dialect::Chromium);
+        assert!(parse_with_options(implements, chromium).is_ok());
+        assert!(parse_with_options(caller, chromium).is_err());
+
+        let async_iterable = "interface A { async iterable<long>; };";
+        assert!(parse_with_options(async_iterable, gecko).is_err());
+        assert!(parse_with_options(async_iterable, chromium).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn should_serialize_without_wrappers() {
+        let (_, parsed) = Definition::parse("typedef (Node or sequence<long>)? Nodes;").unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::json!({
+                "Typedef": {
+                    "attributes": null,
+                    "typedef": "typedef",
+                    "type_": {
+                        "attributes": null,
+                        "type_": {
+                            "Union": {
+                                "type": [
+                                    { "Single": { "attributes": null, "type_": {
+                                        "Identifier": { "type": "Node", "nullable": false }
+                                    } } },
+                                    { "Single": { "attributes": null, "type_": {
+                                        "Sequence": {
+                                            "type": {
+                                                "sequence": "sequence",
+                                                "generics": { "Single": { "NonAny": { "Integer": {
+                                                    "type": { "Long": { "unsigned": null, "long": "long" } },
+                                                    "nullable": false
+                                                } } } }
+                                            },
+                                            "nullable": false
+                                        }
+                                    } } }
+                                ],
+                                "nullable": true
+                            }
+                        }
+                    },
+                    "identifier": "Nodes",
+                    "semi_colon": ";"
+                }
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn should_deserialize_serialized_nodes() {
+        use crate::span::SpanlessEq;
+
+        let (_, parsed) = Definition::parse(
+            "[Exposed=Window] interface A { attribute (long or DOMString)? a; undefined f(long b); };",
+        )
+        .unwrap();
+        let json = serde_json::to_string(&parsed).unwrap();
+        let deserialized: Definition = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.spanless_eq(&parsed));
+
+        let json = r#"{ "Typedef": { "attributes": null, "typedef": "typedef", "type_": {
+            "attributes": null, "type_": { "Single": { "Any": "any" } } }, "identifier": "A",
+            "semi_colon": "," } }"#;
+        let err = serde_json::from_str::<Definition>(json).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("invalid value: string \",\", expected ;"));
+    }
 }
@@ -1,65 +1,63 @@
+use std::borrow::Cow;
+
+use crate::print::{Printer, ToWebIdl};
+
 ast_types! {
     /// Represents an integer value
-    #[derive(Copy)]
     enum IntegerLit<'a> {
         /// Parses `-?[1-9][0-9]*`
-        #[derive(Copy)]
         Dec(struct DecLit<'a>(
-            &'a str = ws!(recognize!(do_parse!(
+            Cow<'a, str> = map!(expect!("integer", ws!(recognize!(do_parse!(
                 opt!(char!('-')) >>
                 one_of!("123456789") >>
                 take_while!(|c: char| c.is_ascii_digit()) >>
                 (())
-            ))),
+            )))), Cow::Borrowed),
         )),
         /// Parses `-?0[Xx][0-9A-Fa-f]+)`
-        #[derive(Copy)]
         Hex(struct HexLit<'a>(
-            &'a str = ws!(recognize!(do_parse!(
+            Cow<'a, str> = map!(expect!("integer", ws!(recognize!(do_parse!(
                 opt!(char!('-')) >>
                 char!('0') >>
                 alt!(char!('x') | char!('X')) >>
                 take_while!(|c: char| c.is_ascii_hexdigit()) >>
                 (())
-            ))),
+            )))), Cow::Borrowed),
         )),
         /// Parses `-?0[0-7]*`
-        #[derive(Copy)]
         Oct(struct OctLit<'a>(
-            &'a str = ws!(recognize!(do_parse!(
+            Cow<'a, str> = map!(expect!("integer", ws!(recognize!(do_parse!(
                 opt!(char!('-')) >>
                 char!('0') >>
-                take_while!(|c| '0' <= c && c <= '7') >>
+                take_while!(|c| ('0'..='7').contains(&c)) >>
                 (())
-            ))),
+            )))), Cow::Borrowed),
         )),
     }
 
     /// Represents a string value
     ///
     /// Follow `/"[^"]*"/`
-    #[derive(Copy)]
     struct StringLit<'a>(
-        &'a str = ws!(do_parse!(
+        Cow<'a, str> = map!(expect!("string", ws!(do_parse!(
             char!('"') >>
             s: take_while!(|c| c != '"') >>
             char!('"') >>
             (s)
-        )),
+        ))), Cow::Borrowed),
     )
 
     /// Represents a default literal value. Ex: `34|34.23|"value"|[ ]|true|false|null`
-    #[derive(Copy)]
     enum DefaultValue<'a> {
         Boolean(BooleanLit),
         /// Represents `[ ]`
-        #[derive(Copy, Default)]
+        #[derive(Default)]
         EmptyArray(struct EmptyArrayLit {
             open_bracket: term!(OpenBracket),
             close_bracket: term!(CloseBracket),
         }),
         /// Represents `{ }`
-        #[derive(Copy, Default)]
+        #[derive(Default)]
         EmptyDictionary(struct EmptyDictionaryLit {
             open_brace: term!(OpenBrace),
             close_brace: term!(CloseBrace),
@@ -71,7 +69,6 @@ ast_types! {
     }
 
     /// Represents `true`, `false`, `34.23`, `null`, `56`, ...
-    #[derive(Copy)]
     enum ConstValue<'a> {
         Boolean(BooleanLit),
         Float(FloatLit<'a>),
@@ -80,7 +77,6 @@ ast_types! {
     }
 
     /// Represents either `true` or `false`
-    #[derive(Copy)]
     struct BooleanLit(
         bool = alt!(
             weedle!(term!(true)) => {|_| true} |
@@ -89,12 +85,10 @@ ast_types! {
     )
 
     /// Represents a floating point value, `NaN`, `Infinity`, '+Infinity`
-    #[derive(Copy)]
     enum FloatLit<'a> {
         /// Parses `/-?(([0-9]+\.[0-9]*|[0-9]*\.[0-9]+)([Ee][+-]?[0-9]+)?|[0-9]+[Ee][+-]?[0-9]+)/`
-        #[derive(Copy)]
         Value(struct FloatValueLit<'a>(
-            &'a str = ws!(recognize!(do_parse!(
+            Cow<'a, str> = map!(expect!("float", ws!(recognize!(do_parse!(
                 opt!(char!('-')) >>
                 alt!(
                     do_parse!(
@@ -134,7 +128,7 @@ ast_types! {
                     )
                 ) >>
                 (())
-            ))),
+            )))), Cow::Borrowed),
         )),
         NegInfinity(term!(-Infinity)),
         Infinity(term!(Infinity)),
@@ -142,6 +136,42 @@ ast_types! {
     }
 }
 
+impl<'a> ToWebIdl for DecLit<'a> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        printer.token(&self.0, self.2.as_deref());
+    }
+}
+
+impl<'a> ToWebIdl for HexLit<'a> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        printer.token(&self.0, self.2.as_deref());
+    }
+}
+
+impl<'a> ToWebIdl for OctLit<'a> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        printer.token(&self.0, self.2.as_deref());
+    }
+}
+
+impl<'a> ToWebIdl for FloatValueLit<'a> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        printer.token(&self.0, self.2.as_deref());
+    }
+}
+
+impl<'a> ToWebIdl for StringLit<'a> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        printer.token(&format!("\"{}\"", self.0), self.2.as_deref());
+    }
+}
+
+impl ToWebIdl for BooleanLit {
+    fn write_webidl(&self, printer: &mut Printer) {
+        printer.token(if self.0 { "true" } else { "false" }, self.2.as_deref());
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -150,72 +180,72 @@ mod test {
 
     test!(should_parse_integer { "45" =>
         "";
-        IntegerLit => IntegerLit::Dec(DecLit("45"))
+        IntegerLit => IntegerLit::Dec(DecLit::new("45"))
     });
 
     test!(should_parse_integer_surrounding_with_spaces { "  123123  " =>
         "";
-        IntegerLit => IntegerLit::Dec(DecLit("123123"))
+        IntegerLit => IntegerLit::Dec(DecLit::new("123123"))
     });
 
     test!(should_parse_integer_preceeding_others { "3453 string" =>
         "string";
-        IntegerLit => IntegerLit::Dec(DecLit("3453"))
+        IntegerLit => IntegerLit::Dec(DecLit::new("3453"))
     });
 
     test!(should_parse_neg_integer { "-435" =>
         "";
-        IntegerLit => IntegerLit::Dec(DecLit("-435"))
+        IntegerLit => IntegerLit::Dec(DecLit::new("-435"))
     });
 
     test!(should_parse_hex_number { "0X08" =>
         "";
-        IntegerLit => IntegerLit::Hex(HexLit("0X08"))
+        IntegerLit => IntegerLit::Hex(HexLit::new("0X08"))
     });
 
     test!(should_parse_hex_large_number { "0xA" =>
         "";
-        IntegerLit => IntegerLit::Hex(HexLit("0xA"))
+        IntegerLit => IntegerLit::Hex(HexLit::new("0xA"))
     });
 
     test!(should_parse_zero { "0" =>
         "";
-        IntegerLit => IntegerLit::Oct(OctLit("0"))
+        IntegerLit => IntegerLit::Oct(OctLit::new("0"))
     });
 
     test!(should_parse_oct_number { "-07561" =>
         "";
-        IntegerLit => IntegerLit::Oct(OctLit("-07561"))
+        IntegerLit => IntegerLit::Oct(OctLit::new("-07561"))
     });
 
     test!(should_parse_float { "45.434" =>
         "";
-        FloatLit => FloatLit::Value(FloatValueLit("45.434"))
+        FloatLit => FloatLit::Value(FloatValueLit::new("45.434"))
     });
 
     test!(should_parse_float_surrounding_with_spaces { "  2345.2345  " =>
         "";
-        FloatLit => FloatLit::Value(FloatValueLit("2345.2345"))
+        FloatLit => FloatLit::Value(FloatValueLit::new("2345.2345"))
     });
 
     test!(should_parse_float_preceeding_others { "3453.32334 string" =>
         "string";
-        FloatLit => FloatLit::Value(FloatValueLit("3453.32334"))
+        FloatLit => FloatLit::Value(FloatValueLit::new("3453.32334"))
     });
 
     test!(should_parse_neg_float { "-435.3435" =>
         "";
-        FloatLit => FloatLit::Value(FloatValueLit("-435.3435"))
+        FloatLit => FloatLit::Value(FloatValueLit::new("-435.3435"))
     });
 
     test!(should_parse_float_exp { "5.3434e23" =>
         "";
-        FloatLit => FloatLit::Value(FloatValueLit("5.3434e23"))
+        FloatLit => FloatLit::Value(FloatValueLit::new("5.3434e23"))
     });
 
     test!(should_parse_float_exp_with_decimal { "3e23" =>
         "";
-        FloatLit => FloatLit::Value(FloatValueLit("3e23"))
+        FloatLit => FloatLit::Value(FloatValueLit::new("3e23"))
     });
 
     test!(should_parse_neg_infinity { "-Infinity" =>
@@ -230,33 +260,33 @@ mod test {
 
     test!(should_parse_string { r#""this is a string""# =>
         "";
-        StringLit => StringLit("this is a string")
+        StringLit => StringLit::new("this is a string")
     });
 
     test!(should_parse_string_surround_with_spaces { r#"  "this is a string"  "# =>
         "";
-        StringLit => StringLit("this is a string")
+        StringLit => StringLit::new("this is a string")
     });
 
     test!(should_parse_string_followed_by_string { r#" "this is first"  "this is second" "# =>
         r#""this is second" "#;
-        StringLit => StringLit("this is first")
+        StringLit => StringLit::new("this is first")
     });
 
     test!(should_parse_string_with_spaces { r#"  "  this is a string  "  "# =>
         "";
-        StringLit => StringLit("  this is a string  ")
+        StringLit => StringLit::new("  this is a string  ")
     });
 
     test!(should_parse_string_with_comment { r#"  "// this is still a string"
      "# =>
         "";
-        StringLit => StringLit("// this is still a string")
+        StringLit => StringLit::new("// this is still a string")
     });
 
     test!(should_parse_string_with_multiline_comment { r#"  "/*"  "*/"  "# =>
         r#""*/"  "#;
-        StringLit => StringLit("/*")
+        StringLit => StringLit::new("/*")
     });
 
     test!(should_parse_null { "null" =>
@@ -271,11 +301,11 @@ mod test {
 
     test!(should_parse_bool_true { "true" =>
         "";
-        BooleanLit => BooleanLit(true)
+        BooleanLit => BooleanLit::new(true)
     });
 
     test!(should_parse_bool_false { "false" =>
         "";
-        BooleanLit => BooleanLit(false)
+        BooleanLit => BooleanLit::new(false)
     });
 }
@@ -22,12 +22,6 @@ macro_rules! take_while1 {
     };
 }
 
-macro_rules! take_until {
-    ($i:expr, $substr:expr) => {
-        nom::bytes::complete::take_until($substr)($i)
-    };
-}
-
 macro_rules! one_of {
     ($i:expr, $inp: expr) => {
         nom::character::complete::one_of($inp)($i)
@@ -40,9 +34,23 @@ macro_rules! char {
     };
 }
 
+/// Records `$what` as expected at the current position if the wrapped parser fails
+macro_rules! expect {
+    ($i:expr, $what:expr, $submac:ident!( $($args:tt)* )) => {
+        match $submac!($i, $($args)*) {
+            Ok(o) => Ok(o),
+            Err(e) => {
+                $crate::state::expected($i, $what);
+                Err(e)
+            }
+        }
+    };
+}
+
 macro_rules! parser {
     ($submac:ident!( $($args:tt)* )) => {
         fn parse(input: &'a str) -> $crate::IResult<&'a str, Self> {
+            let _guard = $crate::state::enter(input);
             $submac!(input, $($args)*)
         }
     };
@@ -59,6 +67,8 @@ macro_rules! ast_types {
     (@extract_type struct $name:ident $($rest:tt)*) => ($name);
     (@extract_type enum $name:ident<'a> $($rest:tt)*) => ($name<'a>);
     (@extract_type enum $name:ident $($rest:tt)*) => ($name);
+    (@owned_type $name:ident [ ]) => ($name);
+    (@owned_type $name:ident [ 'a ]) => ($name<'static>);
 
     () => ();
     (
@@ -181,26 +191,110 @@ macro_rules! ast_types {
 }
 
 macro_rules! __ast_tuple_struct {
-    (@launch_pad
+    // Tuple structs with a custom parser are single tokens, which have a third field for their
+    // trivia
+    (@build
         $(#[$attr:meta])*
         $name:ident
         [ $($maybe_a:tt)* ]
         ( $inner:ty = $submac:ident!( $($args:tt)* ), )
+        $($trivia:ty)?
     ) => (
         $(#[$attr])*
         #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
-        pub struct $name<$($maybe_a)*>(pub $inner);
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+        pub struct $name<$($maybe_a)*>(
+            pub $inner,
+            #[cfg_attr(feature = "serde", serde(skip))] pub $crate::span::Span,
+            $(#[cfg_attr(feature = "serde", serde(skip))] pub Option<$trivia>,)?
+        );
+
+        impl<$($maybe_a)*> $name<$($maybe_a)*> {
+            /// Creates the node without a source location
+            pub fn new(inner: impl Into<$inner>) -> Self {
+                $name(inner.into(), ::std::default::Default::default() $(, None::<$trivia>)?)
+            }
+        }
 
         impl<'a> $crate::Parse<'a> for $name<$($maybe_a)*> {
             fn parse(input: &'a str) -> $crate::IResult<&'a str, Self> {
                 use $crate::nom::lib::std::result::Result::*;
 
+                let _guard = $crate::state::enter(input);
                 match $submac!(input, $($args)*) {
                     Err(e) => Err(e),
-                    Ok((i, inner)) => Ok((i, $name(inner))),
+                    // Every such node is a single token
+                    Ok((i, inner)) => {
+                        let span = $crate::state::token_span(input, i);
+                        Ok((i, $name(inner, span $(, None::<$trivia>)?)))
+                    }
                 }
             }
         }
+
+        impl<$($maybe_a)*> $crate::span::Spanned for $name<$($maybe_a)*> {
+            fn span(&self) -> $crate::span::Span {
+                self.1
+            }
+        }
+
+        impl<$($maybe_a)*> $crate::IntoOwned for $name<$($maybe_a)*> {
+            type Owned = ast_types!(@owned_type $name [ $($maybe_a)* ]);
+
+            fn into_owned(self) -> Self::Owned {
+                $name(
+                    $crate::IntoOwned::into_owned(self.0),
+                    self.1,
+                    $({
+                        let trivia: Option<$trivia> = self.2;
+                        trivia
+                    })?
+                )
+            }
+        }
+
+        impl<$($maybe_a)*> $crate::span::SpanlessEq for $name<$($maybe_a)*> {
+            fn spanless_eq(&self, other: &Self) -> bool {
+                $crate::span::SpanlessEq::spanless_eq(&self.0, &other.0)
+            }
+        }
+    );
+    (@launch_pad
+        $(#[$attr:meta])*
+        $name:ident
+        [ $($maybe_a:tt)* ]
+        ( $inner:ty = $submac:ident!( $($args:tt)* ), )
+    ) => (
+        __ast_tuple_struct! {
+            @build
+            $(#[$attr])*
+            $name
+            [ $($maybe_a)* ]
+            ( $inner = $submac!( $($args)* ), )
+            Box<$crate::trivia::TokenTrivia>
+        }
+
+        // Tuple structs with a custom parser hold raw values and have no children
+        impl<'a> $crate::visit::Walk<'a> for $name<$($maybe_a)*> {
+            fn walk<V: $crate::visit::Visit<'a> + ?Sized>(&'a self, _: &mut V) {}
+            fn walk_mut<V: $crate::visit::VisitMut<'a> + ?Sized>(&mut self, _: &mut V) {}
+        }
+
+        impl<$($maybe_a)*> $crate::trivia::Tokens for $name<$($maybe_a)*> {
+            fn tokens<'t>(
+                &'t self,
+                tokens: &mut Vec<($crate::span::Span, Option<&'t $crate::trivia::TokenTrivia>)>,
+            ) {
+                tokens.push((self.1, self.2.as_deref()));
+            }
+
+            fn tokens_mut<'t>(
+                &'t mut self,
+                tokens: &mut Vec<($crate::span::Span, &'t mut Option<Box<$crate::trivia::TokenTrivia>>)>,
+            ) {
+                tokens.push((self.1, &mut self.2));
+            }
+        }
     );
     (@launch_pad
         $(#[$attr:meta])*
@@ -209,16 +303,91 @@ macro_rules! __ast_tuple_struct {
         ( $inner:ty, )
     ) => (
         __ast_tuple_struct! {
-            @launch_pad
+            @build
             $(#[$attr])*
             $name
             [ $($maybe_a)* ]
             ( $inner = weedle!($inner), )
         }
+
+        impl<'a> $crate::visit::Walk<'a> for $name<$($maybe_a)*> {
+            fn walk<V: $crate::visit::Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+                $crate::visit::Accept::accept(&self.0, visitor);
+            }
+            fn walk_mut<V: $crate::visit::VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+                $crate::visit::Accept::accept_mut(&mut self.0, visitor);
+            }
+        }
+
+        impl<$($maybe_a)*> $crate::trivia::Tokens for $name<$($maybe_a)*> {
+            fn tokens<'t>(
+                &'t self,
+                tokens: &mut Vec<($crate::span::Span, Option<&'t $crate::trivia::TokenTrivia>)>,
+            ) {
+                $crate::trivia::Tokens::tokens(&self.0, tokens);
+            }
+
+            fn tokens_mut<'t>(
+                &'t mut self,
+                tokens: &mut Vec<($crate::span::Span, &'t mut Option<Box<$crate::trivia::TokenTrivia>>)>,
+            ) {
+                $crate::trivia::Tokens::tokens_mut(&mut self.0, tokens);
+            }
+        }
+
+        // Tuple structs with a custom parser hold raw values and are printed by hand
+        impl<$($maybe_a)*> $crate::print::ToWebIdl for $name<$($maybe_a)*> {
+            fn write_webidl(&self, printer: &mut $crate::print::Printer) {
+                $crate::print::ToWebIdl::write_webidl(&self.0, printer);
+            }
+        }
     );
 }
 
 macro_rules! __ast_struct {
+    // Generic structs only wrap other nodes and are serialized by hand, without their tokens
+    (@build_struct_decl
+        {
+            @wrapper
+            $(#[$attr:meta])*
+            $name:ident
+            [ $($generics:ident),* ]
+            $($field:ident : $type:ty)*
+        }
+        { }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        pub struct $name<$($generics),*> {
+            $(pub $field : $type,)*
+            pub span: $crate::span::Span,
+        }
+
+        impl<$($generics),*> $crate::span::Spanned for $name<$($generics),*> {
+            fn span(&self) -> $crate::span::Span {
+                self.span
+            }
+        }
+
+        impl<$($generics: $crate::IntoOwned),*> $crate::IntoOwned for $name<$($generics),*> {
+            type Owned = $name<$(<$generics as $crate::IntoOwned>::Owned),*>;
+
+            fn into_owned(self) -> Self::Owned {
+                $name {
+                    $($field: $crate::IntoOwned::into_owned(self.$field),)*
+                    span: self.span,
+                }
+            }
+        }
+
+        impl<$($generics: $crate::span::SpanlessEq),*> $crate::span::SpanlessEq
+            for $name<$($generics),*>
+        {
+            fn spanless_eq(&self, other: &Self) -> bool {
+                true $(&& $crate::span::SpanlessEq::spanless_eq(&self.$field, &other.$field))*
+            }
+        }
+    };
     (@build_struct_decl
         {
             $(#[$attr:meta])*
@@ -230,8 +399,34 @@ macro_rules! __ast_struct {
     ) => {
         $(#[$attr])*
         #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $name<$($generics)*> {
             $(pub $field : $type,)*
+            #[cfg_attr(feature = "serde", serde(skip))]
+            pub span: $crate::span::Span,
+        }
+
+        impl<$($generics)*> $crate::span::Spanned for $name<$($generics)*> {
+            fn span(&self) -> $crate::span::Span {
+                self.span
+            }
+        }
+
+        impl<$($generics)*> $crate::IntoOwned for $name<$($generics)*> {
+            type Owned = ast_types!(@owned_type $name [ $($generics)* ]);
+
+            fn into_owned(self) -> Self::Owned {
+                $name {
+                    $($field: $crate::IntoOwned::into_owned(self.$field),)*
+                    span: self.span,
+                }
+            }
+        }
+
+        impl<$($generics)*> $crate::span::SpanlessEq for $name<$($generics)*> {
+            fn spanless_eq(&self, other: &Self) -> bool {
+                true $(&& $crate::span::SpanlessEq::spanless_eq(&self.$field, &other.$field))*
+            }
         }
     };
     (@build_struct_decl
@@ -265,15 +460,200 @@ macro_rules! __ast_struct {
         }
     );
 
+    (@build_writer
+        { [ $($generics:tt)* ] [ $($bounds:tt)* ] $name:ident $($field:ident)* }
+        { }
+    ) => (
+        impl<$($generics)*> $crate::print::ToWebIdl for $name<$($generics)*> where $($bounds)* {
+            fn write_webidl(&self, printer: &mut $crate::print::Printer) {
+                $(__ast_struct!(@write_field $name $field &self.$field, printer);)*
+            }
+        }
+    );
+    // Structs with marker fields cannot be printed from their fields alone and are printed by
+    // hand
+    (@build_writer
+        { $($prev:tt)* }
+        { $field:ident : $type:ty = marker, $($rest:tt)* }
+    ) => ();
+    (@build_writer
+        { $($prev:tt)* }
+        { $field:ident : $type:ty = $submac:ident!( $($args:tt)* ), $($rest:tt)* }
+    ) => (
+        __ast_struct! {
+            @build_writer
+            { $($prev)* $field }
+            { $($rest)* }
+        }
+    );
+    (@build_writer
+        { $($prev:tt)* }
+        { $field:ident : $type:ty, $($rest:tt)* }
+    ) => (
+        __ast_struct! {
+            @build_writer
+            { $($prev)* $field }
+            { $($rest)* }
+        }
+    );
+
+    // Names of arguments, attributes and operations may be some keywords without a leading `_`
+    (@write_field SingleArgument identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name ARGUMENT_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_field VariadicArgument identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name ARGUMENT_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_field AttributeInterfaceMember identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name ATTRIBUTE_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_field AttributeMixinMember identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name ATTRIBUTE_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_field AttributeNamespaceMember identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name ATTRIBUTE_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_field OperationInterfaceMember identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name OPERATION_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_field OperationMixinMember identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name OPERATION_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_field OperationNamespaceMember identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name OPERATION_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_field TypedefDefinition identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name TYPEDEF_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_field $name:ident $field:ident $value:expr, $printer:ident) => (
+        $crate::print::ToWebIdl::write_webidl($value, $printer)
+    );
+    (@write_name $keywords:ident $value:expr, $printer:ident) => ({
+        $printer.name_keywords($crate::print::$keywords);
+        $crate::print::ToWebIdl::write_webidl($value, $printer);
+        $printer.name_keywords(&[]);
+    });
+
+    (@build_walker
+        { [ $($impl_generics:tt)* ] [ $($generics:tt)* ] $name:ident $($field:ident)* }
+        { }
+    ) => (
+        impl<'a $($impl_generics)*> $crate::visit::Walk<'a> for $name<$($generics)*> {
+            fn walk<V: $crate::visit::Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+                $($crate::visit::Accept::accept(&self.$field, visitor);)*
+            }
+            fn walk_mut<V: $crate::visit::VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+                $($crate::visit::Accept::accept_mut(&mut self.$field, visitor);)*
+            }
+        }
+    );
+    (@build_walker
+        { $($prev:tt)* }
+        { $field:ident : $type:ty = $($parser:tt)* }
+    ) => (
+        __ast_struct! {
+            @build_walker_skip
+            { $($prev)* $field }
+            { $($parser)* }
+        }
+    );
+    (@build_walker
+        { $($prev:tt)* }
+        { $field:ident : $type:ty, $($rest:tt)* }
+    ) => (
+        __ast_struct! {
+            @build_walker
+            { $($prev)* $field }
+            { $($rest)* }
+        }
+    );
+    // Skips the parser of the field, up to the comma ending it
+    (@build_walker_skip
+        { $($prev:tt)* }
+        { marker, $($rest:tt)* }
+    ) => (
+        __ast_struct! {
+            @build_walker
+            { $($prev)* }
+            { $($rest)* }
+        }
+    );
+    (@build_walker_skip
+        { $($prev:tt)* }
+        { $submac:ident!( $($args:tt)* ), $($rest:tt)* }
+    ) => (
+        __ast_struct! {
+            @build_walker
+            { $($prev)* }
+            { $($rest)* }
+        }
+    );
+
+    // Marker fields are not parsed, so they have no tokens
+    (@build_tokens
+        { [ $($generics:tt)* ] [ $($bounds:tt)* ] $name:ident $($field:ident)* }
+        { }
+    ) => (
+        impl<$($generics)*> $crate::trivia::Tokens for $name<$($generics)*> where $($bounds)* {
+            fn tokens<'t>(
+                &'t self,
+                tokens: &mut Vec<($crate::span::Span, Option<&'t $crate::trivia::TokenTrivia>)>,
+            ) {
+                $($crate::trivia::Tokens::tokens(&self.$field, tokens);)*
+            }
+
+            fn tokens_mut<'t>(
+                &'t mut self,
+                tokens: &mut Vec<($crate::span::Span, &'t mut Option<Box<$crate::trivia::TokenTrivia>>)>,
+            ) {
+                $($crate::trivia::Tokens::tokens_mut(&mut self.$field, tokens);)*
+            }
+        }
+    );
+    (@build_tokens
+        { $($prev:tt)* }
+        { $field:ident : $type:ty = marker, $($rest:tt)* }
+    ) => (
+        __ast_struct! {
+            @build_tokens
+            { $($prev)* }
+            { $($rest)* }
+        }
+    );
+    (@build_tokens
+        { $($prev:tt)* }
+        { $field:ident : $type:ty = $submac:ident!( $($args:tt)* ), $($rest:tt)* }
+    ) => (
+        __ast_struct! {
+            @build_tokens
+            { $($prev)* $field }
+            { $($rest)* }
+        }
+    );
+    (@build_tokens
+        { $($prev:tt)* }
+        { $field:ident : $type:ty, $($rest:tt)* }
+    ) => (
+        __ast_struct! {
+            @build_tokens
+            { $($prev)* $field }
+            { $($rest)* }
+        }
+    );
+
+    (@name_frame identifier $value:ident) => ($crate::state::name_frame(&$value));
+    (@name_frame lhs_identifier $value:ident) => ($crate::state::name_frame(&$value));
+    (@name_frame $field:ident $value:ident) => ();
+
     (@build_parser
-        { $i:expr, $($field:ident)* }
+        { $guard:ident, $input:ident, $i:expr, $($field:ident)* }
         { }
     ) => ({
         use $crate::nom::lib::std::result::Result::Ok;
-        Ok(($i, Self { $($field,)* }))
+        Ok(($i, Self { $($field,)* span: $guard.span($input, $i) }))
     });
     (@build_parser
-        { $i:expr, $($prev:tt)* }
+        { $guard:ident, $input:ident, $i:expr, $($prev:tt)* }
         { $field:ident : $type:ty = $submac:ident!( $($args:tt)* ), $($rest:tt)* }
     ) => ({
         use $crate::nom::lib::std::result::Result::*;
@@ -281,9 +661,10 @@ macro_rules! __ast_struct {
         match $submac!($i, $($args)*) {
             Err(e) => Err(e),
             Ok((i, $field)) => {
+                __ast_struct!(@name_frame $field $field);
                 __ast_struct! {
                     @build_parser
-                    { i, $($prev)* $field }
+                    { $guard, $input, i, $($prev)* $field }
                     { $($rest)* }
                 }
             },
@@ -331,13 +712,32 @@ macro_rules! __ast_struct {
 
         impl<'a> $crate::Parse<'a> for $name {
             fn parse(input: &'a str) -> $crate::IResult<&'a str, Self> {
+                let guard = $crate::state::enter_node(input, stringify!($name));
                 __ast_struct! {
                     @build_parser
-                    { input, }
+                    { guard, input, input, }
                     { $($fields)* }
                 }
             }
         }
+
+        __ast_struct! {
+            @build_writer
+            { [ ] [ ] $name }
+            { $($fields)* }
+        }
+
+        __ast_struct! {
+            @build_walker
+            { [ ] [ ] $name }
+            { $($fields)* }
+        }
+
+        __ast_struct! {
+            @build_tokens
+            { [ ] [ ] $name }
+            { $($fields)* }
+        }
     };
     (
         @launch_pad
@@ -359,13 +759,32 @@ macro_rules! __ast_struct {
 
         impl<'a> $crate::Parse<'a> for $name<'a> {
             fn parse(input: &'a str) -> $crate::IResult<&'a str, Self> {
+                let guard = $crate::state::enter_node(input, stringify!($name));
                 __ast_struct! {
                     @build_parser
-                    { input, }
+                    { guard, input, input, }
                     { $($fields)* }
                 }
             }
         }
+
+        __ast_struct! {
+            @build_writer
+            { [ 'a ] [ ] $name }
+            { $($fields)* }
+        }
+
+        __ast_struct! {
+            @build_walker
+            { [ ] [ 'a ] $name }
+            { $($fields)* }
+        }
+
+        __ast_struct! {
+            @build_tokens
+            { [ 'a ] [ ] $name }
+            { $($fields)* }
+        }
     };
     (
         @launch_pad
@@ -378,6 +797,7 @@ macro_rules! __ast_struct {
         __ast_struct! {
             @build_struct_decl
             {
+                @wrapper
                 $(#[$attr])*
                 $name
                 [$($generics),+]
@@ -387,13 +807,56 @@ macro_rules! __ast_struct {
 
         impl<'a, $($generics),+> $crate::Parse<'a> for $name<$($generics),+> where $($bounds)+ {
             fn parse(input: &'a str) -> $crate::IResult<&'a str, Self> {
+                let guard = $crate::state::enter_node(input, stringify!($name));
                 __ast_struct! {
                     @build_parser
-                    { input, }
+                    { guard, input, input, }
                     { $($fields)* }
                 }
             }
         }
+
+        __ast_struct! {
+            @build_writer
+            {
+                [ $($generics),+ ]
+                [ $($generics: $crate::print::ToWebIdl),+ ]
+                $name
+            }
+            { $($fields)* }
+        }
+
+        __ast_struct! {
+            @build_walker
+            {
+                [ $(, $generics: $crate::visit::Accept<'a> + 'a)+ ]
+                [ $($generics),+ ]
+                $name
+            }
+            { $($fields)* }
+        }
+
+        __ast_struct! {
+            @build_tokens
+            {
+                [ $($generics),+ ]
+                [ $($generics: $crate::trivia::Tokens),+ ]
+                $name
+            }
+            { $($fields)* }
+        }
+
+        // Generic structs only wrap other nodes and have no visitor method of their own
+        impl<'a, $($generics: $crate::visit::Accept<'a> + 'a),+> $crate::visit::Accept<'a>
+            for $name<$($generics),+>
+        {
+            fn accept<V: $crate::visit::Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+                $crate::visit::Walk::walk(self, visitor);
+            }
+            fn accept_mut<V: $crate::visit::VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+                $crate::visit::Walk::walk_mut(self, visitor);
+            }
+        }
     };
 }
 
@@ -409,9 +872,35 @@ macro_rules! __ast_enum {
     ) => (
         $(#[$attr])*
         #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[allow(clippy::large_enum_variant)]
         pub enum $name<$($maybe_a)*> {
             $($variant($member),)*
         }
+
+        impl<$($maybe_a)*> $crate::IntoOwned for $name<$($maybe_a)*> {
+            type Owned = ast_types!(@owned_type $name [ $($maybe_a)* ]);
+
+            fn into_owned(self) -> Self::Owned {
+                match self {
+                    $($name::$variant(member) => {
+                        $name::$variant($crate::IntoOwned::into_owned(member))
+                    })*
+                }
+            }
+        }
+
+        impl<$($maybe_a)*> $crate::span::SpanlessEq for $name<$($maybe_a)*> {
+            fn spanless_eq(&self, other: &Self) -> bool {
+                match (self, other) {
+                    $(($name::$variant(a), $name::$variant(b)) => {
+                        $crate::span::SpanlessEq::spanless_eq(a, b)
+                    })*
+                    #[allow(unreachable_patterns)]
+                    _ => false,
+                }
+            }
+        }
     );
     (@build_enum_decl
         { $($prev:tt)* }
@@ -519,6 +1008,122 @@ macro_rules! __ast_enum {
         }
     );
 
+    (@build_spanned
+        { $name:ident [ $($maybe_a:tt)* ] $($variant:ident)* }
+        { }
+    ) => (
+        impl<$($maybe_a)*> $crate::span::Spanned for $name<$($maybe_a)*> {
+            fn span(&self) -> $crate::span::Span {
+                match self {
+                    $($name::$variant(x) => $crate::span::Spanned::span(x),)*
+                }
+            }
+        }
+    );
+    (@build_spanned
+        { $($prev:tt)* }
+        { $(#[$attr:meta])* $variant:ident( $($member:tt)* ), $($rest:tt)* }
+    ) => (
+        __ast_enum! {
+            @build_spanned
+            { $($prev)* $variant }
+            { $($rest)* }
+        }
+    );
+
+    (@build_writer
+        { $name:ident [ $($maybe_a:tt)* ] $($variant:ident)* }
+        { }
+    ) => (
+        impl<$($maybe_a)*> $crate::print::ToWebIdl for $name<$($maybe_a)*> {
+            fn write_webidl(&self, printer: &mut $crate::print::Printer) {
+                match self {
+                    $($name::$variant(x) => __ast_enum!(@write_variant $name $variant x, printer),)*
+                }
+            }
+        }
+    );
+    // `undefined` is only a keyword of return types here, in other types it is a name
+    (@write_variant NonAnyType Identifier $value:expr, $printer:ident) => (
+        __ast_struct!(@write_name TYPE_NAME_KEYWORDS $value, $printer)
+    );
+    (@write_variant $name:ident $variant:ident $value:expr, $printer:ident) => (
+        $crate::print::ToWebIdl::write_webidl($value, $printer)
+    );
+    (@build_writer
+        { $($prev:tt)* }
+        { $(#[$attr:meta])* $variant:ident( $($member:tt)* ), $($rest:tt)* }
+    ) => (
+        __ast_enum! {
+            @build_writer
+            { $($prev)* $variant }
+            { $($rest)* }
+        }
+    );
+
+    (@build_walker
+        { $name:ident [ $($maybe_a:tt)* ] $($variant:ident)* }
+        { }
+    ) => (
+        impl<'a> $crate::visit::Walk<'a> for $name<$($maybe_a)*> {
+            fn walk<V: $crate::visit::Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+                match self {
+                    $($name::$variant(x) => $crate::visit::Accept::accept(x, visitor),)*
+                }
+            }
+            fn walk_mut<V: $crate::visit::VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+                match self {
+                    $($name::$variant(x) => $crate::visit::Accept::accept_mut(x, visitor),)*
+                }
+            }
+        }
+    );
+    (@build_walker
+        { $($prev:tt)* }
+        { $(#[$attr:meta])* $variant:ident( $($member:tt)* ), $($rest:tt)* }
+    ) => (
+        __ast_enum! {
+            @build_walker
+            { $($prev)* $variant }
+            { $($rest)* }
+        }
+    );
+
+    (@build_tokens
+        { $name:ident [ $($maybe_a:tt)* ] $($variant:ident)* }
+        { }
+    ) => (
+        impl<$($maybe_a)*> $crate::trivia::Tokens for $name<$($maybe_a)*> {
+            fn tokens<'t>(
+                &'t self,
+                tokens: &mut Vec<($crate::span::Span, Option<&'t $crate::trivia::TokenTrivia>)>,
+            ) {
+                match self {
+                    $($name::$variant(x) => $crate::trivia::Tokens::tokens(x, tokens),)*
+                }
+            }
+
+            fn tokens_mut<'t>(
+                &'t mut self,
+                tokens: &mut Vec<($crate::span::Span, &'t mut Option<Box<$crate::trivia::TokenTrivia>>)>,
+            ) {
+                match self {
+                    $($name::$variant(x) => $crate::trivia::Tokens::tokens_mut(x, tokens),)*
+                }
+            }
+        }
+    );
+    (@build_tokens
+        { $($prev:tt)* }
+        { $(#[$attr:meta])* $variant:ident( $($member:tt)* ), $($rest:tt)* }
+    ) => (
+        __ast_enum! {
+            @build_tokens
+            { $($prev)* $variant }
+            { $($rest)* }
+        }
+    );
+
     (@launch_pad
         $(#[$attr:meta])*
         $name:ident
@@ -548,9 +1153,52 @@ macro_rules! __ast_enum {
             { $name [ $($maybe_a)* ] }
             { $($variants)* }
         }
+
+        __ast_enum! {
+            @build_spanned
+            { $name [ $($maybe_a)* ] }
+            { $($variants)* }
+        }
+
+        __ast_enum! {
+            @build_writer
+            { $name [ $($maybe_a)* ] }
+            { $($variants)* }
+        }
+
+        __ast_enum! {
+            @build_walker
+            { $name [ $($maybe_a)* ] }
+            { $($variants)* }
+        }
+
+        __ast_enum! {
+            @build_tokens
+            { $name [ $($maybe_a)* ] }
+            { $($variants)* }
+        }
     );
 }
 
+/// Like `assert_eq!`, but ignores the spans of the nodes
+#[cfg(test)]
+macro_rules! assert_spanless_eq {
+    ($left:expr, $right:expr) => {
+        assert_spanless_eq!($left, $right, "")
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => assert!(
+                $crate::span::SpanlessEq::spanless_eq(left, right),
+                "assertion failed: `left.spanless_eq(right)` {}\n  left: `{:?}`\n right: `{:?}`",
+                format_args!($($arg)+),
+                left,
+                right,
+            ),
+        }
+    };
+}
+
 #[cfg(test)]
 macro_rules! test {
     (@arg $parsed:ident) => {};
@@ -558,8 +1206,8 @@ macro_rules! test {
         assert_eq!($parsed.$($lhs).+, $rhs);
         test!(@arg $parsed $($rest)*);
     };
-    (@arg $parsed:ident $($lhs:tt).+(); $($rest:tt)*) => {
-        assert!($parsed.$($lhs).+());
+    (@arg $parsed:ident $($lhs:tt).+($($args:tt)*); $($rest:tt)*) => {
+        assert!($parsed.$($lhs).+($($args)*));
         test!(@arg $parsed $($rest)*);
     };
     (@arg $parsed:ident $($lhs:tt).+() == $rhs:expr; $($rest:tt)*) => {
@@ -577,7 +1225,7 @@ macro_rules! test {
         fn $name() {
             let (rem, parsed) = <$typ>::parse($raw).unwrap();
             assert_eq!(rem, $rem);
-            assert_eq!(parsed, $val);
+            assert_spanless_eq!(parsed, $val);
         }
     };
     ($name:ident { $raw:expr => $rem:expr; $typ:ty; $($body:tt)* }) => {
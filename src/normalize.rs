@@ -0,0 +1,584 @@
+//! Types with typedefs expanded and unions flattened
+//!
+//! A [`Normalizer`](struct.Normalizer.html) turns a parsed [`Type`](../types/enum.Type.html) into
+//! a [`NormalType`](enum.NormalType.html): typedefs are replaced by the types they stand for,
+//! nested unions are flattened into their [flattened member
+//! types](https://webidl.spec.whatwg.org/#dfn-flattened-union-member-types), and nullability of
+//! union members moves to the union itself. Extended attributes on types are dropped.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::normalize::Normalizer;
+//! use weedle::semantic::Model;
+//! use weedle::Definition;
+//!
+//! let parsed = weedle::parse("
+//!     typedef (DOMString or sequence<Key>?) Keys;
+//!     typedef long Key;
+//!     interface Storage { attribute (Keys or boolean) keys; };
+//! ").unwrap();
+//! let model = Model::new(&parsed);
+//! let normalizer = Normalizer::new(&model);
+//!
+//! let attribute = match &parsed[2] {
+//!     Definition::Interface(interface) => match &interface.members.body[0] {
+//!         weedle::interface::InterfaceMember::Attribute(attribute) => attribute,
+//!         _ => unreachable!(),
+//!     },
+//!     _ => unreachable!(),
+//! };
+//! let normal = normalizer.normalize(&attribute.type_.type_).unwrap();
+//! assert_eq!(normal.to_string(), "(DOMString or sequence<long> or boolean)?");
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::semantic::Model;
+use crate::types::{
+    FloatingPointType, IntegerType, NonAnyType, RecordKeyType, ReturnType, SingleType, Type,
+    UnionMemberType, UnionType,
+};
+
+/// A type with typedefs expanded
+///
+/// Unions are flattened: their members are neither unions nor nullable. A nullable type is
+/// never nullable again, and `any` is never nullable.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum NormalType<'a> {
+    Any,
+    Undefined,
+    /// A type named by keywords. Ex: `unsigned long`
+    Builtin(Builtin),
+    /// An interface, callback interface, dictionary, enum or callback, or a name which is not
+    /// defined
+    Named(&'a str),
+    Sequence(Box<NormalType<'a>>),
+    FrozenArray(Box<NormalType<'a>>),
+    ObservableArray(Box<NormalType<'a>>),
+    Promise(Box<NormalType<'a>>),
+    Record(Box<NormalType<'a>>, Box<NormalType<'a>>),
+    Nullable(Box<NormalType<'a>>),
+    Union(Vec<NormalType<'a>>),
+}
+
+/// Types named by keywords
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Builtin {
+    Boolean,
+    Byte,
+    Octet,
+    Short,
+    UnsignedShort,
+    Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong,
+    Float,
+    UnrestrictedFloat,
+    Double,
+    UnrestrictedDouble,
+    Bigint,
+    ByteString,
+    DOMString,
+    USVString,
+    Object,
+    Symbol,
+    Error,
+    ArrayBuffer,
+    DataView,
+    Int8Array,
+    Int16Array,
+    Int32Array,
+    Uint8Array,
+    Uint16Array,
+    Uint32Array,
+    Uint8ClampedArray,
+    Float16Array,
+    Float32Array,
+    Float64Array,
+    BigInt64Array,
+    BigUint64Array,
+    ArrayBufferView,
+    BufferSource,
+}
+
+impl Builtin {
+    /// The type as written in WebIDL
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Builtin::Boolean => "boolean",
+            Builtin::Byte => "byte",
+            Builtin::Octet => "octet",
+            Builtin::Short => "short",
+            Builtin::UnsignedShort => "unsigned short",
+            Builtin::Long => "long",
+            Builtin::UnsignedLong => "unsigned long",
+            Builtin::LongLong => "long long",
+            Builtin::UnsignedLongLong => "unsigned long long",
+            Builtin::Float => "float",
+            Builtin::UnrestrictedFloat => "unrestricted float",
+            Builtin::Double => "double",
+            Builtin::UnrestrictedDouble => "unrestricted double",
+            Builtin::Bigint => "bigint",
+            Builtin::ByteString => "ByteString",
+            Builtin::DOMString => "DOMString",
+            Builtin::USVString => "USVString",
+            Builtin::Object => "object",
+            Builtin::Symbol => "symbol",
+            Builtin::Error => "Error",
+            Builtin::ArrayBuffer => "ArrayBuffer",
+            Builtin::DataView => "DataView",
+            Builtin::Int8Array => "Int8Array",
+            Builtin::Int16Array => "Int16Array",
+            Builtin::Int32Array => "Int32Array",
+            Builtin::Uint8Array => "Uint8Array",
+            Builtin::Uint16Array => "Uint16Array",
+            Builtin::Uint32Array => "Uint32Array",
+            Builtin::Uint8ClampedArray => "Uint8ClampedArray",
+            Builtin::Float16Array => "Float16Array",
+            Builtin::Float32Array => "Float32Array",
+            Builtin::Float64Array => "Float64Array",
+            Builtin::BigInt64Array => "BigInt64Array",
+            Builtin::BigUint64Array => "BigUint64Array",
+            Builtin::ArrayBufferView => "ArrayBufferView",
+            Builtin::BufferSource => "BufferSource",
+        }
+    }
+
+    fn integer(ty: &IntegerType) -> Self {
+        match ty {
+            IntegerType::LongLong(t) if t.unsigned.is_some() => Builtin::UnsignedLongLong,
+            IntegerType::LongLong(_) => Builtin::LongLong,
+            IntegerType::Long(t) if t.unsigned.is_some() => Builtin::UnsignedLong,
+            IntegerType::Long(_) => Builtin::Long,
+            IntegerType::Short(t) if t.unsigned.is_some() => Builtin::UnsignedShort,
+            IntegerType::Short(_) => Builtin::Short,
+        }
+    }
+
+    fn floating_point(ty: &FloatingPointType) -> Self {
+        match ty {
+            FloatingPointType::Float(t) if t.unrestricted.is_some() => Builtin::UnrestrictedFloat,
+            FloatingPointType::Float(_) => Builtin::Float,
+            FloatingPointType::Double(t) if t.unrestricted.is_some() => Builtin::UnrestrictedDouble,
+            FloatingPointType::Double(_) => Builtin::Double,
+        }
+    }
+}
+
+impl<'a> NormalType<'a> {
+    /// Returns `true` for nullable types
+    pub fn is_nullable(&self) -> bool {
+        matches!(self, NormalType::Nullable(_))
+    }
+
+    /// The type without its nullability
+    pub fn inner(&self) -> &NormalType<'a> {
+        match self {
+            NormalType::Nullable(inner) => inner,
+            _ => self,
+        }
+    }
+
+    /// The flattened member types of a union, or the type itself if it is no union
+    pub fn members(&self) -> &[NormalType<'a>] {
+        match self.inner() {
+            NormalType::Union(members) => members,
+            inner => std::slice::from_ref(inner),
+        }
+    }
+
+    fn nullable(self) -> Self {
+        match self {
+            NormalType::Nullable(_) | NormalType::Any => self,
+            _ => NormalType::Nullable(Box::new(self)),
+        }
+    }
+}
+
+impl<'a> fmt::Display for NormalType<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NormalType::Any => write!(f, "any"),
+            NormalType::Undefined => write!(f, "undefined"),
+            NormalType::Builtin(builtin) => write!(f, "{}", builtin.as_str()),
+            NormalType::Named(name) => write!(f, "{}", name),
+            NormalType::Sequence(inner) => write!(f, "sequence<{}>", inner),
+            NormalType::FrozenArray(inner) => write!(f, "FrozenArray<{}>", inner),
+            NormalType::ObservableArray(inner) => write!(f, "ObservableArray<{}>", inner),
+            NormalType::Promise(inner) => write!(f, "Promise<{}>", inner),
+            NormalType::Record(key, value) => write!(f, "record<{}, {}>", key, value),
+            NormalType::Nullable(inner) => write!(f, "{}?", inner),
+            NormalType::Union(members) => {
+                write!(f, "(")?;
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " or ")?;
+                    }
+                    write!(f, "{}", member)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Typedefs which refer to themselves, directly or through other typedefs
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypedefCycle<'a> {
+    /// Names of the typedefs in the cycle, each referring to the next and the last one to the
+    /// first
+    pub names: Vec<&'a str>,
+}
+
+impl<'a> fmt::Display for TypedefCycle<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "typedef cycle: ")?;
+        for name in &self.names {
+            write!(f, "{} -> ", name)?;
+        }
+        write!(f, "{}", self.names.first().copied().unwrap_or(""))
+    }
+}
+
+/// Expands the typedefs of a [`Model`](../semantic/struct.Model.html)
+#[derive(Clone, Debug)]
+pub struct Normalizer<'a> {
+    typedefs: HashMap<&'a str, &'a Type<'a>>,
+}
+
+impl<'a> Normalizer<'a> {
+    pub fn new(model: &Model<'a>) -> Self {
+        Normalizer {
+            typedefs: model
+                .typedefs
+                .iter()
+                .map(|(&name, typedef)| (name, &typedef.node.type_.type_))
+                .collect(),
+        }
+    }
+
+    /// Normalizes a type
+    pub fn normalize(&self, ty: &'a Type<'a>) -> Result<NormalType<'a>, TypedefCycle<'a>> {
+        self.type_(ty, &mut Vec::new())
+    }
+
+    /// Normalizes a return type, which can be `undefined`
+    pub fn normalize_return_type(
+        &self,
+        ty: &'a ReturnType<'a>,
+    ) -> Result<NormalType<'a>, TypedefCycle<'a>> {
+        self.return_type(ty, &mut Vec::new())
+    }
+
+    /// Normalizes a single type which is not `any`
+    pub fn normalize_non_any(
+        &self,
+        ty: &'a NonAnyType<'a>,
+    ) -> Result<NormalType<'a>, TypedefCycle<'a>> {
+        self.non_any(ty, &mut Vec::new())
+    }
+
+    /// The normalized type the typedef `name` stands for, `None` if there is no such typedef
+    pub fn expand_typedef(&self, name: &str) -> Option<Result<NormalType<'a>, TypedefCycle<'a>>> {
+        let (&name, _) = self.typedefs.get_key_value(name)?;
+        Some(self.named(name, &mut Vec::new()))
+    }
+
+    /// Every typedef cycle, each reported once and starting with its first name in
+    /// alphabetical order
+    pub fn cycles(&self) -> Vec<TypedefCycle<'a>> {
+        let mut names: Vec<_> = self.typedefs.keys().copied().collect();
+        names.sort_unstable();
+        let mut cycles = Vec::new();
+        for name in names {
+            if let Err(mut cycle) = self.named(name, &mut Vec::new()) {
+                let first = (0..cycle.names.len()).min_by_key(|&i| cycle.names[i]);
+                cycle.names.rotate_left(first.unwrap_or(0));
+                if !cycles.contains(&cycle) {
+                    cycles.push(cycle);
+                }
+            }
+        }
+        cycles
+    }
+
+    /// The [number of nullable member
+    /// types](https://webidl.spec.whatwg.org/#dfn-number-of-nullable-member-types) of a union
+    pub fn nullable_member_types(
+        &self,
+        union: &'a UnionType<'a>,
+    ) -> Result<usize, TypedefCycle<'a>> {
+        let mut count = 0;
+        for member in &union.body.list {
+            count += match member {
+                UnionMemberType::Single(single) => {
+                    match self.non_any(&single.type_, &mut Vec::new())? {
+                        NormalType::Nullable(_) => 1,
+                        _ => 0,
+                    }
+                }
+                UnionMemberType::Union(nested) => {
+                    nested.q_mark.is_some() as usize + self.nullable_member_types(&nested.type_)?
+                }
+            };
+        }
+        Ok(count)
+    }
+
+    fn type_(
+        &self,
+        ty: &'a Type<'a>,
+        stack: &mut Vec<&'a str>,
+    ) -> Result<NormalType<'a>, TypedefCycle<'a>> {
+        match ty {
+            Type::Single(SingleType::Any(_)) => Ok(NormalType::Any),
+            Type::Single(SingleType::NonAny(ty)) => self.non_any(ty, stack),
+            Type::Union(union) => {
+                let normal = self.union(&union.type_, stack)?;
+                Ok(if union.q_mark.is_some() {
+                    normal.nullable()
+                } else {
+                    normal
+                })
+            }
+        }
+    }
+
+    fn return_type(
+        &self,
+        ty: &'a ReturnType<'a>,
+        stack: &mut Vec<&'a str>,
+    ) -> Result<NormalType<'a>, TypedefCycle<'a>> {
+        match ty {
+            ReturnType::Undefined(_) => Ok(NormalType::Undefined),
+            ReturnType::Type(ty) => self.type_(ty, stack),
+        }
+    }
+
+    fn union(
+        &self,
+        union: &'a UnionType<'a>,
+        stack: &mut Vec<&'a str>,
+    ) -> Result<NormalType<'a>, TypedefCycle<'a>> {
+        let mut members = Vec::new();
+        let mut nullable = false;
+        for member in &union.body.list {
+            let normal = match member {
+                UnionMemberType::Single(single) => self.non_any(&single.type_, stack)?,
+                UnionMemberType::Union(nested) => {
+                    let normal = self.union(&nested.type_, stack)?;
+                    nullable |= nested.q_mark.is_some();
+                    normal
+                }
+            };
+            let normal = match normal {
+                NormalType::Nullable(inner) => {
+                    nullable = true;
+                    *inner
+                }
+                normal => normal,
+            };
+            match normal {
+                NormalType::Union(nested) => members.extend(nested),
+                normal => members.push(normal),
+            }
+        }
+        let normal = NormalType::Union(members);
+        Ok(if nullable { normal.nullable() } else { normal })
+    }
+
+    fn non_any(
+        &self,
+        ty: &'a NonAnyType<'a>,
+        stack: &mut Vec<&'a str>,
+    ) -> Result<NormalType<'a>, TypedefCycle<'a>> {
+        macro_rules! builtin {
+            ($ty:expr, $builtin:expr) => {
+                (NormalType::Builtin($builtin), $ty.q_mark.is_some())
+            };
+        }
+
+        let (normal, nullable) = match ty {
+            NonAnyType::Promise(promise) => (
+                NormalType::Promise(Box::new(self.return_type(&promise.generics.body, stack)?)),
+                false,
+            ),
+            NonAnyType::Integer(t) => builtin!(t, Builtin::integer(&t.type_)),
+            NonAnyType::FloatingPoint(t) => builtin!(t, Builtin::floating_point(&t.type_)),
+            NonAnyType::Boolean(t) => builtin!(t, Builtin::Boolean),
+            NonAnyType::Byte(t) => builtin!(t, Builtin::Byte),
+            NonAnyType::Octet(t) => builtin!(t, Builtin::Octet),
+            NonAnyType::Bigint(t) => builtin!(t, Builtin::Bigint),
+            NonAnyType::ByteString(t) => builtin!(t, Builtin::ByteString),
+            NonAnyType::DOMString(t) => builtin!(t, Builtin::DOMString),
+            NonAnyType::USVString(t) => builtin!(t, Builtin::USVString),
+            NonAnyType::Object(t) => builtin!(t, Builtin::Object),
+            NonAnyType::Symbol(t) => builtin!(t, Builtin::Symbol),
+            NonAnyType::Error(t) => builtin!(t, Builtin::Error),
+            NonAnyType::ArrayBuffer(t) => builtin!(t, Builtin::ArrayBuffer),
+            NonAnyType::DataView(t) => builtin!(t, Builtin::DataView),
+            NonAnyType::Int8Array(t) => builtin!(t, Builtin::Int8Array),
+            NonAnyType::Int16Array(t) => builtin!(t, Builtin::Int16Array),
+            NonAnyType::Int32Array(t) => builtin!(t, Builtin::Int32Array),
+            NonAnyType::Uint8Array(t) => builtin!(t, Builtin::Uint8Array),
+            NonAnyType::Uint16Array(t) => builtin!(t, Builtin::Uint16Array),
+            NonAnyType::Uint32Array(t) => builtin!(t, Builtin::Uint32Array),
+            NonAnyType::Uint8ClampedArray(t) => builtin!(t, Builtin::Uint8ClampedArray),
+            NonAnyType::Float16Array(t) => builtin!(t, Builtin::Float16Array),
+            NonAnyType::Float32Array(t) => builtin!(t, Builtin::Float32Array),
+            NonAnyType::Float64Array(t) => builtin!(t, Builtin::Float64Array),
+            NonAnyType::BigInt64Array(t) => builtin!(t, Builtin::BigInt64Array),
+            NonAnyType::BigUint64Array(t) => builtin!(t, Builtin::BigUint64Array),
+            NonAnyType::ArrayBufferView(t) => builtin!(t, Builtin::ArrayBufferView),
+            NonAnyType::BufferSource(t) => builtin!(t, Builtin::BufferSource),
+            NonAnyType::Sequence(t) => (
+                NormalType::Sequence(Box::new(self.type_(&t.type_.generics.body, stack)?)),
+                t.q_mark.is_some(),
+            ),
+            NonAnyType::FrozenArrayType(t) => (
+                NormalType::FrozenArray(Box::new(self.type_(&t.type_.generics.body, stack)?)),
+                t.q_mark.is_some(),
+            ),
+            NonAnyType::ObservableArrayType(t) => (
+                NormalType::ObservableArray(Box::new(self.type_(&t.type_.generics.body, stack)?)),
+                t.q_mark.is_some(),
+            ),
+            NonAnyType::RecordType(t) => {
+                let (key, _, value) = &t.type_.generics.body;
+                let key = match &**key {
+                    RecordKeyType::Byte(_) => NormalType::Builtin(Builtin::ByteString),
+                    RecordKeyType::DOM(_) => NormalType::Builtin(Builtin::DOMString),
+                    RecordKeyType::USV(_) => NormalType::Builtin(Builtin::USVString),
+                    RecordKeyType::NonAny(ty) => self.non_any(ty, stack)?,
+                };
+                let value = self.type_(value, stack)?;
+                (
+                    NormalType::Record(Box::new(key), Box::new(value)),
+                    t.q_mark.is_some(),
+                )
+            }
+            NonAnyType::Identifier(t) => (self.named(&t.type_.0, stack)?, t.q_mark.is_some()),
+        };
+        Ok(if nullable { normal.nullable() } else { normal })
+    }
+
+    fn named(
+        &self,
+        name: &'a str,
+        stack: &mut Vec<&'a str>,
+    ) -> Result<NormalType<'a>, TypedefCycle<'a>> {
+        // `undefined` is a keyword which parses as an identifier outside of return types
+        if name == "undefined" {
+            return Ok(NormalType::Undefined);
+        }
+        let ty = match self.typedefs.get(name) {
+            Some(ty) => ty,
+            None => return Ok(NormalType::Named(name)),
+        };
+        if let Some(start) = stack.iter().position(|&n| n == name) {
+            return Err(TypedefCycle {
+                names: stack[start..].to_vec(),
+            });
+        }
+        stack.push(name);
+        let normal = self.type_(ty, stack);
+        stack.pop();
+        normal
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Parse;
+
+    fn normalize(typedefs: &str, ty: &str) -> Result<String, String> {
+        let parsed = crate::parse(typedefs).unwrap();
+        let model = Model::new(&parsed);
+        let (_, ty) = Type::parse(ty).unwrap();
+        let normal = Normalizer::new(&model).normalize(&ty);
+        normal.map(|t| t.to_string()).map_err(|c| c.to_string())
+    }
+
+    #[test]
+    fn should_keep_plain_types() {
+        for ty in &[
+            "any",
+            "unsigned long long",
+            "unrestricted double?",
+            "sequence<DOMString?>",
+            "record<USVString, FrozenArray<Node>>",
+            "ObservableArray<Float16Array?>",
+            "Promise<undefined>",
+            "Promise<(long or DOMString)?>",
+        ] {
+            assert_eq!(normalize("", ty).unwrap(), *ty);
+        }
+    }
+
+    #[test]
+    fn should_expand_typedef_chains() {
+        let typedefs = "typedef B A; typedef [Clamp] sequence<C> B; typedef octet C;";
+        assert_eq!(normalize(typedefs, "A?").unwrap(), "sequence<octet>?");
+        assert_eq!(
+            normalize(typedefs, "record<DOMString, A>").unwrap(),
+            "record<DOMString, sequence<octet>>"
+        );
+    }
+
+    #[test]
+    fn should_flatten_unions() {
+        let typedefs = "typedef (long or boolean) LB; typedef DOMString? S;";
+        assert_eq!(
+            normalize(typedefs, "(LB or (Node or (object or S)))").unwrap(),
+            "(long or boolean or Node or object or DOMString)?"
+        );
+        assert_eq!(
+            normalize(typedefs, "(sequence<(LB or S)> or Node)").unwrap(),
+            "(sequence<(long or boolean or DOMString)?> or Node)"
+        );
+        assert_eq!(
+            normalize(typedefs, "(LB or Node)?").unwrap(),
+            "(long or boolean or Node)?"
+        );
+        assert_eq!(normalize(typedefs, "S?").unwrap(), "DOMString?");
+    }
+
+    #[test]
+    fn should_detect_cycles() {
+        let typedefs = "typedef sequence<B> A; typedef (A or long) B; typedef B C; typedef C D;";
+        assert_eq!(
+            normalize(typedefs, "D").unwrap_err(),
+            "typedef cycle: B -> A -> B"
+        );
+
+        let source = format!("{} typedef E E;", typedefs);
+        let parsed = crate::parse(&source).unwrap();
+        let model = Model::new(&parsed);
+        let normalizer = Normalizer::new(&model);
+        let cycles: Vec<_> = normalizer.cycles().iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            cycles,
+            ["typedef cycle: A -> B -> A", "typedef cycle: E -> E"]
+        );
+        assert!(normalizer.expand_typedef("C").unwrap().is_err());
+        assert!(normalizer.expand_typedef("Missing").is_none());
+    }
+
+    #[test]
+    fn should_count_nullable_member_types() {
+        let parsed = crate::parse("typedef long? L; typedef (boolean or L) BL;").unwrap();
+        let model = Model::new(&parsed);
+        let normalizer = Normalizer::new(&model);
+        let (_, ty) = Type::parse("(L or (DOMString? or Node)? or BL)").unwrap();
+        let union = match &ty {
+            Type::Union(union) => &union.type_,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(normalizer.nullable_member_types(union).unwrap(), 4);
+    }
+}
@@ -0,0 +1,680 @@
+//! Overloaded operations and constructors
+//!
+//! Operations of an interface or namespace sharing a name, and the constructors of an
+//! interface, form an [`OverloadSet`](struct.OverloadSet.html).
+//! [`Overloads::effective_overload_set`](struct.Overloads.html#method.effective_overload_set)
+//! expands a set into the entries of the specification's [effective overload
+//! set](https://webidl.spec.whatwg.org/#dfn-effective-overload-set) algorithm, expanding
+//! optional and variadic arguments, which bindings dispatch on by argument count and by the
+//! type at the [distinguishing argument
+//! index](https://webidl.spec.whatwg.org/#dfn-distinguishing-argument-index).
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::overload::Overloads;
+//! use weedle::semantic::Model;
+//!
+//! let parsed = weedle::parse("
+//!     interface Canvas {
+//!         undefined draw(Image image, optional long x = 0);
+//!         undefined draw(DOMString path, long... points);
+//!     };
+//!     interface Image {};
+//! ").unwrap();
+//! let model = Model::new(&parsed);
+//! let overloads = Overloads::new(&model);
+//!
+//! let sets = overloads.sets();
+//! assert_eq!(sets[0].name, Some("draw"));
+//!
+//! let entries = overloads.effective_overload_set(&sets[0], Some(3)).unwrap();
+//! let types: Vec<_> = entries
+//!     .iter()
+//!     .map(|entry| entry.types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", "))
+//!     .collect();
+//! assert_eq!(
+//!     types,
+//!     ["Image, long", "Image", "DOMString, long", "DOMString, long, long", "DOMString"]
+//! );
+//!
+//! // `Image` and `DOMString` tell apart calls with one argument
+//! let single: Vec<_> = entries.into_iter().filter(|e| e.types.len() == 1).collect();
+//! assert_eq!(overloads.distinguishing_index(&single), Some(0));
+//! assert!(overloads.check().is_empty());
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::argument::{Argument, ArgumentList};
+use crate::diagnostic::Diagnostic;
+use crate::interface::{InterfaceMember, StringifierOrStatic};
+use crate::mixin::MixinMember;
+use crate::namespace::NamespaceMember;
+use crate::normalize::{Builtin, NormalType, Normalizer, TypedefCycle};
+use crate::semantic::{InterfaceMemberRef, Model};
+use crate::span::{Span, Spanned};
+
+/// Operations or constructors which share a name
+#[derive(Clone, Debug)]
+pub struct OverloadSet<'a> {
+    /// Name of the interface or namespace
+    pub owner: &'a str,
+    /// Name of the operations, `None` for constructors
+    pub name: Option<&'a str>,
+    pub kind: OverloadKind,
+    /// Overloads in declaration order, with partial definitions and mixins in model order
+    pub overloads: Vec<Overload<'a>>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum OverloadKind {
+    /// Regular operations of an interface or operations of a namespace
+    Operation,
+    StaticOperation,
+    Constructor,
+}
+
+/// One operation or constructor of an overload set
+#[derive(Clone, Copy, Debug)]
+pub struct Overload<'a> {
+    pub arguments: &'a ArgumentList<'a>,
+    /// Index of the file in the list the model was built from
+    pub file: usize,
+    /// Span of the operation or constructor
+    pub span: Span,
+}
+
+/// How an argument can be passed
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Optionality {
+    Required,
+    Optional,
+    Variadic,
+}
+
+/// One entry of an effective overload set
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry<'a> {
+    /// Index of the overload in [`OverloadSet::overloads`](struct.OverloadSet.html#structfield.overloads)
+    pub overload: usize,
+    pub types: Vec<NormalType<'a>>,
+    pub optionality: Vec<Optionality>,
+}
+
+/// Overload sets of a [`Model`](../semantic/struct.Model.html)
+#[derive(Clone, Debug)]
+pub struct Overloads<'m, 'a> {
+    model: &'m Model<'a>,
+    normalizer: Normalizer<'a>,
+}
+
+/// Categories of the distinguishability table
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Category {
+    Undefined,
+    Boolean,
+    Numeric,
+    Bigint,
+    String,
+    Object,
+    Symbol,
+    InterfaceLike,
+    CallbackFunction,
+    DictionaryLike,
+    SequenceLike,
+}
+
+impl<'m, 'a> Overloads<'m, 'a> {
+    pub fn new(model: &'m Model<'a>) -> Self {
+        Overloads {
+            model,
+            normalizer: Normalizer::new(model),
+        }
+    }
+
+    /// Every overload set of the interfaces and namespaces, including those of a single
+    /// operation or constructor
+    pub fn sets(&self) -> Vec<OverloadSet<'a>> {
+        let mut sets = Vec::new();
+        for interface in self.model.interfaces.values() {
+            let mut named: HashMap<(OverloadKind, &'a str), usize> = HashMap::new();
+            let mut constructors = Vec::new();
+            for member in &interface.members {
+                let (kind, name, arguments) = match member.member {
+                    InterfaceMemberRef::Interface(InterfaceMember::Constructor(c)) => {
+                        constructors.push(Overload {
+                            arguments: &c.args.body,
+                            file: member.origin.file,
+                            span: c.span(),
+                        });
+                        continue;
+                    }
+                    InterfaceMemberRef::Interface(InterfaceMember::Operation(op)) => {
+                        let kind = match op.modifier {
+                            Some(StringifierOrStatic::Static(_)) => OverloadKind::StaticOperation,
+                            _ => OverloadKind::Operation,
+                        };
+                        (kind, op.identifier.as_ref(), &op.args.body)
+                    }
+                    InterfaceMemberRef::Mixin(MixinMember::Operation(op)) => (
+                        OverloadKind::Operation,
+                        op.identifier.as_ref(),
+                        &op.args.body,
+                    ),
+                    _ => continue,
+                };
+                let name: &str = match name {
+                    Some(name) => &name.0,
+                    None => continue,
+                };
+                let overload = Overload {
+                    arguments,
+                    file: member.origin.file,
+                    span: member.member.span(),
+                };
+                let index = *named.entry((kind, name)).or_insert_with(|| {
+                    sets.push(OverloadSet {
+                        owner: interface.name,
+                        name: Some(name),
+                        kind,
+                        overloads: Vec::new(),
+                    });
+                    sets.len() - 1
+                });
+                sets[index].overloads.push(overload);
+            }
+            if !constructors.is_empty() {
+                sets.push(OverloadSet {
+                    owner: interface.name,
+                    name: None,
+                    kind: OverloadKind::Constructor,
+                    overloads: constructors,
+                });
+            }
+        }
+        for namespace in self.model.namespaces.values() {
+            let mut named: HashMap<&'a str, usize> = HashMap::new();
+            for member in &namespace.members {
+                let op = match member.member {
+                    NamespaceMember::Operation(op) => op,
+                    _ => continue,
+                };
+                let name: &str = match &op.identifier {
+                    Some(name) => &name.0,
+                    None => continue,
+                };
+                let index = *named.entry(name).or_insert_with(|| {
+                    sets.push(OverloadSet {
+                        owner: namespace.name,
+                        name: Some(name),
+                        kind: OverloadKind::Operation,
+                        overloads: Vec::new(),
+                    });
+                    sets.len() - 1
+                });
+                sets[index].overloads.push(Overload {
+                    arguments: &op.args.body,
+                    file: member.origin.file,
+                    span: op.span(),
+                });
+            }
+        }
+        sets
+    }
+
+    /// The effective overload set for calls with `argument_count` arguments, or for calls
+    /// with at most as many arguments as the longest overload takes if `None`
+    ///
+    /// Entries are listed by overload, each overload first with all of its arguments, then
+    /// with its variadic argument repeated and then with trailing optional arguments dropped.
+    pub fn effective_overload_set(
+        &self,
+        set: &OverloadSet<'a>,
+        argument_count: Option<usize>,
+    ) -> Result<Vec<Entry<'a>>, TypedefCycle<'a>> {
+        let longest = set
+            .overloads
+            .iter()
+            .map(|o| o.arguments.list.len())
+            .max()
+            .unwrap_or(0);
+        let max = argument_count.map_or(longest, |count| count.max(longest));
+
+        let mut entries = Vec::new();
+        for (index, overload) in set.overloads.iter().enumerate() {
+            let mut types = Vec::new();
+            let mut optionality = Vec::new();
+            for argument in &overload.arguments.list {
+                match argument {
+                    Argument::Single(arg) => {
+                        types.push(self.normalizer.normalize(&arg.type_.type_)?);
+                        optionality.push(match arg.optional {
+                            Some(_) => Optionality::Optional,
+                            None => Optionality::Required,
+                        });
+                    }
+                    Argument::Variadic(arg) => {
+                        types.push(self.normalizer.normalize(&arg.type_)?);
+                        optionality.push(Optionality::Variadic);
+                    }
+                }
+            }
+
+            let mut add = |types: &[NormalType<'a>], optionality: &[Optionality]| {
+                let entry = Entry {
+                    overload: index,
+                    types: types.to_vec(),
+                    optionality: optionality.to_vec(),
+                };
+                if !entries.contains(&entry) {
+                    entries.push(entry);
+                }
+            };
+
+            add(&types, &optionality);
+            if optionality.last() == Some(&Optionality::Variadic) {
+                let mut types = types.clone();
+                let mut optionality = optionality.clone();
+                while types.len() < max {
+                    types.push(types[types.len() - 1].clone());
+                    optionality.push(Optionality::Variadic);
+                    add(&types, &optionality);
+                }
+            }
+            for i in (0..optionality.len()).rev() {
+                if optionality[i] == Optionality::Required {
+                    break;
+                }
+                add(&types[..i], &optionality[..i]);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Lowest index at which the types of every pair of entries are distinguishable, `None`
+    /// if there is no such index or the entries disagree on any argument before it
+    ///
+    /// The entries are expected to have type lists of the same length. A single entry is
+    /// distinguished at index 0.
+    pub fn distinguishing_index(&self, entries: &[Entry<'a>]) -> Option<usize> {
+        let first = entries.first()?;
+        if entries.len() == 1 {
+            return Some(0);
+        }
+        let index = (0..first.types.len()).find(|&i| {
+            entries.iter().enumerate().all(|(a, x)| {
+                entries[a + 1..]
+                    .iter()
+                    .all(|y| self.distinguishable(&x.types[i], &y.types[i]))
+            })
+        })?;
+        let same_prefix = entries.iter().all(|entry| {
+            entry.types[..index] == first.types[..index]
+                && entry.optionality[..index] == first.optionality[..index]
+        });
+        if same_prefix {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if values of the two types can always be told apart, following the
+    /// specification's [distinguishable](https://webidl.spec.whatwg.org/#dfn-distinguishable)
+    /// algorithm
+    ///
+    /// Two interfaces are considered distinguishable unless one inherits from the other.
+    pub fn distinguishable(&self, a: &NormalType<'a>, b: &NormalType<'a>) -> bool {
+        let dictionary = |t: &NormalType<'a>| t.members().iter().any(|m| self.is_dictionary(m));
+        if (a.is_nullable() && (b.is_nullable() || dictionary(b)))
+            || (b.is_nullable() && dictionary(a))
+        {
+            return false;
+        }
+        a.members().iter().all(|x| {
+            b.members()
+                .iter()
+                .all(|y| self.distinguishable_members(x, y))
+        })
+    }
+
+    /// Reports overload sets whose entries cannot be told apart by argument count and type
+    ///
+    /// Each set is reported once, for the smallest argument count which is ambiguous.
+    pub fn check(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for set in self.sets() {
+            let entries = match self.effective_overload_set(&set, None) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            let mut by_count: BTreeMap<usize, Vec<Entry>> = BTreeMap::new();
+            for entry in entries {
+                by_count.entry(entry.types.len()).or_default().push(entry);
+            }
+            let ambiguous = by_count
+                .into_iter()
+                .find(|(_, entries)| self.distinguishing_index(entries).is_none());
+            let (count, entries) = match ambiguous {
+                Some(ambiguous) => ambiguous,
+                None => continue,
+            };
+
+            let first = &set.overloads[entries[0].overload];
+            let last = &set.overloads[entries.iter().map(|e| e.overload).max().unwrap_or(0)];
+            let what = match set.name {
+                Some(name) => format!("overloads of `{}.{}`", set.owner, name),
+                None => format!("constructors of `{}`", set.owner),
+            };
+            let plural = if count == 1 { "" } else { "s" };
+            diagnostics.push(
+                Diagnostic::error(
+                    "indistinguishable-overloads",
+                    format!(
+                        "{} cannot be told apart when called with {} argument{}",
+                        what, count, plural
+                    ),
+                    last.file,
+                    last.span,
+                )
+                .with_note(
+                    "conflicting overload declared here".to_string(),
+                    first.file,
+                    first.span,
+                ),
+            );
+        }
+        diagnostics
+    }
+
+    fn is_dictionary(&self, ty: &NormalType<'a>) -> bool {
+        match ty {
+            NormalType::Named(name) => self.model.dictionaries.contains_key(name),
+            _ => false,
+        }
+    }
+
+    /// Category of a type which is neither nullable nor a union, `None` for `any` and promises
+    fn category(&self, ty: &NormalType<'a>) -> Option<Category> {
+        Some(match ty {
+            NormalType::Any | NormalType::Promise(_) | NormalType::Nullable(_) => return None,
+            NormalType::Union(_) => return None,
+            NormalType::Undefined => Category::Undefined,
+            NormalType::Builtin(builtin) => match builtin {
+                Builtin::Boolean => Category::Boolean,
+                Builtin::Byte
+                | Builtin::Octet
+                | Builtin::Short
+                | Builtin::UnsignedShort
+                | Builtin::Long
+                | Builtin::UnsignedLong
+                | Builtin::LongLong
+                | Builtin::UnsignedLongLong
+                | Builtin::Float
+                | Builtin::UnrestrictedFloat
+                | Builtin::Double
+                | Builtin::UnrestrictedDouble => Category::Numeric,
+                Builtin::Bigint => Category::Bigint,
+                Builtin::ByteString | Builtin::DOMString | Builtin::USVString => Category::String,
+                Builtin::Object => Category::Object,
+                Builtin::Symbol => Category::Symbol,
+                _ => Category::InterfaceLike,
+            },
+            NormalType::Named(name) => {
+                if self.model.enums.contains_key(name) {
+                    Category::String
+                } else if self.model.callbacks.contains_key(name) {
+                    Category::CallbackFunction
+                } else if self.model.dictionaries.contains_key(name)
+                    || self.model.callback_interfaces.contains_key(name)
+                {
+                    Category::DictionaryLike
+                } else {
+                    Category::InterfaceLike
+                }
+            }
+            NormalType::Record(..) => Category::DictionaryLike,
+            NormalType::Sequence(_)
+            | NormalType::FrozenArray(_)
+            | NormalType::ObservableArray(_) => Category::SequenceLike,
+        })
+    }
+
+    /// Distinguishability of two flattened member types
+    fn distinguishable_members(&self, a: &NormalType<'a>, b: &NormalType<'a>) -> bool {
+        use self::Category::*;
+
+        let (x, y) = match (self.category(a), self.category(b)) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return false,
+        };
+        match (x, y) {
+            (InterfaceLike, InterfaceLike) => self.distinguishable_interfaces(a, b),
+            _ if x == y => false,
+            (Undefined, DictionaryLike) | (DictionaryLike, Undefined) => false,
+            (Object, InterfaceLike | CallbackFunction | DictionaryLike | SequenceLike) => false,
+            (InterfaceLike | CallbackFunction | DictionaryLike | SequenceLike, Object) => false,
+            _ => true,
+        }
+    }
+
+    fn distinguishable_interfaces(&self, a: &NormalType<'a>, b: &NormalType<'a>) -> bool {
+        match (a, b) {
+            (NormalType::Builtin(a), NormalType::Builtin(b)) => {
+                let (a, b) = (buffer_types(*a), buffer_types(*b));
+                !a.iter().any(|t| b.contains(t))
+            }
+            (NormalType::Named(a), NormalType::Named(b)) => {
+                a != b && !self.inherits(a, b) && !self.inherits(b, a)
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns `true` if interface `derived` inherits from `base`, directly or not
+    fn inherits(&self, derived: &str, base: &str) -> bool {
+        let mut seen = Vec::new();
+        let mut current = derived;
+        while let Some(parent) = self
+            .model
+            .interfaces
+            .get(current)
+            .and_then(|i| i.definition.as_ref())
+            .and_then(|d| d.node.inheritance.as_ref())
+            .map(|inheritance| &*inheritance.identifier.0)
+        {
+            if parent == base {
+                return true;
+            }
+            if seen.contains(&parent) {
+                return false;
+            }
+            seen.push(parent);
+            current = parent;
+        }
+        false
+    }
+}
+
+/// The types `ArrayBufferView` and `BufferSource` stand for, other types stand for themselves
+fn buffer_types(builtin: Builtin) -> Vec<Builtin> {
+    const VIEWS: [Builtin; 13] = [
+        Builtin::Int8Array,
+        Builtin::Int16Array,
+        Builtin::Int32Array,
+        Builtin::Uint8Array,
+        Builtin::Uint16Array,
+        Builtin::Uint32Array,
+        Builtin::Uint8ClampedArray,
+        Builtin::Float16Array,
+        Builtin::Float32Array,
+        Builtin::Float64Array,
+        Builtin::BigInt64Array,
+        Builtin::BigUint64Array,
+        Builtin::DataView,
+    ];
+    match builtin {
+        Builtin::ArrayBufferView => VIEWS.to_vec(),
+        Builtin::BufferSource => {
+            let mut types = VIEWS.to_vec();
+            types.push(Builtin::ArrayBuffer);
+            types
+        }
+        _ => vec![builtin],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::Type;
+    use crate::Parse;
+
+    const SOURCE: &str = "
+        interface Node {};
+        interface Element : Node {};
+        interface Text : Node {};
+        dictionary Init {};
+        enum Mode { \"a\" };
+        callback Listener = undefined ();
+        callback interface Handler { undefined handle(); };
+    ";
+
+    fn distinguishable(a: &str, b: &str) -> bool {
+        let parsed = crate::parse(SOURCE).unwrap();
+        let model = Model::new(&parsed);
+        let overloads = Overloads::new(&model);
+        let (_, a) = Type::parse(a).unwrap();
+        let (_, b) = Type::parse(b).unwrap();
+        let normalizer = Normalizer::new(&model);
+        let (a, b) = (
+            normalizer.normalize(&a).unwrap(),
+            normalizer.normalize(&b).unwrap(),
+        );
+        overloads.distinguishable(&a, &b)
+    }
+
+    #[test]
+    fn should_distinguish_categories() {
+        for (a, b) in &[
+            ("boolean", "long"),
+            ("DOMString", "double"),
+            ("Mode", "Node"),
+            ("object", "symbol"),
+            ("bigint", "double"),
+            ("(bigint or DOMString)", "boolean"),
+            ("Listener", "Init"),
+            ("sequence<long>", "Init"),
+            ("Element", "Text"),
+            ("ArrayBuffer", "ArrayBufferView"),
+            ("(long or Node)", "DOMString?"),
+            ("Node?", "sequence<long>"),
+        ] {
+            assert!(distinguishable(a, b), "{} and {}", a, b);
+        }
+        for (a, b) in &[
+            ("long", "double"),
+            ("Mode", "USVString"),
+            ("object", "Node"),
+            ("Handler", "record<DOMString, long>"),
+            ("Element", "Node"),
+            ("BufferSource", "Uint8Array"),
+            ("ArrayBufferView", "BigInt64Array"),
+            ("bigint", "bigint?"),
+            ("(long or Node)", "Element"),
+            ("Node?", "Init"),
+            ("DOMString?", "(long or boolean)?"),
+            ("any", "long"),
+        ] {
+            assert!(!distinguishable(a, b), "{} and {}", a, b);
+        }
+    }
+
+    #[test]
+    fn should_compute_effective_overload_sets() {
+        let parsed = crate::parse(
+            "
+            interface Canvas {
+                constructor();
+                constructor(long width, optional long height);
+                undefined fill(optional DOMString rule, long... points);
+                static undefined fill(long color);
+            };
+            ",
+        )
+        .unwrap();
+        let model = Model::new(&parsed);
+        let overloads = Overloads::new(&model);
+        let sets = overloads.sets();
+        let names: Vec<_> = sets.iter().map(|s| (s.name, s.kind)).collect();
+        assert_eq!(
+            names,
+            [
+                (Some("fill"), OverloadKind::Operation),
+                (Some("fill"), OverloadKind::StaticOperation),
+                (None, OverloadKind::Constructor),
+            ]
+        );
+
+        let entries = overloads.effective_overload_set(&sets[2], None).unwrap();
+        let counts: Vec<_> = entries
+            .iter()
+            .map(|e| (e.overload, e.types.len()))
+            .collect();
+        assert_eq!(counts, [(0, 0), (1, 2), (1, 1)]);
+
+        let entries = overloads.effective_overload_set(&sets[0], Some(4)).unwrap();
+        let counts: Vec<_> = entries.iter().map(|e| e.types.len()).collect();
+        assert_eq!(counts, [2, 3, 4, 1, 0]);
+        assert_eq!(
+            entries[2].optionality,
+            [
+                Optionality::Optional,
+                Optionality::Variadic,
+                Optionality::Variadic,
+                Optionality::Variadic
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_indistinguishable_overloads() {
+        let parsed = crate::parse(
+            "
+            interface Node {};
+            interface Element : Node {};
+            interface Tree {
+                undefined add(Node node);
+                undefined add(Element element);
+                undefined remove(long index, optional boolean deep);
+                undefined remove(long index);
+                undefined find(DOMString name, long depth);
+                undefined find(Node root, DOMString name);
+                undefined find(DOMString name, Node root);
+            };
+            namespace Trees { undefined grow(); undefined grow(optional long n); };
+            ",
+        )
+        .unwrap();
+        let model = Model::new(&parsed);
+        let messages: Vec<_> = Overloads::new(&model)
+            .check()
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "error[indistinguishable-overloads]: overloads of `Tree.add` cannot be told \
+                 apart when called with 1 argument",
+                "error[indistinguishable-overloads]: overloads of `Tree.remove` cannot be told \
+                 apart when called with 1 argument",
+                "error[indistinguishable-overloads]: overloads of `Tree.find` cannot be told \
+                 apart when called with 2 arguments",
+                "error[indistinguishable-overloads]: overloads of `Trees.grow` cannot be told \
+                 apart when called with 0 arguments",
+            ]
+        );
+    }
+}
@@ -0,0 +1,691 @@
+//! Turning parsed definitions back into WebIDL source
+//!
+//! Every node implements [`ToWebIdl`](trait.ToWebIdl.html). Printing a node and parsing the
+//! result gives back an equal node. The output is laid out with one member per line, but
+//! comments and the original whitespace are not kept unless printed through
+//! [`trivia::print`](../trivia/fn.print.html).
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::print::ToWebIdl;
+//!
+//! let parsed = weedle::parse("
+//!     [Exposed=Window] interface Storage{ getter DOMString? getItem(DOMString key); };
+//!     typedef ( DOMString or long ) StorageKey;
+//! ").unwrap();
+//!
+//! assert_eq!(
+//!     parsed.to_webidl(),
+//!     "[Exposed=Window]
+//! interface Storage {
+//!     getter DOMString? getItem(DOMString key);
+//! };
+//!
+//! typedef (DOMString or long) StorageKey;
+//! "
+//! );
+//! ```
+
+use crate::term;
+use crate::trivia::{comments_in, Comment, CommentKind, TokenTrivia};
+
+/// Keywords which arguments may be named after, see
+/// [ArgumentNameKeyword](https://webidl.spec.whatwg.org/#prod-ArgumentNameKeyword)
+pub(crate) const ARGUMENT_NAME_KEYWORDS: &[&str] = &[
+    "async",
+    "attribute",
+    "callback",
+    "const",
+    "constructor",
+    "deleter",
+    "dictionary",
+    "enum",
+    "getter",
+    "includes",
+    "inherit",
+    "interface",
+    "iterable",
+    "maplike",
+    "mixin",
+    "namespace",
+    "partial",
+    "readonly",
+    "required",
+    "setlike",
+    "setter",
+    "static",
+    "stringifier",
+    "typedef",
+    "unrestricted",
+];
+
+/// Keywords which attributes may be named after
+pub(crate) const ATTRIBUTE_NAME_KEYWORDS: &[&str] = &["async", "required"];
+
+/// Keywords which operations may be named after
+pub(crate) const OPERATION_NAME_KEYWORDS: &[&str] = &["includes"];
+
+/// Keywords which types may refer to by name
+pub(crate) const TYPE_NAME_KEYWORDS: &[&str] = &["undefined"];
+
+/// Types which are keywords here but typedefs in the standard, which defines them as such
+pub(crate) const TYPEDEF_NAME_KEYWORDS: &[&str] = &["ArrayBufferView", "BufferSource"];
+
+/// Implemented by every node that can be printed as WebIDL
+pub trait ToWebIdl {
+    /// Writes the tokens of the node to `printer`
+    fn write_webidl(&self, printer: &mut Printer);
+
+    /// Prints the node as WebIDL source
+    fn to_webidl(&self) -> String {
+        let mut printer = Printer::new();
+        self.write_webidl(&mut printer);
+        printer.finish()
+    }
+}
+
+/// Lays out tokens as WebIDL source
+///
+/// Tokens are separated by single spaces where needed, bodies of definitions are indented
+/// by four spaces with one member per line, and definitions are separated by blank lines.
+///
+/// A printer made with [`preserving`](#method.preserving) instead writes tokens with the
+/// whitespace and comments kept on them, and one made with
+/// [`with_comments`](#method.with_comments) lays them out as usual but keeps their comments.
+#[derive(Clone, Debug, Default)]
+pub struct Printer {
+    out: String,
+    /// Whether the trivia of tokens is written
+    keep_trivia: bool,
+    /// Whether tokens keep their whitespace, or only their comments
+    keep_layout: bool,
+    /// Argument lists on lines longer than this are split into one argument per line
+    max_width: Option<usize>,
+    /// The last token was written with its trivia
+    kept: bool,
+    /// Trailing trivia of the last token, with comments on the lines after it still to write
+    trailing: String,
+    /// Text of the last token written
+    prev: String,
+    /// Whether the last token was the name of an operation or extended attribute, which
+    /// arguments follow without a space
+    prev_name: bool,
+    /// Number of definition bodies the next token is in
+    indent: usize,
+    /// Number of `[` the next token is in
+    brackets: usize,
+    /// Number of `(` the next token is in
+    parens: usize,
+    /// The last token was `{`; it opens a body unless `}` follows right away
+    open_brace: bool,
+    /// The extended attributes being printed started their line, so the line ends after them
+    attributes_line: bool,
+    /// What goes between the last token and the next one, unless it is taken from the source
+    pending: Break,
+    /// A line comment ended the line, so the next token continues on the next one
+    continuation: bool,
+    /// Where the line being written starts in `out`
+    line_start: usize,
+    /// Indentation of the line being written
+    line_indent: usize,
+    /// Keywords the identifier being written may be without a leading `_`
+    name_keywords: &'static [&'static str],
+    /// The first argument list of the line being written, in case the line gets too long
+    arguments: Option<ArgumentsOnLine>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Break {
+    #[default]
+    None,
+    Line,
+    BlankLine,
+}
+
+/// Offsets into the output of an argument list
+#[derive(Clone, Debug)]
+struct ArgumentsOnLine {
+    /// Number of `(` it is in, including its own
+    depth: usize,
+    /// Right after `(`
+    open: usize,
+    /// Right after each `,` separating arguments
+    commas: Vec<usize>,
+    /// At `)`
+    close: Option<usize>,
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Printer::default()
+    }
+
+    /// Creates a printer which writes the trivia kept on tokens as it is
+    pub fn preserving() -> Self {
+        Printer {
+            keep_trivia: true,
+            keep_layout: true,
+            ..Printer::default()
+        }
+    }
+
+    /// Creates a printer which lays out tokens as usual, but keeps the comments in the trivia
+    /// kept on them
+    ///
+    /// Comments on their own lines stay on their own lines, comments after a token on its line
+    /// stay after it, and single blank lines between members are kept.
+    pub fn with_comments() -> Self {
+        Printer {
+            keep_trivia: true,
+            ..Printer::default()
+        }
+    }
+
+    /// Splits argument lists of lines longer than `width` characters into one argument per
+    /// line. Has no effect on a [`preserving`](#method.preserving) printer.
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Writes a keyword, punctuation or literal with the trivia kept on it
+    pub fn token(&mut self, text: &str, trivia: Option<&TokenTrivia>) {
+        self.write(text, trivia, false);
+    }
+
+    /// Writes an identifier with the trivia kept on it, with a leading `_` if it was `escaped`
+    /// in the source or would otherwise be read as a keyword of the standard grammar
+    pub fn identifier(&mut self, name: &str, escaped: bool, trivia: Option<&TokenTrivia>) {
+        let keyword = term::NAMES.contains(&name)
+            && !term::EXTENSIONS.contains(&name)
+            && !self.name_keywords.contains(&name);
+        if escaped || keyword {
+            self.write(&format!("_{}", name), trivia, true);
+        } else {
+            self.write(name, trivia, true);
+        }
+    }
+
+    /// Sets the keywords which the identifiers written next may be without a leading `_`
+    pub(crate) fn name_keywords(&mut self, keywords: &'static [&'static str]) {
+        self.name_keywords = keywords;
+    }
+
+    /// Writes trivia which is kept on no token, like the trivia after the last one
+    pub fn trivia(&mut self, trivia: &str) {
+        if self.keep_layout {
+            self.out.push_str(trivia);
+            self.kept |= !trivia.is_empty();
+        } else if self.keep_trivia {
+            self.trailing.push_str(trivia);
+        }
+    }
+
+    /// Returns the source written so far, ending with a line break or the trivia written with
+    /// the last token
+    pub fn finish(mut self) -> String {
+        if self.keep_layout {
+            if !self.kept && !self.out.is_empty() && !self.out.ends_with('\n') {
+                self.out.push('\n');
+            }
+            return self.out;
+        }
+
+        if self.keep_trivia {
+            let rest = std::mem::take(&mut self.trailing);
+            if !self.out.is_empty() && !self.out.ends_with('\n') {
+                self.newline();
+            }
+            let comments = own_line_comments(&rest, !self.prev.is_empty());
+            if comments.len() > 1 {
+                self.write_comments(&comments, 0);
+            }
+        }
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.newline();
+        }
+        self.out
+    }
+
+    fn write(&mut self, text: &str, trivia: Option<&TokenTrivia>, identifier: bool) {
+        let mut pending = std::mem::replace(&mut self.pending, Break::None);
+        if self.open_brace {
+            self.open_brace = false;
+            if text != "}" {
+                self.indent += 1;
+                pending = Break::Line;
+            }
+        } else if text == "}" && self.indent > 0 {
+            self.indent -= 1;
+            pending = Break::Line;
+        }
+        let line_start = self.out.is_empty() || pending != Break::None;
+
+        if let Some(trivia) = trivia.filter(|_| self.keep_layout) {
+            // Tokens laid out by the printer leave the space before the next one to it
+            if !self.kept
+                && trivia.leading.is_empty()
+                && !self.out.is_empty()
+                && !self.out.ends_with(char::is_whitespace)
+                && self.needs_space(text)
+            {
+                self.out.push(' ');
+            }
+            // Leading trivia starts a line, even when its token was moved after one which does
+            // not end its line
+            if !trivia.leading.is_empty() && !self.out.is_empty() && !self.out.ends_with('\n') {
+                self.out.push('\n');
+            }
+            self.out.push_str(&trivia.leading);
+            self.out.push_str(text);
+            self.out.push_str(&trivia.trailing);
+            self.kept = true;
+        } else {
+            if self.keep_layout && self.out.ends_with('\n') {
+                // The trivia of the last token ended its line already
+                pending = match pending {
+                    Break::BlankLine if !self.out.ends_with("\n\n") => Break::Line,
+                    _ => Break::None,
+                };
+                self.line_start = self.out.len();
+                self.line_indent = self.indent;
+            }
+            let gap = if self.keep_trivia && !self.keep_layout {
+                self.take_gap(trivia)
+            } else {
+                String::new()
+            };
+            let comments = if self.keep_trivia && !self.keep_layout {
+                own_line_comments(&gap, !self.prev.is_empty())
+            } else {
+                Vec::new()
+            };
+            let blank_line = comments.first().is_some_and(|&(_, blank)| blank);
+            if blank_line && pending == Break::Line && self.prev == ";" && text != "}" {
+                pending = Break::BlankLine;
+            }
+            if comments.len() > 1 && pending == Break::None && !self.out.is_empty() {
+                pending = Break::Line;
+                self.continuation = true;
+            }
+            if pending == Break::None && self.continuation {
+                pending = Break::Line;
+            }
+            match pending {
+                Break::None => {}
+                Break::Line => self.newline(),
+                Break::BlankLine => {
+                    self.newline();
+                    self.newline();
+                }
+            }
+            let comment_indent = self.line_indent + (text == "}") as usize;
+            self.write_comments(&comments[comments.len().min(1)..], comment_indent);
+
+            if self.out.is_empty() || self.out.ends_with('\n') {
+                self.out.push_str(&"    ".repeat(self.line_indent));
+            } else if self.needs_space(text) && !self.out.ends_with(' ') {
+                self.out.push(' ');
+            }
+            self.out.push_str(text);
+            self.kept = false;
+            if let Some(trivia) = trivia.filter(|_| self.keep_trivia && !self.keep_layout) {
+                self.write_trailing_comments(&trivia.trailing, matches!(text, "{" | ";"));
+                self.trailing.clone_from(&trivia.trailing);
+            }
+        }
+
+        let arguments = self.prev_name
+            || self.prev == "constructor"
+            || !(line_start
+                || matches!(
+                    self.prev.as_str(),
+                    "(" | ","
+                        | "<"
+                        | "="
+                        | "]"
+                        | "or"
+                        | "attribute"
+                        | "typedef"
+                        | "optional"
+                        | "required"
+                        | "getter"
+                        | "setter"
+                        | "deleter"
+                        | "legacycaller"
+                        | "static"
+                        | "stringifier"
+                ));
+        match text {
+            "{" => self.open_brace = true,
+            "[" => {
+                if self.brackets == 0 {
+                    self.attributes_line = line_start;
+                }
+                self.brackets += 1;
+            }
+            "]" => {
+                self.brackets = self.brackets.saturating_sub(1);
+                if self.brackets == 0 && self.attributes_line && self.indent == 0 {
+                    self.pending = Break::Line;
+                }
+            }
+            "(" => {
+                self.parens += 1;
+                if self.arguments.is_none() && self.brackets == 0 && arguments {
+                    self.arguments = Some(ArgumentsOnLine {
+                        depth: self.parens,
+                        open: self.out.len(),
+                        commas: Vec::new(),
+                        close: None,
+                    });
+                }
+            }
+            "," | ")" => {
+                if let Some(ref mut arguments) = self.arguments {
+                    if arguments.depth == self.parens && arguments.close.is_none() {
+                        if text == "," {
+                            arguments.commas.push(self.out.len());
+                        } else {
+                            arguments.close = Some(self.out.len() - 1);
+                        }
+                    }
+                }
+                if text == ")" {
+                    self.parens = self.parens.saturating_sub(1);
+                }
+            }
+            ";" if self.indent == 0 => self.pending = Break::BlankLine,
+            ";" => self.pending = Break::Line,
+            _ => {}
+        }
+        // Identifiers right after these are return types, not names
+        self.prev_name = identifier
+            && (self.brackets > 0
+                || !(line_start
+                    || matches!(
+                        self.prev.as_str(),
+                        "]" | "="
+                            | "getter"
+                            | "setter"
+                            | "deleter"
+                            | "legacycaller"
+                            | "static"
+                            | "stringifier"
+                    )));
+        self.prev.clear();
+        self.prev.push_str(text);
+    }
+
+    /// Ends the line being written, wrapping its argument list if it is too long
+    fn newline(&mut self) {
+        if let (Some(max_width), Some(arguments)) = (self.max_width, self.arguments.take()) {
+            let line = &self.out[self.line_start..];
+            if let Some(close) = arguments.close.filter(|&close| close > arguments.open) {
+                if line.chars().count() > max_width {
+                    let indent = "    ".repeat(self.line_indent);
+                    let mut wrapped = self.out[self.line_start..arguments.open].to_string();
+                    let mut start = arguments.open;
+                    for &end in arguments.commas.iter().chain(Some(&close)) {
+                        wrapped.push('\n');
+                        wrapped.push_str(&indent);
+                        wrapped.push_str("    ");
+                        wrapped.push_str(self.out[start..end].trim());
+                        start = end;
+                    }
+                    wrapped.push('\n');
+                    wrapped.push_str(&indent);
+                    wrapped.push_str(&self.out[close..]);
+                    self.out.truncate(self.line_start);
+                    self.out.push_str(&wrapped);
+                }
+            }
+        }
+        self.arguments = None;
+        self.out.push('\n');
+        self.line_start = self.out.len();
+        self.line_indent = self.indent + self.continuation as usize;
+        self.continuation = false;
+    }
+
+    /// The trivia between the last token and the next one, which has `trivia`
+    fn take_gap(&mut self, trivia: Option<&TokenTrivia>) -> String {
+        let mut gap = std::mem::take(&mut self.trailing);
+        if let Some(trivia) = trivia {
+            // Leading trivia starts a line, whatever trivia it now follows
+            if !trivia.leading.is_empty() && !self.prev.is_empty() && !gap.ends_with('\n') {
+                gap.push('\n');
+            }
+            gap.push_str(&trivia.leading);
+        }
+        gap
+    }
+
+    /// Writes comments each on their own line
+    fn write_comments(&mut self, comments: &[(Option<Comment<'_>>, bool)], indent: usize) {
+        for &(comment, blank) in comments {
+            if blank && !self.out.is_empty() {
+                self.newline();
+            }
+            if let Some(comment) = comment {
+                if !self.out.is_empty() && !self.out.ends_with('\n') {
+                    self.newline();
+                }
+                self.out.push_str(&"    ".repeat(indent));
+                self.out.push_str(comment.text);
+                self.newline();
+            }
+        }
+    }
+
+    /// Writes the comments at the start of `trivia`, on the line of the last token written.
+    /// `line_end` tells whether a line break follows the token anyway.
+    fn write_trailing_comments(&mut self, trivia: &str, line_end: bool) {
+        for comment in comments_in(trivia) {
+            if trivia[..comment.span.start].contains('\n') {
+                break;
+            }
+            self.out.push(' ');
+            self.out.push_str(comment.text);
+            if comment.kind == CommentKind::Line && !line_end {
+                // Whatever follows has to go on the next line
+                self.continuation = true;
+                self.arguments = None;
+            }
+        }
+    }
+
+    fn needs_space(&self, text: &str) -> bool {
+        match text {
+            "," | ";" | ")" | "]" | ">" | "?" | "..." | "<" => return false,
+            "}" if self.prev == "{" => return false,
+            "(" if self.prev_name || self.prev == "constructor" => return false,
+            _ => {}
+        }
+        match self.prev.as_str() {
+            "(" | "[" | "<" => false,
+            "=" if self.brackets > 0 => false,
+            _ => !(text == "=" && self.brackets > 0),
+        }
+    }
+}
+
+/// Comments in the trivia `gap` which do not share their line with the token before it, each
+/// with whether a blank line comes before it. The first entry stands for the token after the
+/// trivia and tells whether a blank line comes before the token or its first comment.
+fn own_line_comments(gap: &str, after_token: bool) -> Vec<(Option<Comment<'_>>, bool)> {
+    let mut comments = vec![(None, false)];
+    let mut gap_start = 0;
+    for comment in comments_in(gap) {
+        if after_token && !gap[..comment.span.start].contains('\n') {
+            gap_start = comment.span.end;
+            continue;
+        }
+        let blank = gap[gap_start..comment.span.start].matches('\n').count() > 1;
+        if comments.len() == 1 {
+            comments[0].1 = blank;
+        }
+        comments.push((Some(comment), blank && comments.len() > 1));
+        gap_start = comment.span.end;
+    }
+    let blank = gap[gap_start..].matches('\n').count() > 1;
+    if comments.len() == 1 {
+        comments[0].1 = blank && !gap.is_empty();
+    } else if blank {
+        comments.push((None, true));
+    }
+    comments
+}
+
+impl<T: ToWebIdl> ToWebIdl for Option<T> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        if let Some(inner) = self {
+            inner.write_webidl(printer);
+        }
+    }
+}
+
+impl<T: ToWebIdl> ToWebIdl for Box<T> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        (**self).write_webidl(printer);
+    }
+}
+
+impl<T: ToWebIdl> ToWebIdl for Vec<T> {
+    fn write_webidl(&self, printer: &mut Printer) {
+        for item in self {
+            item.write_webidl(printer);
+        }
+    }
+}
+
+impl<T: ToWebIdl, U: ToWebIdl> ToWebIdl for (T, U) {
+    fn write_webidl(&self, printer: &mut Printer) {
+        self.0.write_webidl(printer);
+        self.1.write_webidl(printer);
+    }
+}
+
+impl<T: ToWebIdl, U: ToWebIdl, V: ToWebIdl> ToWebIdl for (T, U, V) {
+    fn write_webidl(&self, printer: &mut Printer) {
+        self.0.write_webidl(printer);
+        self.1.write_webidl(printer);
+        self.2.write_webidl(printer);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::Identifier;
+    use crate::types::Type;
+    use crate::{Definition, Parse};
+
+    fn round_trip(source: &str) -> String {
+        let parsed = crate::parse(source).unwrap();
+        let printed = parsed.to_webidl();
+        assert_spanless_eq!(crate::parse(&printed).unwrap(), parsed, "{}", printed);
+        printed
+    }
+
+    #[test]
+    fn should_escape_identifiers_as_in_the_source() {
+        let (_, parsed) = Identifier::parse("_interface").unwrap();
+        assert_eq!(parsed.to_webidl(), "_interface\n");
+        let (_, parsed) = Identifier::parse("_Foo").unwrap();
+        assert_eq!(parsed.to_webidl(), "_Foo\n");
+        let (_, parsed) = Identifier::parse("Foo").unwrap();
+        assert_eq!(parsed.to_webidl(), "Foo\n");
+    }
+
+    #[test]
+    fn should_not_escape_keywords_used_as_names() {
+        assert_eq!(
+            round_trip("interface A { attribute long required; undefined f(any callback); };"),
+            "interface A {\n    attribute long required;\n    undefined f(any callback);\n};\n"
+        );
+    }
+
+    #[test]
+    fn should_escape_keywords_which_were_not_parsed() {
+        assert_eq!(Identifier::new("interface").to_webidl(), "_interface\n");
+        assert_eq!(Identifier::new("Foo").to_webidl(), "Foo\n");
+    }
+
+    #[test]
+    fn should_escape_renamed_identifiers_by_their_name() {
+        let (_, mut parsed) = Identifier::parse("Window").unwrap();
+        parsed.0 = "Windo".into();
+        assert_eq!(parsed.to_webidl(), "Windo\n");
+        parsed.0 = "any".into();
+        assert_eq!(parsed.to_webidl(), "_any\n");
+
+        let (_, mut parsed) = Identifier::parse("_Foo").unwrap();
+        parsed.0 = "Bar".into();
+        assert_eq!(parsed.to_webidl(), "_Bar\n");
+    }
+
+    #[test]
+    fn should_print_types() {
+        let (_, parsed) =
+            Type::parse("sequence< record<DOMString,(long or [Clamp] octet)? > >?").unwrap();
+        assert_eq!(
+            parsed.to_webidl(),
+            "sequence<record<DOMString, (long or [Clamp] octet)?>>?\n"
+        );
+    }
+
+    #[test]
+    fn should_print_extended_attributes() {
+        let (_, parsed) = Definition::parse(
+            "[ Exposed = ( Window , Worker ) , LegacyFactoryFunction = Image ( long w ) ] \
+             interface HTMLImageElement { [ CEReactions ] attribute DOMString alt; };",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.to_webidl(),
+            "[Exposed=(Window, Worker), LegacyFactoryFunction=Image(long w)]\n\
+             interface HTMLImageElement {\n    \
+             [CEReactions] attribute DOMString alt;\n\
+             };\n"
+        );
+    }
+
+    #[test]
+    fn should_round_trip_definitions() {
+        let printed = round_trip(
+            "
+            callback Callback = undefined (long... args);
+            callback interface EventListener { undefined handleEvent(Event event); };
+            interface mixin M { const unrestricted double X = -Infinity; };
+            partial interface mixin M {};
+            namespace N { readonly attribute long _required; };
+            partial namespace N { undefined f(any _interface); };
+            dictionary D : Base { required long x; sequence<long> y = []; D z = {}; };
+            partial dictionary D { long w = 0x1F; };
+            enum E { \"a\", \"b\", };
+            A includes B;
+            A implements B;
+            interface I : J {
+                constructor();
+                stringifier;
+                static attribute boolean b;
+                iterable<long, DOMString>;
+                async iterable<long>(optional D options = {});
+                readonly maplike<DOMString, long>;
+                setlike<long>;
+                getter any (unsigned long index);
+                getter Node (DOMString name);
+                Node item(long index);
+                inherit attribute float f;
+            };
+            ",
+        );
+        assert!(printed.contains("readonly attribute long _required;"));
+        assert!(printed.contains("D z = {};"));
+        assert!(printed.contains("getter Node (DOMString name);"));
+        assert!(printed.contains("Node item(long index);"));
+        assert!(printed.contains("partial interface mixin M {};"));
+    }
+}
@@ -0,0 +1,433 @@
+//! Loading many files as one set of definitions
+//!
+//! A [`SourceMap`](struct.SourceMap.html) owns the text of every file and gives each one a
+//! [`FileId`](type.FileId.html), which is the index of the file used by the
+//! [`semantic`](../semantic/index.html), [`resolve`](../resolve/index.html) and
+//! [`validate`](../validate/index.html) modules. A [`Project`](struct.Project.html) borrows the
+//! map and holds the definitions parsed from it, so spans and diagnostics can be located as
+//! `html.webidl:123:5`.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::project::{Project, SourceMap};
+//!
+//! let mut sources = SourceMap::new();
+//! sources.add("dom.webidl", "interface Window {};".to_string());
+//! sources.add(
+//!     "html.webidl",
+//!     "partial interface Window {\n  attribute DOMString name;\n};".to_string(),
+//! );
+//!
+//! let project = Project::parse(&sources).unwrap();
+//! let partials: Vec<_> = project
+//!     .partials("Window")
+//!     .map(|partial| sources.location(partial.file, partial.node.span().start).to_string())
+//!     .collect();
+//! assert_eq!(partials, ["html.webidl:1:1"]);
+//! #
+//! # use weedle::span::Spanned;
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::resolve::{self, DefinitionKind, Resolution};
+use crate::semantic::{Located, Model};
+use crate::span::{LineColumn, LineIndex, Span};
+use crate::{known_attribute, validate, Definition, Definitions, ParseOptions};
+
+/// Index of a file in a [`SourceMap`](struct.SourceMap.html)
+pub type FileId = usize;
+
+/// The name and text of every file of a project
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+/// A file of a [`SourceMap`](struct.SourceMap.html)
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    /// The path the file was loaded from, or the name it was added with
+    pub name: String,
+    /// The path the file was loaded from, which `name` may not show exactly if it is not UTF-8
+    pub path: Option<PathBuf>,
+    pub text: String,
+    lines: LineIndex,
+}
+
+/// A position in a named file, displayed as `name:line:column`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Location<'a> {
+    pub name: &'a str,
+    pub position: LineColumn,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap::default()
+    }
+
+    /// Adds a source under the given name
+    pub fn add(&mut self, name: impl Into<String>, text: String) -> FileId {
+        self.push(name.into(), None, text)
+    }
+
+    /// Reads a file, named by its path
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<FileId> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Ok(self.push(path.display().to_string(), Some(path.to_path_buf()), text))
+    }
+
+    fn push(&mut self, name: String, path: Option<PathBuf>, text: String) -> FileId {
+        self.files.push(SourceFile {
+            name,
+            path,
+            lines: LineIndex::new(&text),
+            text,
+        });
+        self.files.len() - 1
+    }
+
+    /// Reads the `.webidl` files of a directory and its subdirectories, sorted by path
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> io::Result<Vec<FileId>> {
+        let mut paths = Vec::new();
+        collect_webidl_files(dir.as_ref(), &mut paths)?;
+        paths.sort();
+        paths.iter().map(|path| self.load(path)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Panics if `file` was not returned by this map
+    pub fn file(&self, file: FileId) -> &SourceFile {
+        &self.files[file]
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files.iter().enumerate()
+    }
+
+    /// Id of the first file with the given name
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.files.iter().position(|file| file.name == name)
+    }
+
+    /// Locates a byte offset into a file
+    pub fn location(&self, file: FileId, offset: usize) -> Location<'_> {
+        let file = self.file(file);
+        Location {
+            name: &file.name,
+            position: file.line_column(offset),
+        }
+    }
+
+    /// Formats a diagnostic with the location of it and of its notes.
+    /// Ex: `html.webidl:3:5: error[duplicate-member]: ...`
+    pub fn display(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = format!(
+            "{}: {}",
+            self.location(diagnostic.file, diagnostic.span.start),
+            diagnostic
+        );
+        for note in &diagnostic.notes {
+            out += &format!(
+                "\n  = note: {}: {}",
+                self.location(note.file, note.span.start),
+                note.message
+            );
+        }
+        out
+    }
+}
+
+impl SourceFile {
+    /// Converts a byte offset into the file to a line and column
+    pub fn line_column(&self, offset: usize) -> LineColumn {
+        self.lines.line_column(&self.text, offset)
+    }
+
+    /// The text of the given 1-based line, without its line break
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = self.lines.line_start(line)?;
+        self.text[start..].lines().next().or(Some(""))
+    }
+
+    /// The text a span covers
+    pub fn source_text(&self, span: Span) -> &str {
+        span.source_text(&self.text)
+    }
+}
+
+impl<'a> fmt::Display for Location<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.name, self.position.line, self.position.column
+        )
+    }
+}
+
+fn collect_webidl_files(dir: &Path, paths: &mut Vec<std::path::PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_webidl_files(&path, paths)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "webidl")
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The definitions of every file of a [`SourceMap`](struct.SourceMap.html)
+#[derive(Clone, Debug)]
+pub struct Project<'a> {
+    sources: &'a SourceMap,
+    files: Vec<Definitions<'a>>,
+}
+
+/// A file of a project which could not be parsed
+#[derive(Clone, Debug)]
+pub struct FileError<'a> {
+    pub file: FileId,
+    pub name: &'a str,
+    pub error: Error,
+}
+
+impl<'a> fmt::Display for FileError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.name, self.error.line, self.error.column, self.error
+        )
+    }
+}
+
+impl<'a> std::error::Error for FileError<'a> {}
+
+impl<'a> Project<'a> {
+    /// Parses every file, failing with the errors of all the files which do not parse
+    pub fn parse(sources: &'a SourceMap) -> Result<Self, Vec<FileError<'a>>> {
+        Project::parse_with_options(sources, ParseOptions::default())
+    }
+
+    /// Like [`parse`](#method.parse), but accepts only what `options` allow in every file
+    pub fn parse_with_options(
+        sources: &'a SourceMap,
+        options: ParseOptions,
+    ) -> Result<Self, Vec<FileError<'a>>> {
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+        for (file, source) in sources.files() {
+            match crate::parse_with_options(&source.text, options) {
+                Ok(definitions) => files.push(definitions),
+                Err(error) => errors.push(FileError {
+                    file,
+                    name: &source.name,
+                    error,
+                }),
+            }
+        }
+        if errors.is_empty() {
+            Ok(Project { sources, files })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn sources(&self) -> &'a SourceMap {
+        self.sources
+    }
+
+    /// The definitions of one file
+    pub fn definitions(&self, file: FileId) -> &[Definition<'a>] {
+        &self.files[file]
+    }
+
+    /// The definitions of every file, in the order of their ids, as the analyses take them
+    pub fn files(&self) -> impl Iterator<Item = &[Definition<'a>]> + Clone {
+        self.files.iter().map(|definitions| &definitions[..])
+    }
+
+    /// Every definition with its file
+    pub fn iter(&self) -> impl Iterator<Item = Located<&Definition<'a>>> {
+        self.files
+            .iter()
+            .enumerate()
+            .flat_map(|(file, definitions)| {
+                definitions.iter().map(move |definition| Located {
+                    node: definition,
+                    file,
+                })
+            })
+    }
+
+    /// The definitions and partial definitions named `name`, across all files
+    pub fn definitions_named<'p>(
+        &'p self,
+        name: &'p str,
+    ) -> impl Iterator<Item = Located<&'p Definition<'a>>> + 'p {
+        self.iter()
+            .filter(move |definition| definition_name(definition.node) == Some(name))
+    }
+
+    /// The partial definitions named `name`, across all files
+    pub fn partials<'p>(
+        &'p self,
+        name: &'p str,
+    ) -> impl Iterator<Item = Located<&'p Definition<'a>>> + 'p {
+        self.definitions_named(name)
+            .filter(|definition| DefinitionKind::of(definition.node).is_none())
+    }
+
+    /// Merges the definitions of all files, see the [`semantic`](../semantic/index.html) module
+    pub fn model(&self) -> Model<'_> {
+        Model::from_files(self.files())
+    }
+
+    /// Resolves names across all files, see the [`resolve`](../resolve/index.html) module
+    pub fn resolve(&self) -> Resolution<'_> {
+        resolve::resolve(self.files())
+    }
+
+    /// Checks the rules of the specification and the use of standard extended attributes,
+    /// see the [`validate`](../validate/index.html) and
+    /// [`known_attribute`](../known_attribute/index.html) modules
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = validate::validate(self.files());
+        diagnostics.extend(known_attribute::check(self.files()));
+        diagnostics
+    }
+}
+
+/// Name of a definition or partial definition, `None` for statements
+fn definition_name<'a>(definition: &'a Definition<'a>) -> Option<&'a str> {
+    if let Some((_, identifier)) = DefinitionKind::of(definition) {
+        return Some(&identifier.0);
+    }
+    match definition {
+        Definition::PartialInterface(d) => Some(&d.identifier.0),
+        Definition::PartialInterfaceMixin(d) => Some(&d.identifier.0),
+        Definition::PartialDictionary(d) => Some(&d.identifier.0),
+        Definition::PartialNamespace(d) => Some(&d.identifier.0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::span::Spanned;
+
+    fn sources() -> SourceMap {
+        let mut sources = SourceMap::new();
+        sources.add(
+            "dom.webidl",
+            "interface Node {};\ninterface Window {};\n".to_string(),
+        );
+        sources.add(
+            "html.webidl",
+            "partial interface Window {\n  attribute DOMString name;\n};\n".to_string(),
+        );
+        sources.add(
+            "webgpu.webidl",
+            "enum E { \"a\" };\n  partial interface Window {};\n".to_string(),
+        );
+        sources
+    }
+
+    #[test]
+    fn should_find_definitions_across_files() {
+        let sources = sources();
+        let project = Project::parse(&sources).unwrap();
+
+        let named: Vec<_> = project
+            .definitions_named("Window")
+            .map(|definition| {
+                sources
+                    .location(definition.file, definition.node.span().start)
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            named,
+            ["dom.webidl:2:1", "html.webidl:1:1", "webgpu.webidl:2:3"]
+        );
+        assert_eq!(project.partials("Window").count(), 2);
+        assert_eq!(project.partials("Node").count(), 0);
+        assert_eq!(project.model().interfaces["Window"].partials.len(), 2);
+    }
+
+    #[test]
+    fn should_report_every_file_which_does_not_parse() {
+        let mut sources = sources();
+        sources.add("a.webidl", "interface A {".to_string());
+        sources.add("b.webidl", "\n\ninterface".to_string());
+
+        let errors = Project::parse(&sources).unwrap_err();
+        let locations: Vec<_> = errors
+            .iter()
+            .map(|error| (error.file, error.name, error.error.line, error.error.column))
+            .collect();
+        assert_eq!(locations, [(3, "a.webidl", 1, 14), (4, "b.webidl", 3, 10)]);
+        assert!(errors[1]
+            .to_string()
+            .starts_with("b.webidl:3:10: expected one of"));
+    }
+
+    #[test]
+    fn should_parse_every_file_with_options() {
+        let mut sources = sources();
+        sources.add(
+            "gecko.webidl",
+            "interface A {\n  jsonifier;\n};\n".to_string(),
+        );
+        assert_eq!(
+            Project::parse(&sources).unwrap_err()[0].name,
+            "gecko.webidl"
+        );
+
+        let options = ParseOptions::new().dialect(crate::Dialect::Gecko);
+        let project = Project::parse_with_options(&sources, options).unwrap();
+        assert_eq!(project.definitions_named("A").count(), 1);
+    }
+
+    #[test]
+    fn should_locate_diagnostics() {
+        let mut sources = SourceMap::new();
+        sources.add("a.webidl", "interface A {};".to_string());
+        sources.add("b.webidl", "\ninterface A {};".to_string());
+        let project = Project::parse(&sources).unwrap();
+
+        let messages: Vec<_> = project
+            .resolve()
+            .diagnostics
+            .iter()
+            .map(|diagnostic| sources.display(diagnostic))
+            .collect();
+        assert_eq!(
+            messages,
+            ["b.webidl:2:11: error[duplicate-definition]: `A` is defined more than once\n  = note: a.webidl:1:11: `A` is first defined here"]
+        );
+        assert_eq!(sources.file(1).line(2), Some("interface A {};"));
+        assert_eq!(sources.find("b.webidl"), Some(1));
+    }
+}
@@ -0,0 +1,136 @@
+//! Skipping over input that does not parse, so the rest of it still can be
+//!
+//! Lists of definitions and members are where parsing resumes: an element which fails is
+//! skipped up to and including the next `;` outside of any brackets, which ends every
+//! definition and member, and the failure is kept as a diagnostic.
+
+use crate::state::{self, Failure};
+use crate::{Err, IResult, Parse};
+
+/// Parses `T` repeatedly like `many0`, skipping elements which fail until the end of the input
+/// or a `}` closing the list is reached
+pub(crate) fn many0_recovering<'a, T: Parse<'a>>(mut input: &'a str) -> IResult<&'a str, Vec<T>> {
+    let mut list = Vec::new();
+    loop {
+        let next = state::skip_trivia(input);
+        if next.is_empty() || next.starts_with('}') {
+            return Ok((input, list));
+        }
+
+        state::reset_failure();
+        let recovered = state::recovered_len();
+        match T::parse(input) {
+            Ok((rest, _)) if rest.len() == input.len() => return Ok((input, list)),
+            Ok((rest, item)) => {
+                list.push(item);
+                input = rest;
+            }
+            Err(Err::Error(_)) => {
+                state::truncate_recovered(recovered);
+                state::record_recovered(failure_at(next));
+                input = skip_statement(next, true);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The failure the last parser recorded, or an empty one at `input` if there is none
+pub(crate) fn failure_at(input: &str) -> Failure {
+    state::take_failure().unwrap_or_else(|| Failure {
+        offset: state::offset(input),
+        ..Failure::default()
+    })
+}
+
+/// Skips to right after the next `;` that is not nested in brackets. With `in_braces`, also
+/// stops before a `}` closing the enclosing braces.
+pub(crate) fn skip_statement(mut input: &str, in_braces: bool) -> &str {
+    let mut depth = 0usize;
+    loop {
+        input = state::skip_trivia(input);
+        let c = match input.chars().next() {
+            Some(c) => c,
+            None => return input,
+        };
+        match c {
+            '"' => {
+                input = match input[1..].find('"') {
+                    Some(end) => &input[end + 2..],
+                    None => "",
+                };
+                continue;
+            }
+            '{' | '(' | '[' => depth += 1,
+            '}' if depth == 0 && in_braces => return input,
+            '}' | ')' | ']' => depth = depth.saturating_sub(1),
+            ';' if depth == 0 => return &input[1..],
+            _ => {}
+        }
+        input = &input[c.len_utf8()..];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_skip_to_statement_end() {
+        assert_eq!(skip_statement("a b; c;", false), " c;");
+        assert_eq!(skip_statement("a { b; }; c;", false), " c;");
+        assert_eq!(skip_statement("a \";\" // ;\n b; c", false), " c");
+        assert_eq!(skip_statement("a /* ; */ b", false), "");
+    }
+
+    #[test]
+    fn should_skip_bad_definitions() {
+        let (parsed, errors) = crate::parse_recovering(
+            "interface A {};\ncallback = ;\ndictionary B { long x; };\ntypedef ;",
+        );
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].line, errors[0].column), (2, 10));
+        assert_eq!((errors[1].line, errors[1].column), (4, 9));
+    }
+
+    #[test]
+    fn should_keep_members_around_bad_ones() {
+        let source = "interface A {\n  attribute long a;\n  attribute = 3;\n  undefined b();\n};\n\
+                      dictionary B { long c; required; long d; };";
+        let (parsed, errors) = crate::parse_recovering(source);
+        assert_eq!(parsed.len(), 2);
+        match &parsed[0] {
+            crate::Definition::Interface(interface) => {
+                assert_eq!(interface.members.body.len(), 2)
+            }
+            _ => unreachable!(),
+        }
+        match &parsed[1] {
+            crate::Definition::Dictionary(dictionary) => {
+                assert_eq!(dictionary.members.body.len(), 2)
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].line, errors[0].column), (3, 13));
+        assert_eq!(
+            errors[0].context,
+            vec!["interface `A`", "attribute interface member"]
+        );
+        assert_eq!(errors[1].found.as_deref(), Some(";"));
+    }
+
+    #[test]
+    fn should_recover_nothing_from_valid_input() {
+        let (parsed, errors) = crate::parse_recovering("enum E { \"a\" }; typedef long L;");
+        assert_eq!(parsed.len(), 2);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn should_stop_before_closing_brace() {
+        assert_eq!(skip_statement("a (b; c) }; d", true), "}; d");
+        assert_eq!(skip_statement("a } ; d", false), " d");
+    }
+}
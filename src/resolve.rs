@@ -0,0 +1,520 @@
+//! Resolving the names definitions refer to each other by
+//!
+//! Types, inheritance, partial definitions and `includes` statements name other definitions.
+//! [`resolve`](fn.resolve.html) maps each of these references to the definition it names and
+//! reports names which are not defined, defined more than once, or name the wrong kind of
+//! definition.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::resolve::{resolve, DefinitionKind};
+//!
+//! let parsed = weedle::parse("
+//!     dictionary Options { Mode mode; };
+//!     enum Mode { \"fast\", \"slow\" };
+//!     interface Runner : Options { undefined run(optional Options options = {}, Task task); };
+//! ").unwrap();
+//! let resolution = resolve(vec![&parsed[..]]);
+//!
+//! assert_eq!(resolution.lookup("Mode").unwrap().kind, DefinitionKind::Enum);
+//!
+//! let messages: Vec<_> = resolution.diagnostics.iter().map(|d| d.to_string()).collect();
+//! assert_eq!(
+//!     messages,
+//!     [
+//!         "error[wrong-kind]: `Options` is a dictionary, but an interface is expected here",
+//!         "error[unknown-name]: `Task` is not defined",
+//!     ]
+//! );
+//! ```
+
+use std::collections::HashMap;
+
+use crate::common::Identifier;
+use crate::diagnostic::Diagnostic;
+use crate::span::Spanned;
+use crate::types::{ConstType, NonAnyType};
+use crate::visit::{Accept, Visit, Walk};
+use crate::{
+    CallbackInterfaceDefinition, Definition, DictionaryDefinition, ImplementsDefinition,
+    IncludesStatementDefinition, InterfaceDefinition,
+};
+
+/// Kinds of definitions which introduce a name
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DefinitionKind {
+    Interface,
+    CallbackInterface,
+    InterfaceMixin,
+    Namespace,
+    Dictionary,
+    Enum,
+    Typedef,
+    Callback,
+}
+
+impl DefinitionKind {
+    /// Kind and name of the definition, `None` for partial definitions and statements
+    pub fn of<'a>(definition: &'a Definition<'a>) -> Option<(DefinitionKind, &'a Identifier<'a>)> {
+        match definition {
+            Definition::Interface(d) => Some((DefinitionKind::Interface, &d.identifier)),
+            Definition::CallbackInterface(d) => {
+                Some((DefinitionKind::CallbackInterface, &d.identifier))
+            }
+            Definition::InterfaceMixin(d) => Some((DefinitionKind::InterfaceMixin, &d.identifier)),
+            Definition::Namespace(d) => Some((DefinitionKind::Namespace, &d.identifier)),
+            Definition::Dictionary(d) => Some((DefinitionKind::Dictionary, &d.identifier)),
+            Definition::Enum(d) => Some((DefinitionKind::Enum, &d.identifier)),
+            Definition::Typedef(d) => Some((DefinitionKind::Typedef, &d.identifier)),
+            Definition::Callback(d) => Some((DefinitionKind::Callback, &d.identifier)),
+            Definition::PartialInterface(_)
+            | Definition::PartialInterfaceMixin(_)
+            | Definition::PartialDictionary(_)
+            | Definition::PartialNamespace(_)
+            | Definition::IncludesStatement(_)
+            | Definition::Implements(_) => None,
+        }
+    }
+
+    /// Describes the kind with its article. Ex: `an interface`
+    pub fn describe(self) -> &'static str {
+        match self {
+            DefinitionKind::Interface => "an interface",
+            DefinitionKind::CallbackInterface => "a callback interface",
+            DefinitionKind::InterfaceMixin => "an interface mixin",
+            DefinitionKind::Namespace => "a namespace",
+            DefinitionKind::Dictionary => "a dictionary",
+            DefinitionKind::Enum => "an enum",
+            DefinitionKind::Typedef => "a typedef",
+            DefinitionKind::Callback => "a callback",
+        }
+    }
+}
+
+/// A definition which introduces a name
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DefinitionRef<'a> {
+    pub definition: &'a Definition<'a>,
+    pub kind: DefinitionKind,
+    pub identifier: &'a Identifier<'a>,
+    /// Index of the file the definition is in
+    pub file: usize,
+}
+
+/// Where a name is referred to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReferenceKind {
+    /// A type. Ex: `Node` in `attribute Node? parent;`
+    Type,
+    /// The type of a constant, which can only be a typedef
+    ConstType,
+    /// The parent of an interface
+    InterfaceInheritance,
+    /// The parent of a callback interface
+    CallbackInterfaceInheritance,
+    /// The parent of a dictionary
+    DictionaryInheritance,
+    /// The name of a partial definition, which has to match the kind of the partial
+    Partial(DefinitionKind),
+    /// The interface on the left of `includes`
+    IncludesInterface,
+    /// The mixin on the right of `includes`
+    IncludesMixin,
+    /// Either side of the legacy `implements` statement
+    Implements,
+}
+
+impl ReferenceKind {
+    /// Kinds of definitions the reference can name
+    pub fn expected(self) -> &'static [DefinitionKind] {
+        use self::DefinitionKind::*;
+
+        match self {
+            ReferenceKind::Type => &[
+                Interface,
+                CallbackInterface,
+                Dictionary,
+                Enum,
+                Typedef,
+                Callback,
+            ],
+            ReferenceKind::ConstType => &[Typedef],
+            ReferenceKind::InterfaceInheritance
+            | ReferenceKind::IncludesInterface
+            | ReferenceKind::Implements => &[Interface],
+            ReferenceKind::CallbackInterfaceInheritance => &[CallbackInterface],
+            ReferenceKind::DictionaryInheritance => &[Dictionary],
+            ReferenceKind::Partial(Interface) => &[Interface],
+            ReferenceKind::Partial(InterfaceMixin) | ReferenceKind::IncludesMixin => {
+                &[InterfaceMixin]
+            }
+            ReferenceKind::Partial(Dictionary) => &[Dictionary],
+            ReferenceKind::Partial(Namespace) => &[Namespace],
+            ReferenceKind::Partial(_) => &[],
+        }
+    }
+}
+
+/// A name referring to another definition
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Reference<'a> {
+    pub identifier: &'a Identifier<'a>,
+    pub kind: ReferenceKind,
+    /// Index of the file the reference is in
+    pub file: usize,
+    /// The definition named, if any. It can be of a kind the reference does not expect.
+    pub target: Option<DefinitionRef<'a>>,
+}
+
+/// The result of [`resolve`](fn.resolve.html)
+#[derive(Clone, Debug, Default)]
+pub struct Resolution<'a> {
+    /// The first definition of each name
+    pub definitions: HashMap<&'a str, DefinitionRef<'a>>,
+    /// Every reference, in the order of the files and then of the source
+    pub references: Vec<Reference<'a>>,
+    /// Unknown names, duplicate definitions and references to the wrong kind of definition
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Resolution<'a> {
+    /// The definition introducing `name`
+    pub fn lookup(&self, name: &str) -> Option<DefinitionRef<'a>> {
+        self.definitions.get(name).copied()
+    }
+}
+
+/// Resolves the references within and across files
+///
+/// Diagnostics refer to files by their index in `files`.
+pub fn resolve<'a, I: IntoIterator<Item = &'a [Definition<'a>]>>(files: I) -> Resolution<'a> {
+    let files: Vec<_> = files.into_iter().collect();
+    let mut resolution = Resolution::default();
+
+    for (file, definitions) in files.iter().enumerate() {
+        for definition in definitions.iter() {
+            let (kind, identifier) = match DefinitionKind::of(definition) {
+                Some(named) => named,
+                None => continue,
+            };
+            let new = DefinitionRef {
+                definition,
+                kind,
+                identifier,
+                file,
+            };
+            match resolution.definitions.get(&*identifier.0) {
+                Some(first) => resolution.diagnostics.push(
+                    Diagnostic::error(
+                        "duplicate-definition",
+                        format!("`{}` is defined more than once", identifier.0),
+                        file,
+                        identifier.span(),
+                    )
+                    .with_note(
+                        format!("`{}` is first defined here", identifier.0),
+                        first.file,
+                        first.identifier.span(),
+                    ),
+                ),
+                None => {
+                    resolution.definitions.insert(&identifier.0, new);
+                }
+            }
+        }
+    }
+
+    for (file, definitions) in files.iter().enumerate() {
+        let mut collector = Collector {
+            references: Vec::new(),
+        };
+        for definition in definitions.iter() {
+            definition.accept(&mut collector);
+        }
+        for (identifier, kind) in collector.references {
+            let target = resolution.lookup(&identifier.0);
+            match target {
+                None => resolution.diagnostics.push(Diagnostic::error(
+                    "unknown-name",
+                    format!("`{}` is not defined", identifier.0),
+                    file,
+                    identifier.span(),
+                )),
+                Some(target) if !kind.expected().contains(&target.kind) => {
+                    let expected: Vec<_> = kind.expected().iter().map(|k| k.describe()).collect();
+                    let expected = match expected.split_last() {
+                        Some((last, [])) => last.to_string(),
+                        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+                        None => "nothing".to_string(),
+                    };
+                    resolution.diagnostics.push(
+                        Diagnostic::error(
+                            "wrong-kind",
+                            format!(
+                                "`{}` is {}, but {} is expected here",
+                                identifier.0,
+                                target.kind.describe(),
+                                expected
+                            ),
+                            file,
+                            identifier.span(),
+                        )
+                        .with_note(
+                            format!("`{}` is defined here", identifier.0),
+                            target.file,
+                            target.identifier.span(),
+                        ),
+                    );
+                }
+                Some(_) => {}
+            }
+            resolution.references.push(Reference {
+                identifier,
+                kind,
+                file,
+                target,
+            });
+        }
+    }
+
+    resolution
+}
+
+/// Collects the references of one file
+struct Collector<'a> {
+    references: Vec<(&'a Identifier<'a>, ReferenceKind)>,
+}
+
+impl<'a> Visit<'a> for Collector<'a> {
+    fn visit_definition(&mut self, node: &'a Definition<'a>) {
+        let partial = match node {
+            Definition::PartialInterface(d) => Some((&d.identifier, DefinitionKind::Interface)),
+            Definition::PartialInterfaceMixin(d) => {
+                Some((&d.identifier, DefinitionKind::InterfaceMixin))
+            }
+            Definition::PartialDictionary(d) => Some((&d.identifier, DefinitionKind::Dictionary)),
+            Definition::PartialNamespace(d) => Some((&d.identifier, DefinitionKind::Namespace)),
+            _ => None,
+        };
+        if let Some((identifier, kind)) = partial {
+            self.references
+                .push((identifier, ReferenceKind::Partial(kind)));
+        }
+        node.walk(self);
+    }
+
+    fn visit_interface_definition(&mut self, node: &'a InterfaceDefinition<'a>) {
+        if let Some(inheritance) = &node.inheritance {
+            self.references
+                .push((&inheritance.identifier, ReferenceKind::InterfaceInheritance));
+        }
+        node.walk(self);
+    }
+
+    fn visit_callback_interface_definition(&mut self, node: &'a CallbackInterfaceDefinition<'a>) {
+        if let Some(inheritance) = &node.inheritance {
+            self.references.push((
+                &inheritance.identifier,
+                ReferenceKind::CallbackInterfaceInheritance,
+            ));
+        }
+        node.walk(self);
+    }
+
+    fn visit_dictionary_definition(&mut self, node: &'a DictionaryDefinition<'a>) {
+        if let Some(inheritance) = &node.inheritance {
+            self.references.push((
+                &inheritance.identifier,
+                ReferenceKind::DictionaryInheritance,
+            ));
+        }
+        node.walk(self);
+    }
+
+    fn visit_includes_statement_definition(&mut self, node: &'a IncludesStatementDefinition<'a>) {
+        self.references
+            .push((&node.lhs_identifier, ReferenceKind::IncludesInterface));
+        self.references
+            .push((&node.rhs_identifier, ReferenceKind::IncludesMixin));
+        node.walk(self);
+    }
+
+    fn visit_implements_definition(&mut self, node: &'a ImplementsDefinition<'a>) {
+        self.references
+            .push((&node.lhs_identifier, ReferenceKind::Implements));
+        self.references
+            .push((&node.rhs_identifier, ReferenceKind::Implements));
+        node.walk(self);
+    }
+
+    fn visit_non_any_type(&mut self, node: &'a NonAnyType<'a>) {
+        match node {
+            // `undefined` is only a keyword in return types and parses as an identifier
+            // elsewhere, like in `Promise<undefined>`
+            NonAnyType::Identifier(identifier) if identifier.type_.0 == "undefined" => {}
+            NonAnyType::Identifier(identifier) => {
+                self.references
+                    .push((&identifier.type_, ReferenceKind::Type));
+            }
+            _ => {}
+        }
+        node.walk(self);
+    }
+
+    fn visit_const_type(&mut self, node: &'a ConstType<'a>) {
+        if let ConstType::Identifier(identifier) = node {
+            self.references
+                .push((&identifier.type_, ReferenceKind::ConstType));
+        }
+        node.walk(self);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn diagnostics(files: &[&str]) -> Vec<(&'static str, usize, String)> {
+        let parsed: Vec<_> = files.iter().map(|f| crate::parse(f).unwrap()).collect();
+        let resolution = resolve(parsed.iter().map(|p| &p[..]));
+        resolution
+            .diagnostics
+            .iter()
+            .map(|d| (d.rule, d.file, d.message.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn should_resolve_references() {
+        let parsed = crate::parse(
+            "interface A : B { const T C = 1; attribute sequence<D?> d; };
+            interface B {};
+            typedef long T;
+            dictionary D {};
+            interface mixin M {};
+            A includes M;
+            partial interface A {};",
+        )
+        .unwrap();
+        let resolution = resolve(vec![&parsed[..]]);
+
+        assert_eq!(resolution.diagnostics, []);
+        let references: Vec<_> = resolution
+            .references
+            .iter()
+            .map(|r| (&*r.identifier.0, r.kind, r.target.map(|t| t.kind)))
+            .collect();
+        assert_eq!(
+            references,
+            [
+                (
+                    "B",
+                    ReferenceKind::InterfaceInheritance,
+                    Some(DefinitionKind::Interface)
+                ),
+                ("T", ReferenceKind::ConstType, Some(DefinitionKind::Typedef)),
+                ("D", ReferenceKind::Type, Some(DefinitionKind::Dictionary)),
+                (
+                    "A",
+                    ReferenceKind::IncludesInterface,
+                    Some(DefinitionKind::Interface)
+                ),
+                (
+                    "M",
+                    ReferenceKind::IncludesMixin,
+                    Some(DefinitionKind::InterfaceMixin)
+                ),
+                (
+                    "A",
+                    ReferenceKind::Partial(DefinitionKind::Interface),
+                    Some(DefinitionKind::Interface)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_unknown_names() {
+        assert_eq!(
+            diagnostics(&[
+                "interface A { Promise<Missing> f(); Promise<undefined> g(); };
+                partial dictionary P {};"
+            ]),
+            [
+                ("unknown-name", 0, "`Missing` is not defined".to_string()),
+                ("unknown-name", 0, "`P` is not defined".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_duplicates_across_files() {
+        let files = ["interface A {};", "dictionary B {};", "enum A { \"a\" };"];
+        let parsed: Vec<_> = files.iter().map(|f| crate::parse(f).unwrap()).collect();
+        let resolution = resolve(parsed.iter().map(|p| &p[..]));
+
+        assert_eq!(resolution.diagnostics.len(), 1);
+        let diagnostic = &resolution.diagnostics[0];
+        assert_eq!(diagnostic.rule, "duplicate-definition");
+        assert_eq!(diagnostic.file, 2);
+        assert_eq!(diagnostic.notes[0].file, 0);
+        assert_eq!(diagnostic.notes[0].span.start, 10);
+        assert_eq!(
+            resolution.lookup("A").unwrap().kind,
+            DefinitionKind::Interface
+        );
+    }
+
+    #[test]
+    fn should_report_wrong_kinds() {
+        assert_eq!(
+            diagnostics(&["dictionary D {}; interface mixin M {}; namespace N {};
+                interface I : D {};
+                dictionary E : I {};
+                M includes I;
+                partial interface D {};
+                typedef N T;
+                interface J { const D c = 1; };",]),
+            [
+                (
+                    "wrong-kind",
+                    0,
+                    "`D` is a dictionary, but an interface is expected here".to_string()
+                ),
+                (
+                    "wrong-kind",
+                    0,
+                    "`I` is an interface, but a dictionary is expected here".to_string()
+                ),
+                (
+                    "wrong-kind",
+                    0,
+                    "`M` is an interface mixin, but an interface is expected here".to_string()
+                ),
+                (
+                    "wrong-kind",
+                    0,
+                    "`I` is an interface, but an interface mixin is expected here".to_string()
+                ),
+                (
+                    "wrong-kind",
+                    0,
+                    "`D` is a dictionary, but an interface is expected here".to_string()
+                ),
+                (
+                    "wrong-kind",
+                    0,
+                    "`N` is a namespace, but an interface, a callback interface, a dictionary, \
+                     an enum, a typedef or a callback is expected here"
+                        .to_string()
+                ),
+                (
+                    "wrong-kind",
+                    0,
+                    "`D` is a dictionary, but a typedef is expected here".to_string()
+                ),
+            ]
+        );
+    }
+}
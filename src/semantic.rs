@@ -0,0 +1,554 @@
+//! A merged view of definitions spread over partials, mixins and files
+//!
+//! WebIDL lets an interface be split into a definition, any number of `partial` definitions
+//! and the members of mixins it `includes`, possibly across files. A [`Model`](struct.Model.html)
+//! puts these pieces back together: every interface, mixin, dictionary and namespace gets the
+//! full list of its members, each tagged with the [`Origin`](struct.Origin.html) it was
+//! declared in.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::semantic::{Model, OriginKind};
+//!
+//! let dom = weedle::parse("
+//!     interface Document { readonly attribute DOMString URL; };
+//!     interface mixin ParentNode { readonly attribute unsigned long childElementCount; };
+//!     Document includes ParentNode;
+//! ").unwrap();
+//! let html = weedle::parse("
+//!     partial interface Document { attribute DOMString title; };
+//! ").unwrap();
+//!
+//! let model = Model::from_files(vec![&dom[..], &html[..]]);
+//! let document = &model.interfaces["Document"];
+//!
+//! let members: Vec<_> = document
+//!     .members
+//!     .iter()
+//!     .map(|member| (member.member.identifier().unwrap(), member.origin.file, member.origin.kind))
+//!     .collect();
+//! assert_eq!(
+//!     members,
+//!     [
+//!         ("URL", 0, OriginKind::Definition),
+//!         ("title", 1, OriginKind::Partial),
+//!         ("childElementCount", 0, OriginKind::Mixin { name: "ParentNode", partial: false }),
+//!     ]
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::attribute::ExtendedAttributeList;
+use crate::dictionary::DictionaryMember;
+use crate::interface::{AsyncIterableInterfaceMember, InterfaceMember, IterableInterfaceMember};
+use crate::mixin::MixinMember;
+use crate::namespace::NamespaceMember;
+use crate::span::{Span, Spanned};
+use crate::{
+    CallbackDefinition, CallbackInterfaceDefinition, Definition, DictionaryDefinition,
+    EnumDefinition, IncludesStatementDefinition, InterfaceDefinition, InterfaceMixinDefinition,
+    NamespaceDefinition, PartialDictionaryDefinition, PartialInterfaceDefinition,
+    PartialInterfaceMixinDefinition, PartialNamespaceDefinition, TypedefDefinition,
+};
+
+/// Definitions of one or more files, merged by name
+///
+/// When a name is defined more than once, the first definition is kept.
+#[derive(Clone, Debug, Default)]
+pub struct Model<'a> {
+    pub interfaces: BTreeMap<&'a str, Interface<'a>>,
+    pub callback_interfaces: BTreeMap<&'a str, Located<&'a CallbackInterfaceDefinition<'a>>>,
+    pub mixins: BTreeMap<&'a str, Mixin<'a>>,
+    pub dictionaries: BTreeMap<&'a str, Dictionary<'a>>,
+    pub namespaces: BTreeMap<&'a str, Namespace<'a>>,
+    pub callbacks: BTreeMap<&'a str, Located<&'a CallbackDefinition<'a>>>,
+    pub enums: BTreeMap<&'a str, Located<&'a EnumDefinition<'a>>>,
+    pub typedefs: BTreeMap<&'a str, Located<&'a TypedefDefinition<'a>>>,
+}
+
+/// A node together with the index of the file it was found in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Located<T> {
+    pub node: T,
+    /// Index of the file in the list given to [`Model::from_files`](struct.Model.html#method.from_files)
+    pub file: usize,
+}
+
+/// An interface with the members of its partials and included mixins
+#[derive(Clone, Debug)]
+pub struct Interface<'a> {
+    pub name: &'a str,
+    /// `None` if only partial definitions or `includes` statements were found
+    pub definition: Option<Located<&'a InterfaceDefinition<'a>>>,
+    pub partials: Vec<Located<&'a PartialInterfaceDefinition<'a>>>,
+    pub includes: Vec<Located<&'a IncludesStatementDefinition<'a>>>,
+    /// Members of the definition, then of the partials, then of the included mixins
+    pub members: Vec<Member<'a, InterfaceMemberRef<'a>>>,
+}
+
+/// An interface mixin with the members of its partials
+#[derive(Clone, Debug)]
+pub struct Mixin<'a> {
+    pub name: &'a str,
+    /// `None` if only partial definitions were found
+    pub definition: Option<Located<&'a InterfaceMixinDefinition<'a>>>,
+    pub partials: Vec<Located<&'a PartialInterfaceMixinDefinition<'a>>>,
+    /// Members of the definition, then of the partials
+    pub members: Vec<Member<'a, &'a MixinMember<'a>>>,
+}
+
+/// A dictionary with the members of its partials
+///
+/// Members of inherited dictionaries are not included.
+#[derive(Clone, Debug)]
+pub struct Dictionary<'a> {
+    pub name: &'a str,
+    /// `None` if only partial definitions were found
+    pub definition: Option<Located<&'a DictionaryDefinition<'a>>>,
+    pub partials: Vec<Located<&'a PartialDictionaryDefinition<'a>>>,
+    /// Members of the definition, then of the partials
+    pub members: Vec<Member<'a, &'a DictionaryMember<'a>>>,
+}
+
+/// A namespace with the members of its partials
+#[derive(Clone, Debug)]
+pub struct Namespace<'a> {
+    pub name: &'a str,
+    /// `None` if only partial definitions were found
+    pub definition: Option<Located<&'a NamespaceDefinition<'a>>>,
+    pub partials: Vec<Located<&'a PartialNamespaceDefinition<'a>>>,
+    /// Members of the definition, then of the partials
+    pub members: Vec<Member<'a, &'a NamespaceMember<'a>>>,
+}
+
+/// A merged member and where it was declared
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Member<'a, T> {
+    pub member: T,
+    pub origin: Origin<'a>,
+}
+
+/// The definition a member was declared in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Origin<'a> {
+    /// Index of the file in the list given to [`Model::from_files`](struct.Model.html#method.from_files)
+    pub file: usize,
+    /// Span of the whole definition
+    pub span: Span,
+    pub kind: OriginKind<'a>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OriginKind<'a> {
+    /// The definition of the interface, mixin, dictionary or namespace itself
+    Definition,
+    /// One of its partial definitions
+    Partial,
+    /// The mixin named `name`, which the interface includes, or one of its partial definitions
+    Mixin { name: &'a str, partial: bool },
+}
+
+/// A member of an interface, declared in the interface itself or in an included mixin
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InterfaceMemberRef<'a> {
+    Interface(&'a InterfaceMember<'a>),
+    Mixin(&'a MixinMember<'a>),
+}
+
+impl<'a> InterfaceMemberRef<'a> {
+    /// Name of the constant, attribute or operation, `None` for other members and unnamed
+    /// operations
+    pub fn identifier(&self) -> Option<&'a str> {
+        match *self {
+            InterfaceMemberRef::Interface(member) => match member {
+                InterfaceMember::Const(m) => Some(&m.identifier.0),
+                InterfaceMember::Attribute(m) => Some(&m.identifier.0),
+                InterfaceMember::Operation(m) => m.identifier.as_ref().map(|id| &*id.0),
+                _ => None,
+            },
+            InterfaceMemberRef::Mixin(member) => match member {
+                MixinMember::Const(m) => Some(&m.identifier.0),
+                MixinMember::Attribute(m) => Some(&m.identifier.0),
+                MixinMember::Operation(m) => m.identifier.as_ref().map(|id| &*id.0),
+                MixinMember::Stringifier(_) => None,
+            },
+        }
+    }
+
+    /// Extended attributes of the member
+    pub fn attributes(&self) -> Option<&'a ExtendedAttributeList<'a>> {
+        match *self {
+            InterfaceMemberRef::Interface(member) => match member {
+                InterfaceMember::Const(m) => m.attributes.as_ref(),
+                InterfaceMember::Attribute(m) => m.attributes.as_ref(),
+                InterfaceMember::Constructor(m) => m.attributes.as_ref(),
+                InterfaceMember::Operation(m) => m.attributes.as_ref(),
+                InterfaceMember::Iterable(IterableInterfaceMember::Single(m)) => {
+                    m.attributes.as_ref()
+                }
+                InterfaceMember::Iterable(IterableInterfaceMember::Double(m)) => {
+                    m.attributes.as_ref()
+                }
+                InterfaceMember::AsyncIterable(AsyncIterableInterfaceMember::Single(m)) => {
+                    m.attributes.as_ref()
+                }
+                InterfaceMember::AsyncIterable(AsyncIterableInterfaceMember::Double(m)) => {
+                    m.attributes.as_ref()
+                }
+                InterfaceMember::Maplike(m) => m.attributes.as_ref(),
+                InterfaceMember::Setlike(m) => m.attributes.as_ref(),
+                InterfaceMember::Stringifier(m) => m.attributes.as_ref(),
+                InterfaceMember::Jsonifier(m) => m.attributes.as_ref(),
+                InterfaceMember::Serializer(m) => m.attributes.as_ref(),
+            },
+            InterfaceMemberRef::Mixin(member) => match member {
+                MixinMember::Const(m) => m.attributes.as_ref(),
+                MixinMember::Attribute(m) => m.attributes.as_ref(),
+                MixinMember::Operation(m) => m.attributes.as_ref(),
+                MixinMember::Stringifier(m) => m.attributes.as_ref(),
+            },
+        }
+    }
+}
+
+impl<'a> Spanned for InterfaceMemberRef<'a> {
+    fn span(&self) -> Span {
+        match self {
+            InterfaceMemberRef::Interface(member) => member.span(),
+            InterfaceMemberRef::Mixin(member) => member.span(),
+        }
+    }
+}
+
+impl<'a> Model<'a> {
+    /// Merges the definitions of a single file
+    pub fn new(definitions: &'a [Definition<'a>]) -> Self {
+        Model::from_files(Some(definitions))
+    }
+
+    /// Merges the definitions of several files, which are referred to by their index
+    pub fn from_files<I: IntoIterator<Item = &'a [Definition<'a>]>>(files: I) -> Self {
+        let mut model = Model::default();
+        for (file, definitions) in files.into_iter().enumerate() {
+            for definition in definitions {
+                model.add(definition, file);
+            }
+        }
+        for interface in model.interfaces.values_mut() {
+            interface.merge(&model.mixins);
+        }
+        for mixin in model.mixins.values_mut() {
+            mixin.merge();
+        }
+        for dictionary in model.dictionaries.values_mut() {
+            dictionary.merge();
+        }
+        for namespace in model.namespaces.values_mut() {
+            namespace.merge();
+        }
+        model
+    }
+
+    fn add(&mut self, definition: &'a Definition<'a>, file: usize) {
+        match definition {
+            Definition::Interface(d) => {
+                let entry = self.interface(&d.identifier.0);
+                if entry.definition.is_none() {
+                    entry.definition = Some(Located { node: d, file });
+                }
+            }
+            Definition::PartialInterface(d) => {
+                let entry = self.interface(&d.identifier.0);
+                entry.partials.push(Located { node: d, file });
+            }
+            Definition::IncludesStatement(d) => {
+                let entry = self.interface(&d.lhs_identifier.0);
+                entry.includes.push(Located { node: d, file });
+            }
+            Definition::InterfaceMixin(d) => {
+                let entry = self.mixin(&d.identifier.0);
+                if entry.definition.is_none() {
+                    entry.definition = Some(Located { node: d, file });
+                }
+            }
+            Definition::PartialInterfaceMixin(d) => {
+                let entry = self.mixin(&d.identifier.0);
+                entry.partials.push(Located { node: d, file });
+            }
+            Definition::Dictionary(d) => {
+                let entry = self.dictionary(&d.identifier.0);
+                if entry.definition.is_none() {
+                    entry.definition = Some(Located { node: d, file });
+                }
+            }
+            Definition::PartialDictionary(d) => {
+                let entry = self.dictionary(&d.identifier.0);
+                entry.partials.push(Located { node: d, file });
+            }
+            Definition::Namespace(d) => {
+                let entry = self.namespace(&d.identifier.0);
+                if entry.definition.is_none() {
+                    entry.definition = Some(Located { node: d, file });
+                }
+            }
+            Definition::PartialNamespace(d) => {
+                let entry = self.namespace(&d.identifier.0);
+                entry.partials.push(Located { node: d, file });
+            }
+            Definition::CallbackInterface(d) => {
+                self.callback_interfaces
+                    .entry(&d.identifier.0)
+                    .or_insert(Located { node: d, file });
+            }
+            Definition::Callback(d) => {
+                self.callbacks
+                    .entry(&d.identifier.0)
+                    .or_insert(Located { node: d, file });
+            }
+            Definition::Enum(d) => {
+                self.enums
+                    .entry(&d.identifier.0)
+                    .or_insert(Located { node: d, file });
+            }
+            Definition::Typedef(d) => {
+                self.typedefs
+                    .entry(&d.identifier.0)
+                    .or_insert(Located { node: d, file });
+            }
+            // The legacy `implements` statement has no effect on members
+            Definition::Implements(_) => {}
+        }
+    }
+
+    fn interface(&mut self, name: &'a str) -> &mut Interface<'a> {
+        self.interfaces.entry(name).or_insert_with(|| Interface {
+            name,
+            definition: None,
+            partials: Vec::new(),
+            includes: Vec::new(),
+            members: Vec::new(),
+        })
+    }
+
+    fn mixin(&mut self, name: &'a str) -> &mut Mixin<'a> {
+        self.mixins.entry(name).or_insert_with(|| Mixin {
+            name,
+            definition: None,
+            partials: Vec::new(),
+            members: Vec::new(),
+        })
+    }
+
+    fn dictionary(&mut self, name: &'a str) -> &mut Dictionary<'a> {
+        self.dictionaries.entry(name).or_insert_with(|| Dictionary {
+            name,
+            definition: None,
+            partials: Vec::new(),
+            members: Vec::new(),
+        })
+    }
+
+    fn namespace(&mut self, name: &'a str) -> &mut Namespace<'a> {
+        self.namespaces.entry(name).or_insert_with(|| Namespace {
+            name,
+            definition: None,
+            partials: Vec::new(),
+            members: Vec::new(),
+        })
+    }
+}
+
+impl<'a> Interface<'a> {
+    fn merge(&mut self, mixins: &BTreeMap<&'a str, Mixin<'a>>) {
+        let mut members = Vec::new();
+        if let Some(Located { node, file }) = self.definition {
+            let origin = Origin::new(file, node, OriginKind::Definition);
+            members.extend(node.members.body.iter().map(|member| Member {
+                member: InterfaceMemberRef::Interface(member),
+                origin,
+            }));
+        }
+        for &Located { node, file } in &self.partials {
+            let origin = Origin::new(file, node, OriginKind::Partial);
+            members.extend(node.members.body.iter().map(|member| Member {
+                member: InterfaceMemberRef::Interface(member),
+                origin,
+            }));
+        }
+        for include in &self.includes {
+            let name = &*include.node.rhs_identifier.0;
+            let mixin = match mixins.get(name) {
+                Some(mixin) => mixin,
+                None => continue,
+            };
+            if let Some(Located { node, file }) = mixin.definition {
+                let origin = Origin::new(
+                    file,
+                    node,
+                    OriginKind::Mixin {
+                        name,
+                        partial: false,
+                    },
+                );
+                members.extend(node.members.body.iter().map(|member| Member {
+                    member: InterfaceMemberRef::Mixin(member),
+                    origin,
+                }));
+            }
+            for &Located { node, file } in &mixin.partials {
+                let origin = Origin::new(
+                    file,
+                    node,
+                    OriginKind::Mixin {
+                        name,
+                        partial: true,
+                    },
+                );
+                members.extend(node.members.body.iter().map(|member| Member {
+                    member: InterfaceMemberRef::Mixin(member),
+                    origin,
+                }));
+            }
+        }
+        self.members = members;
+    }
+}
+
+/// Merges the members of a definition and its partials, which share their member type
+macro_rules! merge_partials {
+    ($($name:ident),*) => {
+        $(
+            impl<'a> $name<'a> {
+                fn merge(&mut self) {
+                    let mut members = Vec::new();
+                    if let Some(Located { node, file }) = self.definition {
+                        let origin = Origin::new(file, node, OriginKind::Definition);
+                        members.extend(
+                            node.members.body.iter().map(|member| Member { member, origin }),
+                        );
+                    }
+                    for &Located { node, file } in &self.partials {
+                        let origin = Origin::new(file, node, OriginKind::Partial);
+                        members.extend(
+                            node.members.body.iter().map(|member| Member { member, origin }),
+                        );
+                    }
+                    self.members = members;
+                }
+            }
+        )*
+    };
+}
+
+merge_partials!(Mixin, Dictionary, Namespace);
+
+impl<'a> Origin<'a> {
+    fn new(file: usize, definition: &impl Spanned, kind: OriginKind<'a>) -> Self {
+        Origin {
+            file,
+            span: definition.span(),
+            kind,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names<T>(members: &[Member<'_, T>], name: impl Fn(&T) -> &str) -> Vec<String> {
+        members.iter().map(|m| name(&m.member).to_owned()).collect()
+    }
+
+    #[test]
+    fn should_merge_partials_and_mixins() {
+        let parsed = crate::parse(
+            "partial interface A { attribute long b; };
+            interface A { attribute long a; };
+            A includes M;
+            A includes N;
+            interface mixin M { attribute long m; };
+            partial interface mixin M { attribute long pm; };
+            interface mixin N { const long N = 1; stringifier; };
+            partial interface A { attribute long c; };",
+        )
+        .unwrap();
+        let model = Model::new(&parsed);
+        let a = &model.interfaces["A"];
+
+        assert!(a.definition.is_some());
+        assert_eq!(a.partials.len(), 2);
+        assert_eq!(
+            names(&a.members, |m| m.identifier().unwrap_or("-")),
+            ["a", "b", "c", "m", "pm", "N", "-"]
+        );
+        let kinds: Vec<_> = a.members.iter().map(|m| m.origin.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                OriginKind::Definition,
+                OriginKind::Partial,
+                OriginKind::Partial,
+                OriginKind::Mixin {
+                    name: "M",
+                    partial: false
+                },
+                OriginKind::Mixin {
+                    name: "M",
+                    partial: true
+                },
+                OriginKind::Mixin {
+                    name: "N",
+                    partial: false
+                },
+                OriginKind::Mixin {
+                    name: "N",
+                    partial: false
+                },
+            ]
+        );
+        assert_eq!(model.mixins["M"].members.len(), 2);
+    }
+
+    #[test]
+    fn should_merge_dictionaries_and_namespaces_across_files() {
+        let first = crate::parse(
+            "dictionary D { long a; };
+            namespace N { readonly attribute long a; };
+            typedef long T;",
+        )
+        .unwrap();
+        let second = crate::parse(
+            "partial dictionary D { long b; };
+            partial namespace N { undefined f(); };
+            typedef DOMString T;
+            enum E { \"e\" };",
+        )
+        .unwrap();
+        let model = Model::from_files(vec![&first[..], &second[..]]);
+
+        let d = &model.dictionaries["D"];
+        assert_eq!(names(&d.members, |m| &m.identifier.0), ["a", "b"]);
+        assert_eq!(d.members[1].origin.file, 1);
+        assert_eq!(d.members[1].origin.kind, OriginKind::Partial);
+
+        let n = &model.namespaces["N"];
+        assert_eq!(n.members.len(), 2);
+        assert_eq!(n.members[0].origin.kind, OriginKind::Definition);
+
+        assert_eq!(model.typedefs["T"].file, 0);
+        assert_eq!(model.enums["E"].file, 1);
+    }
+
+    #[test]
+    fn should_keep_interfaces_without_definition() {
+        let parsed = crate::parse("partial interface A {}; B includes M;").unwrap();
+        let model = Model::new(&parsed);
+
+        assert!(model.interfaces["A"].definition.is_none());
+        assert_eq!(model.interfaces["B"].includes.len(), 1);
+        assert!(model.interfaces["B"].members.is_empty());
+    }
+}
@@ -0,0 +1,318 @@
+//! Source locations of parsed nodes
+//!
+//! Every node produced by the parser carries a [`Span`](struct.Span.html) holding the byte
+//! range it was parsed from, measured from the start of the string handed to the outermost
+//! `parse` call. Leading and trailing whitespace and comments are not part of a span.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::span::Spanned;
+//! use weedle::Definition;
+//!
+//! let source = "
+//!     interface Window {
+//!         readonly attribute Storage sessionStorage;
+//!     };
+//! ";
+//! let parsed = weedle::parse(source).unwrap();
+//!
+//! match &parsed[0] {
+//!     Definition::Interface(interface) => {
+//!         let span = interface.identifier.span();
+//!         assert_eq!(span.source_text(source), "Window");
+//!
+//!         let position = span.start_line_column(source);
+//!         assert_eq!((position.line, position.column), (2, 15));
+//!     }
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+use std::borrow::Cow;
+
+/// A byte range `start..end` into the parsed source
+///
+/// Spans take part in comparisons of the nodes holding them, so nodes parsed from differently
+/// laid out sources compare unequal. [`SpanlessEq`](trait.SpanlessEq.html) compares nodes
+/// without them.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// Creates a span covering `start..end`
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Number of bytes covered by the span
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns `true` if the span covers no bytes
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the smallest span covering both `self` and `other`
+    pub fn join(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Returns the text covered by the span, or an empty string if the span is out of range
+    pub fn source_text<'s>(&self, source: &'s str) -> &'s str {
+        source.get(self.start..self.end).unwrap_or("")
+    }
+
+    /// Line and column at which the span starts
+    pub fn start_line_column(&self, source: &str) -> LineColumn {
+        line_column(source, self.start)
+    }
+
+    /// Line and column at which the span ends
+    pub fn end_line_column(&self, source: &str) -> LineColumn {
+        line_column(source, self.end)
+    }
+}
+
+/// Implemented by every node that knows where it came from
+pub trait Spanned {
+    fn span(&self) -> Span;
+}
+
+impl<T: Spanned> Spanned for Box<T> {
+    fn span(&self) -> Span {
+        (**self).span()
+    }
+}
+
+impl<T: Spanned, U: Spanned> Spanned for (T, U) {
+    fn span(&self) -> Span {
+        self.0.span().join(self.1.span())
+    }
+}
+
+/// Equality of nodes which ignores where they came from
+///
+/// Useful to compare parsed nodes with nodes built by hand, or with the same nodes parsed from
+/// a differently laid out source.
+///
+/// ### Example
+///
+/// ```
+/// use weedle::span::SpanlessEq;
+///
+/// let compact = weedle::parse("interface Window{};").unwrap();
+/// let spaced = weedle::parse("interface Window {\n};").unwrap();
+/// assert_ne!(compact, spaced);
+/// assert!(compact.spanless_eq(&spaced));
+/// ```
+pub trait SpanlessEq {
+    fn spanless_eq(&self, other: &Self) -> bool;
+}
+
+impl SpanlessEq for Span {
+    fn spanless_eq(&self, _: &Span) -> bool {
+        true
+    }
+}
+
+impl<'a> SpanlessEq for Cow<'a, str> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl SpanlessEq for bool {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl<T: SpanlessEq> SpanlessEq for Option<T> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.spanless_eq(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: SpanlessEq + ?Sized> SpanlessEq for Box<T> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        (**self).spanless_eq(other)
+    }
+}
+
+impl<T: SpanlessEq + ?Sized> SpanlessEq for &T {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        (**self).spanless_eq(*other)
+    }
+}
+
+impl<T: SpanlessEq> SpanlessEq for [T] {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.spanless_eq(b))
+    }
+}
+
+impl<T: SpanlessEq> SpanlessEq for Vec<T> {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self[..].spanless_eq(&other[..])
+    }
+}
+
+impl<T: SpanlessEq, U: SpanlessEq> SpanlessEq for (T, U) {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.0.spanless_eq(&other.0) && self.1.spanless_eq(&other.1)
+    }
+}
+
+impl<T: SpanlessEq, U: SpanlessEq, V: SpanlessEq> SpanlessEq for (T, U, V) {
+    fn spanless_eq(&self, other: &Self) -> bool {
+        self.0.spanless_eq(&other.0) && self.1.spanless_eq(&other.1) && self.2.spanless_eq(&other.2)
+    }
+}
+
+/// A 1-based line and column. Columns count characters, not bytes.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Converts a byte offset into `source` to a line and column
+///
+/// Offsets past the end of `source` are clamped to its end.
+pub fn line_column(source: &str, offset: usize) -> LineColumn {
+    LineIndex::new(source).line_column(source, offset)
+}
+
+/// Precomputed line starts of a source, for converting many offsets
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            source
+                .bytes()
+                .enumerate()
+                .filter(|&(_, b)| b == b'\n')
+                .map(|(i, _)| i + 1),
+        );
+        LineIndex { line_starts }
+    }
+
+    /// Converts a byte offset into `source`, which must be the string the index was built
+    /// from, to a line and column
+    pub fn line_column(&self, source: &str, offset: usize) -> LineColumn {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let line_start = self.line_starts[line];
+        LineColumn {
+            line: line + 1,
+            column: source[line_start..offset].chars().count() + 1,
+        }
+    }
+
+    /// Byte offset at which the given 1-based line starts
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line.checked_sub(1)?).cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::Identifier;
+    use crate::interface::InterfaceMember;
+    use crate::{Definition, Parse};
+
+    #[test]
+    fn should_compare_spans() {
+        assert_ne!(Span::new(0, 3), Span::new(5, 9));
+        assert!(Span::new(0, 3).spanless_eq(&Span::new(5, 9)));
+    }
+
+    #[test]
+    fn should_compare_nodes_without_spans() {
+        let (_, parsed) = Identifier::parse("  Window").unwrap();
+        assert_ne!(parsed, Identifier::new("Window"));
+        assert!(parsed.spanless_eq(&Identifier::new("Window")));
+        assert!(!parsed.spanless_eq(&Identifier::new("Windows")));
+    }
+
+    #[test]
+    fn should_compute_line_column() {
+        let source = "ab\ncd\n\u{e9}f";
+        assert_eq!(line_column(source, 0), LineColumn { line: 1, column: 1 });
+        assert_eq!(line_column(source, 4), LineColumn { line: 2, column: 2 });
+        assert_eq!(line_column(source, 8), LineColumn { line: 3, column: 2 });
+        assert_eq!(line_column(source, 100), LineColumn { line: 3, column: 3 });
+    }
+
+    #[test]
+    fn should_span_tokens_without_trivia() {
+        let source = "  /* hi */ hello  // there\n";
+        let (_, parsed) = Identifier::parse(source).unwrap();
+        assert_eq!(parsed.span().source_text(source), "hello");
+        assert_eq!(parsed.span(), Span::new(11, 16));
+        assert_eq!(parsed.span().start, 11);
+    }
+
+    #[test]
+    fn should_span_nested_nodes() {
+        let source = "
+            // comment
+            [Exposed=Window]
+            interface Foo {
+                attribute long bar;
+                undefined baz();
+            };
+        ";
+        let definitions = crate::parse(source).unwrap();
+        let interface = match &definitions[0] {
+            Definition::Interface(interface) => interface,
+            _ => unreachable!(),
+        };
+        assert!(interface
+            .span
+            .source_text(source)
+            .starts_with("[Exposed=Window]"));
+        assert!(interface.span.source_text(source).ends_with("};"));
+        assert_eq!(interface.interface.span.source_text(source), "interface");
+        assert_eq!(interface.identifier.1.source_text(source), "Foo");
+
+        let members = &interface.members.body;
+        assert_eq!(members[0].span().source_text(source), "attribute long bar;");
+        assert_eq!(members[1].span().source_text(source), "undefined baz();");
+        match &members[1] {
+            InterfaceMember::Operation(op) => {
+                assert_eq!(op.args.span.source_text(source), "()");
+                assert_eq!(op.args.body.span.start, op.args.open_paren.span.end);
+            }
+            _ => unreachable!(),
+        }
+
+        let position = members[1].span().start_line_column(source);
+        assert_eq!((position.line, position.column), (6, 17));
+    }
+}
@@ -0,0 +1,358 @@
+//! Bookkeeping shared by all parsers during a single top-level parse
+//!
+//! Parsers only ever see the remaining input, so positions are recovered from the address of
+//! that input relative to the string given to the outermost `parse` call. The outermost call
+//! is detected by nesting depth and owns the state until it returns.
+
+use std::cell::{Cell, RefCell};
+
+use crate::common::Identifier;
+use crate::span::{Span, Spanned};
+use crate::whitespace::sp;
+use crate::{Grammar, ParseOptions};
+
+#[derive(Default)]
+struct State {
+    /// Tokens parsed by the nodes which are being parsed. A node which is done leaves a single
+    /// token behind, covering all of its own.
+    tokens: Vec<Token>,
+    /// Nodes currently being parsed, outermost first
+    frames: Vec<Frame>,
+    /// The failure which got furthest into the input
+    failure: Option<Failure>,
+    /// Whether lists skip over elements they cannot parse
+    recovering: bool,
+    /// Failures of the elements that were skipped
+    recovered: Vec<Failure>,
+    /// Which forms of the grammar and which extensions are accepted
+    options: ParseOptions,
+}
+
+/// Offsets of a token, or of all the tokens of a node
+#[derive(Clone, Copy, Debug)]
+struct Token {
+    start: usize,
+    end: usize,
+    /// Offset after the trailing trivia
+    rest: usize,
+}
+
+/// A node which was being parsed when a failure was recorded
+#[derive(Clone, Debug)]
+pub(crate) struct Frame {
+    /// Name of the AST type, e.g. `DictionaryMember`
+    pub(crate) kind: &'static str,
+    /// Span of the node's identifier, once it has been parsed
+    pub(crate) name: Option<Span>,
+}
+
+/// What the parser expected at the furthest point it reached
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Failure {
+    pub(crate) offset: usize,
+    /// Sorted and without duplicates once taken
+    pub(crate) expected: Vec<&'static str>,
+    pub(crate) frames: Vec<Frame>,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+    // Touched by every parser, so kept out of `STATE` where they are cheap to get at
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static BASE: Cell<usize> = const { Cell::new(0) };
+    // Offset of the furthest failure, so failures behind it are dropped without touching `STATE`
+    static FURTHEST: Cell<usize> = const { Cell::new(0) };
+    // Whether failures and the nodes they happened in are recorded
+    static TRACKING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Keeps the parse state alive while at least one parser is running
+pub(crate) struct Guard {
+    frame: bool,
+    /// Number of tokens parsed before the node started
+    mark: usize,
+}
+
+impl Guard {
+    /// Span of a node which was parsed from `input` and left `rest` unconsumed
+    ///
+    /// The tokens the node was parsed from are replaced with one covering them all, which is
+    /// what the node looks like to its parent.
+    pub(crate) fn span(&self, input: &str, rest: &str) -> Span {
+        let rest = offset(rest);
+        let parsed = STATE.with(|state| {
+            let tokens = &mut state.borrow_mut().tokens;
+            let start = tokens.get(self.mark)?.start;
+            // Tokens after the node's last one were parsed by lookahead which failed later
+            let end = tokens[self.mark..]
+                .iter()
+                .rev()
+                .find(|token| token.rest == rest)?
+                .end;
+            tokens.truncate(self.mark);
+            tokens.push(Token { start, end, rest });
+            Some(Span::new(start, end))
+        });
+        parsed.unwrap_or_else(|| empty_span(input))
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let depth = DEPTH.with(|depth| {
+            depth.set(depth.get() - 1);
+            depth.get()
+        });
+        if self.frame || depth == 0 {
+            STATE.with(|state| {
+                let mut state = state.borrow_mut();
+                if self.frame {
+                    state.frames.pop();
+                }
+                if depth == 0 {
+                    state.tokens.clear();
+                }
+            });
+        }
+    }
+}
+
+/// Counts the parser in, setting up the state if it is the outermost one
+fn enter_depth(input: &str) {
+    let outermost = DEPTH.with(|depth| {
+        depth.set(depth.get() + 1);
+        depth.get() == 1
+    });
+    if outermost {
+        BASE.with(|base| base.set(input.as_ptr() as usize));
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            state.frames.clear();
+            state.failure = None;
+            state.recovered.clear();
+        });
+        FURTHEST.with(|furthest| furthest.set(0));
+    }
+}
+
+/// Must be held by every parser for the duration of its run. Only the outermost parser gets
+/// a guard, which sets up the state for the ones it runs.
+pub(crate) fn enter(input: &str) -> Option<Guard> {
+    if DEPTH.with(Cell::get) != 0 {
+        return None;
+    }
+    enter_depth(input);
+    Some(Guard {
+        frame: false,
+        mark: 0,
+    })
+}
+
+/// Like `enter`, but for a node made of other nodes, which shows up in the context of errors
+/// while failures are tracked
+pub(crate) fn enter_node(input: &str, kind: &'static str) -> Guard {
+    enter_depth(input);
+    let frame = TRACKING.with(Cell::get);
+    let mark = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if frame {
+            state.frames.push(Frame { kind, name: None });
+        }
+        state.tokens.len()
+    });
+    Guard { frame, mark }
+}
+
+/// Byte offset of `input` in the string given to the outermost parser
+pub(crate) fn offset(input: &str) -> usize {
+    (input.as_ptr() as usize).wrapping_sub(BASE.with(Cell::get))
+}
+
+pub(crate) fn skip_trivia(input: &str) -> &str {
+    match sp(input) {
+        Ok((input, _)) => input,
+        Err(_) => input,
+    }
+}
+
+/// Records a token which started at `start`, ended right before `end` and whose trailing
+/// trivia ended before `rest`
+pub(crate) fn record_token(start: &str, end: &str, rest: &str) {
+    let token = Token {
+        start: offset(start),
+        end: offset(end),
+        rest: offset(rest),
+    };
+    STATE.with(|state| state.borrow_mut().tokens.push(token));
+}
+
+/// Span of a node which was parsed from a single token, right after parsing it from `input`
+/// up to `rest`
+pub(crate) fn token_span(input: &str, rest: &str) -> Span {
+    let rest = offset(rest);
+    let token = STATE.with(|state| state.borrow().tokens.last().cloned());
+    match token {
+        Some(token) if token.rest == rest => Span::new(token.start, token.end),
+        _ => empty_span(input),
+    }
+}
+
+fn empty_span(input: &str) -> Span {
+    let start = offset(skip_trivia(input));
+    Span::new(start, start)
+}
+
+/// Records that `what` was expected but not found at the start of `input`
+pub(crate) fn expected(input: &str, what: &'static str) {
+    if !TRACKING.with(Cell::get) {
+        return;
+    }
+    let at = offset(skip_trivia(input));
+    if at < FURTHEST.with(Cell::get) {
+        return;
+    }
+    FURTHEST.with(|furthest| furthest.set(at));
+    STATE.with(|state| {
+        let state = &mut *state.borrow_mut();
+        let failure = state.failure.get_or_insert_with(|| Failure {
+            offset: at,
+            ..Failure::default()
+        });
+        if at < failure.offset {
+            return;
+        }
+        // Most failures get further than the last, so their allocations are reused
+        if at > failure.offset {
+            failure.offset = at;
+            failure.expected.clear();
+            failure.frames.clear();
+        }
+        if !failure.expected.contains(&what) {
+            failure.expected.push(what);
+        }
+        if state.frames.len() > failure.frames.len() {
+            failure.frames.clone_from(&state.frames);
+        }
+    });
+}
+
+/// Returns the furthest failure of the last top-level parse
+pub(crate) fn take_failure() -> Option<Failure> {
+    FURTHEST.with(|furthest| furthest.set(0));
+    let mut failure = STATE.with(|state| state.borrow_mut().failure.take())?;
+    failure.expected.sort_unstable();
+    failure.expected.dedup();
+    Some(failure)
+}
+
+/// Forgets the furthest failure, so the next one describes only what is parsed from now on
+pub(crate) fn reset_failure() {
+    STATE.with(|state| state.borrow_mut().failure = None);
+    FURTHEST.with(|furthest| furthest.set(0));
+}
+
+/// Records failures and the nodes they happen in until the returned guard is dropped
+///
+/// Only needed to describe errors, and slows the parser down, so it is off by default.
+pub(crate) fn track_failures() -> TrackingGuard {
+    TrackingGuard(TRACKING.with(|tracking| tracking.replace(true)))
+}
+
+pub(crate) struct TrackingGuard(bool);
+
+impl Drop for TrackingGuard {
+    fn drop(&mut self) {
+        TRACKING.with(|tracking| tracking.set(self.0));
+    }
+}
+
+/// Turns recovery on until the returned guard is dropped
+pub(crate) fn start_recovering() -> RecoveringGuard {
+    STATE.with(|state| state.borrow_mut().recovering = true);
+    RecoveringGuard(())
+}
+
+pub(crate) struct RecoveringGuard(());
+
+impl Drop for RecoveringGuard {
+    fn drop(&mut self) {
+        STATE.with(|state| state.borrow_mut().recovering = false);
+    }
+}
+
+pub(crate) fn recovering() -> bool {
+    STATE.with(|state| state.borrow().recovering)
+}
+
+/// Accepts what `options` allow until the returned guard is dropped
+pub(crate) fn use_options(options: ParseOptions) -> OptionsGuard {
+    let previous = STATE.with(|state| std::mem::replace(&mut state.borrow_mut().options, options));
+    OptionsGuard(previous)
+}
+
+pub(crate) struct OptionsGuard(ParseOptions);
+
+impl Drop for OptionsGuard {
+    fn drop(&mut self) {
+        STATE.with(|state| state.borrow_mut().options = self.0);
+    }
+}
+
+/// Returns `true` if `keyword` is not part of what is being parsed: a legacy keyword with
+/// the strict grammar, or an extension outside of its dialect
+pub(crate) fn excludes(keyword: &str, legacy: bool) -> bool {
+    let options = STATE.with(|state| state.borrow().options);
+    let outside = !legacy || options.grammar == Grammar::Strict;
+    outside && !options.dialect.has_keyword(keyword)
+}
+
+/// Remembers the failure of an element that was skipped
+pub(crate) fn record_recovered(failure: Failure) {
+    STATE.with(|state| state.borrow_mut().recovered.push(failure));
+}
+
+/// Number of failures recorded by `record_recovered` so far
+pub(crate) fn recovered_len() -> usize {
+    STATE.with(|state| state.borrow().recovered.len())
+}
+
+/// Forgets the failures recorded after the first `len`, because the parse they belonged to
+/// was abandoned
+pub(crate) fn truncate_recovered(len: usize) {
+    STATE.with(|state| state.borrow_mut().recovered.truncate(len));
+}
+
+pub(crate) fn take_recovered() -> Vec<Failure> {
+    STATE.with(|state| std::mem::take(&mut state.borrow_mut().recovered))
+}
+
+/// Something which can name the node that is being parsed
+pub(crate) trait FrameName {
+    fn frame_name(&self) -> Option<Span>;
+}
+
+impl<'a> FrameName for Identifier<'a> {
+    fn frame_name(&self) -> Option<Span> {
+        Some(self.span())
+    }
+}
+
+impl<T: FrameName> FrameName for Option<T> {
+    fn frame_name(&self) -> Option<Span> {
+        self.as_ref().and_then(FrameName::frame_name)
+    }
+}
+
+/// Names the innermost node being parsed
+pub(crate) fn name_frame<T: FrameName>(name: &T) {
+    if !TRACKING.with(Cell::get) {
+        return;
+    }
+    if let Some(name) = name.frame_name() {
+        STATE.with(|state| {
+            if let Some(frame) = state.borrow_mut().frames.last_mut() {
+                frame.name = Some(name);
+            }
+        });
+    }
+}
@@ -2,14 +2,90 @@ macro_rules! generate_terms {
     ($( $(#[$attr:meta])* $typ:ident => $tok:expr ),*) => {
         $(
             $(#[$attr])*
-            #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
-            pub struct $typ;
+            #[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+            pub struct $typ {
+                pub span: $crate::span::Span,
+                /// Only kept by [`parse_with_trivia`](../fn.parse_with_trivia.html)
+                pub trivia: Option<Box<$crate::trivia::TokenTrivia>>,
+            }
+
+            /// The token without a source location, so `term!(...)` can be used as a value
+            #[allow(non_upper_case_globals)]
+            pub const $typ: $typ = $typ {
+                span: $crate::span::Span { start: 0, end: 0 },
+                trivia: None,
+            };
 
             impl<'a> $crate::Parse<'a> for $typ {
-                parser!(do_parse!(
-                    ws!(tag!($tok)) >>
-                    ($typ)
-                ));
+                fn parse(input: &'a str) -> $crate::IResult<&'a str, Self> {
+                    let _guard = $crate::state::enter(input);
+                    let (rest, _) = expect!(input, concat!("`", $tok, "`"), ws!(tag!($tok)))?;
+                    Ok((rest, $typ { span: $crate::state::token_span(input, rest), trivia: None }))
+                }
+            }
+
+            impl $crate::span::Spanned for $typ {
+                fn span(&self) -> $crate::span::Span {
+                    self.span
+                }
+            }
+
+            impl $crate::IntoOwned for $typ {
+                type Owned = $typ;
+
+                fn into_owned(self) -> Self::Owned {
+                    self
+                }
+            }
+
+            // Tokens of one type only differ in where they are
+            impl $crate::span::SpanlessEq for $typ {
+                fn spanless_eq(&self, _: &Self) -> bool {
+                    true
+                }
+            }
+
+            impl $crate::print::ToWebIdl for $typ {
+                fn write_webidl(&self, printer: &mut $crate::print::Printer) {
+                    printer.token($tok, self.trivia.as_deref());
+                }
+            }
+
+            impl $crate::trivia::Tokens for $typ {
+                fn tokens<'t>(
+                    &'t self,
+                    tokens: &mut Vec<($crate::span::Span, Option<&'t $crate::trivia::TokenTrivia>)>,
+                ) {
+                    tokens.push((self.span, self.trivia.as_deref()));
+                }
+
+                fn tokens_mut<'t>(
+                    &'t mut self,
+                    tokens: &mut Vec<($crate::span::Span, &'t mut Option<Box<$crate::trivia::TokenTrivia>>)>,
+                ) {
+                    tokens.push((self.span, &mut self.trivia));
+                }
+            }
+
+            // Tokens are serialized as their text, their location is left out
+            #[cfg(feature = "serde")]
+            impl serde::Serialize for $typ {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str($tok)
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<'de> serde::Deserialize<'de> for $typ {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_token(deserializer, $tok).map(|()| $typ)
+                }
+            }
+
+            // Tokens have no visitor method
+            impl<'a> $crate::visit::Accept<'a> for $typ {
+                fn accept<V: $crate::visit::Visit<'a> + ?Sized>(&'a self, _: &mut V) {}
+                fn accept_mut<V: $crate::visit::VisitMut<'a> + ?Sized>(&mut self, _: &mut V) {}
             }
         )*
     };
@@ -39,16 +115,100 @@ macro_rules! generate_terms_for_names {
     ($( $(#[$attr:meta])* $typ:ident => $tok:expr,)*) => {
         $(
             $(#[$attr])*
-            #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
-            pub struct $typ;
+            #[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+            pub struct $typ {
+                pub span: $crate::span::Span,
+                /// Only kept by [`parse_with_trivia`](../fn.parse_with_trivia.html)
+                pub trivia: Option<Box<$crate::trivia::TokenTrivia>>,
+            }
+
+            /// The token without a source location, so `term!(...)` can be used as a value
+            #[allow(non_upper_case_globals)]
+            pub const $typ: $typ = $typ {
+                span: $crate::span::Span { start: 0, end: 0 },
+                trivia: None,
+            };
 
             impl<'a> $crate::Parse<'a> for $typ {
-                parser!(do_parse!(
-                    ws!(ident_tag!($tok)) >>
-                    ($typ)
-                ));
+                fn parse(input: &'a str) -> $crate::IResult<&'a str, Self> {
+                    let _guard = $crate::state::enter(input);
+                    // Not recorded as expected, so errors suggest the standard form instead
+                    let legacy = LEGACY.contains(&$tok);
+                    if (legacy || EXTENSIONS.contains(&$tok)) && $crate::state::excludes($tok, legacy) {
+                        return Err($crate::Err::Error((input, $crate::ErrorKind::Tag)));
+                    }
+                    let (rest, _) = expect!(input, concat!("`", $tok, "`"), ws!(ident_tag!($tok)))?;
+                    Ok((rest, $typ { span: $crate::state::token_span(input, rest), trivia: None }))
+                }
+            }
+
+            impl $crate::span::Spanned for $typ {
+                fn span(&self) -> $crate::span::Span {
+                    self.span
+                }
+            }
+
+            impl $crate::IntoOwned for $typ {
+                type Owned = $typ;
+
+                fn into_owned(self) -> Self::Owned {
+                    self
+                }
+            }
+
+            // Tokens of one type only differ in where they are
+            impl $crate::span::SpanlessEq for $typ {
+                fn spanless_eq(&self, _: &Self) -> bool {
+                    true
+                }
+            }
+
+            impl $crate::print::ToWebIdl for $typ {
+                fn write_webidl(&self, printer: &mut $crate::print::Printer) {
+                    printer.token($tok, self.trivia.as_deref());
+                }
+            }
+
+            impl $crate::trivia::Tokens for $typ {
+                fn tokens<'t>(
+                    &'t self,
+                    tokens: &mut Vec<($crate::span::Span, Option<&'t $crate::trivia::TokenTrivia>)>,
+                ) {
+                    tokens.push((self.span, self.trivia.as_deref()));
+                }
+
+                fn tokens_mut<'t>(
+                    &'t mut self,
+                    tokens: &mut Vec<($crate::span::Span, &'t mut Option<Box<$crate::trivia::TokenTrivia>>)>,
+                ) {
+                    tokens.push((self.span, &mut self.trivia));
+                }
+            }
+
+            // Tokens are serialized as their text, their location is left out
+            #[cfg(feature = "serde")]
+            impl serde::Serialize for $typ {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str($tok)
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<'de> serde::Deserialize<'de> for $typ {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_token(deserializer, $tok).map(|()| $typ)
+                }
+            }
+
+            // Tokens have no visitor method
+            impl<'a> $crate::visit::Accept<'a> for $typ {
+                fn accept<V: $crate::visit::Visit<'a> + ?Sized>(&'a self, _: &mut V) {}
+                fn accept_mut<V: $crate::visit::VisitMut<'a> + ?Sized>(&mut self, _: &mut V) {}
             }
         )*
+
+        /// Every keyword, so identifiers spelled like one can be told apart when printing
+        pub(crate) const NAMES: &[&str] = &[$($tok,)*];
     };
 }
 
@@ -115,6 +275,9 @@ generate_terms_for_names! {
     /// Represents the terminal symbol `async`
     Async => "async",
 
+    /// Represents the terminal symbol `async_iterable`
+    AsyncIterable => "async_iterable",
+
     /// Represents the terminal symbol `attribute`
     Attribute => "attribute",
 
@@ -181,6 +344,9 @@ generate_terms_for_names! {
     /// Represents the terminal symbol `symbol`
     Symbol => "symbol",
 
+    /// Represents the terminal symbol `bigint`
+    Bigint => "bigint",
+
     /// Represents the terminal symbol `Infinity`
     NegInfinity => "-Infinity",
 
@@ -193,6 +359,9 @@ generate_terms_for_names! {
     /// Represents the terminal symbol `FrozenArray`
     FrozenArray => "FrozenArray",
 
+    /// Represents the terminal symbol `ObservableArray`
+    ObservableArray => "ObservableArray",
+
     /// Represents the terminal symbol `Infinity`
     Infinity => "Infinity",
 
@@ -277,12 +446,21 @@ generate_terms_for_names! {
     /// Represents the terminal symbol `Uint8ClampedArray`
     Uint8ClampedArray => "Uint8ClampedArray",
 
+    /// Represents the terminal symbol `Float16Array`
+    Float16Array => "Float16Array",
+
     /// Represents the terminal symbol `Float32Array`
     Float32Array => "Float32Array",
 
     /// Represents the terminal symbol `Float64Array`
     Float64Array => "Float64Array",
 
+    /// Represents the terminal symbol `BigInt64Array`
+    BigInt64Array => "BigInt64Array",
+
+    /// Represents the terminal symbol `BigUint64Array`
+    BigUint64Array => "BigUint64Array",
+
     /// Represents the terminal symbol `ArrayBufferView`
     ArrayBufferView => "ArrayBufferView",
 
@@ -309,8 +487,38 @@ generate_terms_for_names! {
 
     /// Represents the terminal symbol `constructor`
     Constructor => "constructor",
+
+    /// Represents the terminal symbol `jsonifier`
+    Jsonifier => "jsonifier",
+
+    /// Represents the terminal symbol `serializer`
+    Serializer => "serializer",
 }
 
+/// Keywords which only appear in forms removed from the current grammar: `async iterable`,
+/// Reads a token serialized as its text, which must be `token`
+#[cfg(feature = "serde")]
+fn deserialize_token<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+    token: &'static str,
+) -> Result<(), D::Error> {
+    let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+    if text == token {
+        Ok(())
+    } else {
+        Err(serde::de::Error::invalid_value(
+            serde::de::Unexpected::Str(&text),
+            &token,
+        ))
+    }
+}
+
+/// `implements` statements and `legacycaller` operations
+const LEGACY: &[&str] = &["async", "implements", "legacycaller"];
+
+/// Keywords which only browser engine dialects have
+pub(crate) const EXTENSIONS: &[&str] = &["jsonifier", "serializer"];
+
 #[macro_export]
 macro_rules! term {
     (OpenParen) => {
@@ -373,6 +581,9 @@ macro_rules! term {
     (async) => {
         $crate::term::Async
     };
+    (async_iterable) => {
+        $crate::term::AsyncIterable
+    };
     (attribute) => {
         $crate::term::Attribute
     };
@@ -439,6 +650,9 @@ macro_rules! term {
     (symbol) => {
         $crate::term::Symbol
     };
+    (bigint) => {
+        $crate::term::Bigint
+    };
     (- Infinity) => {
         $crate::term::NegInfinity
     };
@@ -451,6 +665,9 @@ macro_rules! term {
     (FrozenArray) => {
         $crate::term::FrozenArray
     };
+    (ObservableArray) => {
+        $crate::term::ObservableArray
+    };
     (Infinity) => {
         $crate::term::Infinity
     };
@@ -535,12 +752,21 @@ macro_rules! term {
     (Uint8ClampedArray) => {
         $crate::term::Uint8ClampedArray
     };
+    (Float16Array) => {
+        $crate::term::Float16Array
+    };
     (Float32Array) => {
         $crate::term::Float32Array
     };
     (Float64Array) => {
         $crate::term::Float64Array
     };
+    (BigInt64Array) => {
+        $crate::term::BigInt64Array
+    };
+    (BigUint64Array) => {
+        $crate::term::BigUint64Array
+    };
     (ArrayBufferView) => {
         $crate::term::ArrayBufferView
     };
@@ -565,6 +791,12 @@ macro_rules! term {
     (legacycaller) => {
         $crate::term::LegacyCaller
     };
+    (jsonifier) => {
+        $crate::term::Jsonifier
+    };
+    (serializer) => {
+        $crate::term::Serializer
+    };
     (constructor) => {
         $crate::term::Constructor
     };
@@ -583,35 +815,35 @@ mod test {
                     fn should_parse() {
                         let (rem, parsed) = $typ::parse(concat!($string)).unwrap();
                         assert_eq!(rem, "");
-                        assert_eq!(parsed, $typ);
+                        assert_spanless_eq!(parsed, $typ);
                     }
 
                     #[test]
                     fn should_parse_with_preceding_spaces() {
                         let (rem, parsed) = $typ::parse(concat!("  ", $string)).unwrap();
                         assert_eq!(rem, "");
-                        assert_eq!(parsed, $typ);
+                        assert_spanless_eq!(parsed, $typ);
                     }
 
                     #[test]
                     fn should_parse_with_succeeding_spaces() {
                         let (rem, parsed) = $typ::parse(concat!($string, "  ")).unwrap();
                         assert_eq!(rem, "");
-                        assert_eq!(parsed, $typ);
+                        assert_spanless_eq!(parsed, $typ);
                     }
 
                     #[test]
                     fn should_parse_with_surrounding_spaces() {
                         let (rem, parsed) = $typ::parse(concat!("  ", $string, "  ")).unwrap();
                         assert_eq!(rem, "");
-                        assert_eq!(parsed, $typ);
+                        assert_spanless_eq!(parsed, $typ);
                     }
 
                     #[test]
                     fn should_parse_if_anything_next() {
                         let (rem, parsed) = $typ::parse(concat!($string, "  anything")).unwrap();
                         assert_eq!(rem, "anything");
-                        assert_eq!(parsed, $typ);
+                        assert_spanless_eq!(parsed, $typ);
                     }
                 }
             )*
@@ -639,6 +871,7 @@ mod test {
         or, Or, "or";
         optional, Optional, "optional";
         async_, Async, "async";
+        async_iterable, AsyncIterable, "async_iterable";
         attribute, Attribute, "attribute";
         callback, Callback, "callback";
         const_, Const, "const";
@@ -661,10 +894,12 @@ mod test {
         typedef, Typedef, "typedef";
         unrestricted, Unrestricted, "unrestricted";
         symbol, Symbol, "symbol";
+        bigint, Bigint, "bigint";
         neginfinity, NegInfinity, "-Infinity";
         bytestring, ByteString, "ByteString";
         domstring, DOMString, "DOMString";
         frozenarray, FrozenArray, "FrozenArray";
+        observablearray, ObservableArray, "ObservableArray";
         infinity, Infinity, "Infinity";
         nan, NaN, "NaN";
         usvstring, USVString, "USVString";
@@ -693,12 +928,28 @@ mod test {
         uint16array, Uint16Array, "Uint16Array";
         uint32array, Uint32Array, "Uint32Array";
         uint8clampedarray, Uint8ClampedArray, "Uint8ClampedArray";
+        float16array, Float16Array, "Float16Array";
         float32array, Float32Array, "Float32Array";
         float64array, Float64Array, "Float64Array";
+        bigint64array, BigInt64Array, "BigInt64Array";
+        biguint64array, BigUint64Array, "BigUint64Array";
         promise, Promise, "Promise";
         error, Error, "Error";
         implements, Implements, "implements";
         legacycaller, LegacyCaller, "legacycaller";
         constructor, Constructor, "constructor";
     ];
+
+    #[test]
+    fn should_parse_extensions_only_in_dialects() {
+        use super::{Jsonifier, Serializer};
+        use crate::{Dialect, Parse, ParseOptions};
+
+        assert!(Jsonifier::parse("jsonifier").is_err());
+        assert!(Serializer::parse("serializer").is_err());
+
+        let _options = crate::state::use_options(ParseOptions::new().dialect(Dialect::Gecko));
+        assert_spanless_eq!(Jsonifier::parse("jsonifier").unwrap().1, Jsonifier);
+        assert_spanless_eq!(Serializer::parse(" serializer ").unwrap().1, Serializer);
+    }
 }
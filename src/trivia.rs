@@ -0,0 +1,447 @@
+//! Whitespace and comments around the tokens of a parsed source
+//!
+//! The parser skips trivia, but [`parse_with_trivia`](../fn.parse_with_trivia.html) keeps it on
+//! the tokens of the tree: every keyword, punctuation, literal and identifier has a
+//! [`TokenTrivia`](struct.TokenTrivia.html) holding what comes before it and what follows it on
+//! its line. The functions of this module find the comments attached to any node from its
+//! tokens, and [`print`](fn.print.html) prints nodes back with their original layout.
+//!
+//! As the trivia lives on the tokens, it goes wherever a node is moved, and nodes built by hand
+//! can be given trivia of their own.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::Definition;
+//! use weedle::trivia;
+//!
+//! let source = "
+//! // The storage of a browsing context
+//! interface Storage {
+//!   /* Number of items */ readonly attribute unsigned long length;
+//!   getter DOMString? getItem(DOMString key); // null if missing
+//! };
+//! ";
+//! let (parsed, end) = weedle::parse_with_trivia(source).unwrap();
+//!
+//! let interface = match &parsed[0] {
+//!     Definition::Interface(interface) => interface,
+//!     _ => unreachable!(),
+//! };
+//! assert_eq!(
+//!     trivia::doc_comment(interface).as_deref(),
+//!     Some("The storage of a browsing context")
+//! );
+//!
+//! let members = &interface.members.body;
+//! assert_eq!(trivia::doc_comment(&members[0]).as_deref(), Some("Number of items"));
+//! assert_eq!(trivia::trailing_comment(&members[1]).unwrap().text, "// null if missing");
+//!
+//! assert_eq!(trivia::print(&parsed) + end, source);
+//! ```
+
+use crate::print::{Printer, ToWebIdl};
+use crate::span::Span;
+
+/// The whitespace and comments around a token
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TokenTrivia {
+    /// Trivia before the token, after the line of the previous token
+    pub leading: String,
+    /// Trivia after the token up to the end of its line, including the line break
+    pub trailing: String,
+}
+
+/// Implemented by every node, lists its tokens with the trivia kept on them
+pub trait Tokens {
+    /// Pushes the span and trivia of each token of the node, in source order
+    fn tokens<'t>(&'t self, tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>);
+
+    /// Like [`tokens`](#tymethod.tokens), but with mutable access to the trivia
+    fn tokens_mut<'t>(&'t mut self, tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>);
+}
+
+/// A comment found in the trivia
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Comment<'a> {
+    pub kind: CommentKind,
+    /// The comment including its `//`, `/*` and `*/` markers
+    pub text: &'a str,
+    /// Where the comment is in the trivia it was found in
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommentKind {
+    /// `// ...` up to the end of the line
+    Line,
+    /// `/* ... */`
+    Block,
+}
+
+impl<T: Tokens> Tokens for Option<T> {
+    fn tokens<'t>(&'t self, tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>) {
+        if let Some(inner) = self {
+            inner.tokens(tokens);
+        }
+    }
+
+    fn tokens_mut<'t>(&'t mut self, tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>) {
+        if let Some(inner) = self {
+            inner.tokens_mut(tokens);
+        }
+    }
+}
+
+impl<T: Tokens> Tokens for Box<T> {
+    fn tokens<'t>(&'t self, tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>) {
+        (**self).tokens(tokens);
+    }
+
+    fn tokens_mut<'t>(&'t mut self, tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>) {
+        (**self).tokens_mut(tokens);
+    }
+}
+
+impl<T: Tokens> Tokens for Vec<T> {
+    fn tokens<'t>(&'t self, tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>) {
+        for item in self {
+            item.tokens(tokens);
+        }
+    }
+
+    fn tokens_mut<'t>(&'t mut self, tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>) {
+        for item in self {
+            item.tokens_mut(tokens);
+        }
+    }
+}
+
+impl<T: Tokens, U: Tokens> Tokens for (T, U) {
+    fn tokens<'t>(&'t self, tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>) {
+        self.0.tokens(tokens);
+        self.1.tokens(tokens);
+    }
+
+    fn tokens_mut<'t>(&'t mut self, tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>) {
+        self.0.tokens_mut(tokens);
+        self.1.tokens_mut(tokens);
+    }
+}
+
+impl<T: Tokens, U: Tokens, V: Tokens> Tokens for (T, U, V) {
+    fn tokens<'t>(&'t self, tokens: &mut Vec<(Span, Option<&'t TokenTrivia>)>) {
+        self.0.tokens(tokens);
+        self.1.tokens(tokens);
+        self.2.tokens(tokens);
+    }
+
+    fn tokens_mut<'t>(&'t mut self, tokens: &mut Vec<(Span, &'t mut Option<Box<TokenTrivia>>)>) {
+        self.0.tokens_mut(tokens);
+        self.1.tokens_mut(tokens);
+        self.2.tokens_mut(tokens);
+    }
+}
+
+/// Trivia kept on the first token of `node`
+fn first(node: &impl Tokens) -> Option<&TokenTrivia> {
+    let mut tokens = Vec::new();
+    node.tokens(&mut tokens);
+    tokens.first()?.1
+}
+
+/// Trivia kept on the last token of `node`
+fn last(node: &impl Tokens) -> Option<&TokenTrivia> {
+    let mut tokens = Vec::new();
+    node.tokens(&mut tokens);
+    tokens.last()?.1
+}
+
+/// Whitespace and comments before `node`
+pub fn leading(node: &impl Tokens) -> &str {
+    first(node).map_or("", |trivia| &trivia.leading)
+}
+
+/// Whitespace and comments after `node`, up to the end of its line
+pub fn trailing(node: &impl Tokens) -> &str {
+    last(node).map_or("", |trivia| &trivia.trailing)
+}
+
+/// Comments before `node`, in source order
+pub fn comments(node: &impl Tokens) -> Vec<Comment<'_>> {
+    comments_in(leading(node))
+}
+
+/// Text of the comments documenting `node`, without comment markers
+///
+/// These are the comments before `node` which are not separated from it by a blank line.
+pub fn doc_comment(node: &impl Tokens) -> Option<String> {
+    let leading = leading(node);
+    let comments = comments_in(leading);
+    let mut lines = Vec::new();
+    for (i, comment) in comments.iter().enumerate() {
+        lines.extend(comment_lines(*comment));
+        let next = comments
+            .get(i + 1)
+            .map_or(leading.len(), |next| next.span.start);
+        if leading[comment.span.end..next].matches('\n').count() > 1 {
+            lines.clear();
+        }
+    }
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// A comment on the same line right after `node`. Ex: `long x; // note`
+pub fn trailing_comment(node: &impl Tokens) -> Option<Comment<'_>> {
+    let trailing = trailing(node);
+    comments_in(trailing)
+        .into_iter()
+        .next()
+        .filter(|comment| !trailing[..comment.span.start].contains('\n'))
+}
+
+/// Prints `node` with the whitespace and comments kept on its tokens, so printing all parsed
+/// definitions and then the trivia after them gives back the source byte for byte
+///
+/// Tokens without trivia, like those of nodes built by hand, are laid out like
+/// [`ToWebIdl::to_webidl`](../print/trait.ToWebIdl.html#method.to_webidl) does.
+pub fn print(node: &impl ToWebIdl) -> String {
+    let mut printer = Printer::preserving();
+    node.write_webidl(&mut printer);
+    printer.finish()
+}
+
+/// Keeps the trivia of `source` on the tokens of `node`, which was parsed from it. Returns the
+/// trivia after the line of the last token, or all of `source` if it has no tokens.
+pub(crate) fn attach<'s>(source: &'s str, node: &mut impl Tokens) -> &'s str {
+    let mut tokens = Vec::new();
+    node.tokens_mut(&mut tokens);
+    let mut start = 0;
+    let mut prev: Option<&mut TokenTrivia> = None;
+    for (span, slot) in tokens {
+        let gap = match source.get(start..span.start) {
+            Some(gap) if !span.is_empty() => gap,
+            // Tokens which were not parsed from the source have no trivia in it
+            _ => continue,
+        };
+        let leading = match prev.take() {
+            Some(prev) => {
+                let (trailing, leading) = split_gap(gap);
+                prev.trailing = trailing.to_string();
+                leading
+            }
+            None => gap,
+        };
+        let trivia = slot.insert(Box::new(TokenTrivia {
+            leading: leading.to_string(),
+            trailing: String::new(),
+        }));
+        prev = Some(trivia);
+        start = span.end;
+    }
+
+    let rest = &source[start..];
+    match prev {
+        Some(prev) => {
+            let (trailing, rest) = split_gap(rest);
+            prev.trailing = trailing.to_string();
+            rest
+        }
+        None => rest,
+    }
+}
+
+/// Splits the trivia between two tokens into the part which follows the first on its line and
+/// the part which leads to the second
+fn split_gap(gap: &str) -> (&str, &str) {
+    let mut offset = 0;
+    while offset < gap.len() {
+        let rest = &gap[offset..];
+        if rest.starts_with('\n') {
+            return gap.split_at(offset + 1);
+        } else if rest.starts_with("//") {
+            offset += rest.find('\n').unwrap_or(rest.len());
+        } else if rest.starts_with("/*") {
+            offset += rest.find("*/").map_or(rest.len(), |end| end + 2);
+        } else {
+            offset += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    (gap, "")
+}
+
+/// Splits `trivia` into its comments
+pub(crate) fn comments_in(trivia: &str) -> Vec<Comment<'_>> {
+    let mut comments = Vec::new();
+    let mut offset = 0;
+    while offset < trivia.len() {
+        let rest = &trivia[offset..];
+        let (kind, len) = if rest.starts_with("//") {
+            (CommentKind::Line, rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("/*") {
+            (
+                CommentKind::Block,
+                rest.find("*/").map_or(rest.len(), |i| i + 2),
+            )
+        } else {
+            offset += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+        comments.push(Comment {
+            kind,
+            text: &rest[..len],
+            span: Span::new(offset, offset + len),
+        });
+        offset += len;
+    }
+    comments
+}
+
+/// Lines of a comment without its markers and the `*` that commonly starts block comment lines
+fn comment_lines(comment: Comment<'_>) -> Vec<&str> {
+    match comment.kind {
+        CommentKind::Line => vec![comment.text[2..].trim()],
+        CommentKind::Block => comment
+            .text
+            .trim_start_matches("/*")
+            .trim_end_matches("*/")
+            .lines()
+            .map(|line| line.trim().trim_start_matches('*').trim())
+            .filter(|line| !line.is_empty())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dictionary::DictionaryMember;
+    use crate::{Definition, Parse};
+
+    fn dictionary_members<'a>(parsed: &'a [Definition<'a>]) -> &'a [DictionaryMember<'a>] {
+        match &parsed[0] {
+            Definition::Dictionary(dictionary) => &dictionary.members.body,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn should_find_doc_comments() {
+        let source = "dictionary D {
+            // Detached from x
+
+            /**
+             * The x
+             * coordinate
+             */
+            long x; // trailing
+            // The y
+            long y;
+        };";
+        let (parsed, _) = crate::parse_with_trivia(source).unwrap();
+        let members = dictionary_members(&parsed);
+
+        assert_eq!(
+            doc_comment(&members[0]).as_deref(),
+            Some("The x\ncoordinate")
+        );
+        assert_eq!(doc_comment(&members[1]).as_deref(), Some("The y"));
+        assert_eq!(comments(&members[0]).len(), 2);
+        assert_eq!(comments(&members[1])[0].text, "// The y");
+        assert_eq!(trailing(&members[0]), " // trailing\n");
+        assert_eq!(trailing_comment(&members[0]).unwrap().text, "// trailing");
+        assert_eq!(trailing_comment(&members[1]), None);
+    }
+
+    #[test]
+    fn should_split_comments() {
+        let comments = comments_in(" // a\n /* b */\t// c");
+        let texts: Vec<_> = comments.iter().map(|comment| comment.text).collect();
+        assert_eq!(texts, vec!["// a", "/* b */", "// c"]);
+        assert_eq!(comments[1].kind, CommentKind::Block);
+        assert_eq!(comments[1].span, Span::new(7, 14));
+    }
+
+    #[test]
+    fn should_split_trivia_between_tokens() {
+        assert_eq!(
+            split_gap(" /* a */ // b\n  // c\n "),
+            (" /* a */ // b\n", "  // c\n ")
+        );
+        assert_eq!(split_gap(" /* a\n */ "), (" /* a\n */ ", ""));
+        assert_eq!(split_gap(" /* b */"), (" /* b */", ""));
+    }
+
+    #[test]
+    fn should_reproduce_source() {
+        let sources = [
+            "",
+            "  // only a comment\n",
+            "/* a */ enum E { \"a\" , /* b */ \"b\" , } ;  \n// end",
+            "interface _interface{attribute long _required ;};\r\n",
+            "[Exposed = ( Window,Worker ) ]\n\ninterface A : B {\n\tconstructor ( ) ;\n};\n",
+            "typedef ( long\n  // c\n  or DOMString ) T;",
+        ];
+        for source in &sources {
+            let (parsed, end) = crate::parse_with_trivia(source).unwrap();
+            assert_eq!(print(&parsed) + end, *source);
+        }
+    }
+
+    #[test]
+    fn should_keep_trivia_on_separators() {
+        let source = "enum E {\n  \"a\", // The a\n  // The b\n  \"b\"\n};";
+        let (parsed, _) = crate::parse_with_trivia(source).unwrap();
+        let values = match &parsed[0] {
+            Definition::Enum(enum_) => &enum_.values.body,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(
+            trailing_comment(&values.separators[0]).unwrap().text,
+            "// The a"
+        );
+        assert_eq!(doc_comment(&values.list[1]).as_deref(), Some("The b"));
+    }
+
+    #[test]
+    fn should_keep_trivia_on_moved_nodes() {
+        let source = "dictionary D {\n  // The x\n  long x; // x\n  long y;\n};\n";
+        let (mut parsed, end) = crate::parse_with_trivia(source).unwrap();
+        if let Definition::Dictionary(dictionary) = &mut parsed[0] {
+            dictionary.members.body.swap(0, 1);
+            dictionary.identifier = crate::common::Identifier::new("Renamed");
+        }
+        let members = dictionary_members(&parsed);
+
+        assert_eq!(doc_comment(&members[1]).as_deref(), Some("The x"));
+        assert_eq!(
+            print(&parsed) + end,
+            "dictionary Renamed {\n  long y;\n  // The x\n  long x; // x\n};\n"
+        );
+    }
+
+    #[test]
+    fn should_lay_out_new_nodes() {
+        let source = "// Some dictionary\ndictionary D {\n  long x; // x\n};\n";
+        let (mut parsed, _) = crate::parse_with_trivia(source).unwrap();
+        let (_, mut added) = Definition::parse("typedef long L;").unwrap();
+        let mut tokens = Vec::new();
+        added.tokens_mut(&mut tokens);
+        *tokens[0].1 = Some(Box::new(TokenTrivia {
+            leading: "\n/* Added */ ".to_string(),
+            trailing: String::new(),
+        }));
+        parsed.push(added);
+
+        assert_eq!(doc_comment(&parsed[1]).as_deref(), Some("Added"));
+        assert_eq!(
+            print(&parsed),
+            "// Some dictionary\ndictionary D {\n  long x; // x\n};\n\n/* Added */ typedef long L;\n"
+        );
+    }
+}
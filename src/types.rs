@@ -25,6 +25,7 @@ ast_types! {
         Boolean(MayBeNull<term!(boolean)>),
         Byte(MayBeNull<term!(byte)>),
         Octet(MayBeNull<term!(octet)>),
+        Bigint(MayBeNull<term!(bigint)>),
         ByteString(MayBeNull<term!(ByteString)>),
         DOMString(MayBeNull<term!(DOMString)>),
         USVString(MayBeNull<term!(USVString)>),
@@ -41,11 +42,15 @@ ast_types! {
         Uint16Array(MayBeNull<term!(Uint16Array)>),
         Uint32Array(MayBeNull<term!(Uint32Array)>),
         Uint8ClampedArray(MayBeNull<term!(Uint8ClampedArray)>),
+        Float16Array(MayBeNull<term!(Float16Array)>),
         Float32Array(MayBeNull<term!(Float32Array)>),
         Float64Array(MayBeNull<term!(Float64Array)>),
+        BigInt64Array(MayBeNull<term!(BigInt64Array)>),
+        BigUint64Array(MayBeNull<term!(BigUint64Array)>),
         ArrayBufferView(MayBeNull<term!(ArrayBufferView)>),
         BufferSource(MayBeNull<term!(BufferSource)>),
         FrozenArrayType(MayBeNull<FrozenArrayType<'a>>),
+        ObservableArrayType(MayBeNull<ObservableArrayType<'a>>),
         RecordType(MayBeNull<RecordType<'a>>),
         Identifier(MayBeNull<Identifier<'a>>),
     }
@@ -62,10 +67,15 @@ ast_types! {
         generics: Generics<Box<Type<'a>>>,
     }
 
+    /// Parses `ObservableArray<Type>`
+    struct ObservableArrayType<'a> {
+        observable_array: term!(ObservableArray),
+        generics: Generics<Box<Type<'a>>>,
+    }
+
     /// Parses a nullable type. Ex: `object | object??`
     ///
     /// `??` means an actual ? not an optional requirement
-    #[derive(Copy)]
     struct MayBeNull<T> where [T: Parse<'a>] {
         type_: T,
         q_mark: Option<term::QMark>,
@@ -78,22 +88,18 @@ ast_types! {
     }
 
     /// Parses `unsigned? short|long|(long long)`
-    #[derive(Copy)]
     enum IntegerType {
         /// Parses `unsigned? long long`
-        #[derive(Copy)]
         LongLong(struct LongLongType {
             unsigned: Option<term!(unsigned)>,
             long_long: (term!(long), term!(long)),
         }),
         /// Parses `unsigned? long`
-        #[derive(Copy)]
         Long(struct LongType {
             unsigned: Option<term!(unsigned)>,
             long: term!(long),
         }),
         /// Parses `unsigned? short`
-        #[derive(Copy)]
         Short(struct ShortType {
             unsigned: Option<term!(unsigned)>,
             short: term!(short),
@@ -101,16 +107,13 @@ ast_types! {
     }
 
     /// Parses `unrestricted? float|double`
-    #[derive(Copy)]
     enum FloatingPointType {
         /// Parses `unrestricted? float`
-        #[derive(Copy)]
         Float(struct FloatType {
             unrestricted: Option<term!(unrestricted)>,
             float: term!(float),
         }),
         /// Parses `unrestricted? double`
-        #[derive(Copy)]
         Double(struct DoubleType {
             unrestricted: Option<term!(unrestricted)>,
             double: term!(double),
@@ -144,6 +147,7 @@ ast_types! {
         Boolean(MayBeNull<term!(boolean)>),
         Byte(MayBeNull<term!(byte)>),
         Octet(MayBeNull<term!(octet)>),
+        Bigint(MayBeNull<term!(bigint)>),
         Identifier(MayBeNull<Identifier<'a>>),
     }
 
@@ -166,6 +170,43 @@ ast_types! {
     }
 }
 
+/// Serialized as `{ "type": ..., "nullable": ... }`
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for MayBeNull<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("MayBeNull", 2)?;
+        state.serialize_field("type", &self.type_)?;
+        state.serialize_field("nullable", &self.q_mark.is_some())?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for MayBeNull<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "MayBeNull")]
+        struct Fields<T> {
+            #[serde(rename = "type")]
+            type_: T,
+            nullable: bool,
+        }
+
+        let fields: Fields<T> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(MayBeNull {
+            type_: fields.type_,
+            q_mark: if fields.nullable {
+                Some(term!(?))
+            } else {
+                None
+            },
+            span: crate::span::Span::default(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -196,6 +237,7 @@ mod test {
             Boolean == "boolean",
             Byte == "byte",
             Octet == "octet",
+            Bigint == "bigint",
             Identifier == "name",
         }
     );
@@ -208,6 +250,7 @@ mod test {
             Boolean == "boolean",
             Byte == "byte",
             Octet == "octet",
+            Bigint == "bigint",
             ByteString == "ByteString",
             DOMString == "DOMString",
             USVString == "USVString",
@@ -224,11 +267,15 @@ mod test {
             Uint16Array == "Uint16Array",
             Uint32Array == "Uint32Array",
             Uint8ClampedArray == "Uint8ClampedArray",
+            Float16Array == "Float16Array",
             Float32Array == "Float32Array",
             Float64Array == "Float64Array",
+            BigInt64Array == "BigInt64Array",
+            BigUint64Array == "BigUint64Array",
             ArrayBufferView == "ArrayBufferView",
             BufferSource == "BufferSource",
             FrozenArrayType == "FrozenArray<short>",
+            ObservableArrayType == "ObservableArray<short>",
             RecordType == "record<DOMString, short>",
             Identifier == "mango"
         }
@@ -259,6 +306,55 @@ mod test {
         RecordType;
     });
 
+    test!(should_parse_record_type_bigint { "record<DOMString, bigint>" =>
+        "";
+        RecordType;
+    });
+
+    test!(should_parse_nullable_bigint { "bigint?" =>
+        "";
+        MayBeNull<term!(bigint)>;
+        q_mark.is_some();
+    });
+
+    #[test]
+    fn should_parse_bigint_record_key_type() {
+        use crate::types::{NonAnyType, RecordKeyType};
+        let (rem, parsed) = RecordKeyType::parse("bigint").unwrap();
+        assert_eq!(rem, "");
+        assert!(matches!(
+            parsed,
+            RecordKeyType::NonAny(NonAnyType::Bigint(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_bigint_union_type() {
+        use crate::types::{NonAnyType, UnionMemberType};
+        let (rem, parsed) =
+            UnionType::parse("(bigint or BigInt64Array or BigUint64Array?)").unwrap();
+        assert_eq!(rem, "");
+        let list = &parsed.body.list;
+        assert_eq!(list.len(), 3);
+        assert!(matches!(
+            list[0],
+            UnionMemberType::Single(AttributedNonAnyType {
+                type_: NonAnyType::Bigint(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            list[2],
+            UnionMemberType::Single(AttributedNonAnyType {
+                type_: NonAnyType::BigUint64Array(MayBeNull {
+                    q_mark: Some(_),
+                    ..
+                }),
+                ..
+            })
+        ));
+    }
+
     test!(should_parse_double_type { "double" =>
         "";
         DoubleType;
@@ -309,6 +405,11 @@ mod test {
         FrozenArrayType;
     });
 
+    test!(should_parse_observable_array_type { "ObservableArray<CSSStyleSheet>" =>
+        "";
+        ObservableArrayType;
+    });
+
     test!(should_parse_sequence_type { "sequence<short>" =>
         "";
         SequenceType;
@@ -334,6 +435,19 @@ mod test {
         attributes.is_some();
     });
 
+    #[test]
+    fn should_parse_allow_shared_buffer_source_as_identifier() {
+        use crate::types::NonAnyType;
+        let (rem, parsed) = NonAnyType::parse("AllowSharedBufferSource").unwrap();
+        assert_eq!(rem, "");
+        match parsed {
+            NonAnyType::Identifier(MayBeNull { type_, .. }) => {
+                assert_eq!(type_.0, "AllowSharedBufferSource")
+            }
+            _ => panic!("expected an identifier"),
+        }
+    }
+
     test!(should_parse_type_as_identifier { "DOMStringMap" =>
         // if type is not parsed as identifier, it is parsed as `DOMString` and 'Map' is left
         "";
@@ -0,0 +1,841 @@
+//! Checking definitions against the static rules of the WebIDL specification
+//!
+//! The parser accepts some definitions which the specification rules out with an "it is an
+//! error if…" sentence. [`validate`](fn.validate.html) reports these as
+//! [`Diagnostic`](../diagnostic/struct.Diagnostic.html)s, each carrying the identifier of the
+//! rule it breaks:
+//!
+//! | Rule | Reported for |
+//! |------|--------------|
+//! | `required-member-default` | a `required` dictionary member with a default value |
+//! | `required-after-optional` | a required argument following an optional one |
+//! | `variadic-not-last` | a variadic argument which is not the last argument |
+//! | `dictionary-argument-optional` | a required argument, or an optional one without default, whose dictionary type has no required members |
+//! | `record-key-type` | a `record` key other than `DOMString`, `USVString` or `ByteString` |
+//! | `nullable-nullable` | a nullable typedef or `any` made nullable again |
+//! | `nullable-union-members` | a union with more than one nullable member, or a nullable union with a nullable member |
+//! | `nullable-dictionary` | a nullable union containing a dictionary, or a nullable dictionary as argument or dictionary member |
+//! | `attribute-type` | an attribute of sequence, record or dictionary type |
+//! | `duplicate-member` | a constant, attribute or dictionary member name used twice |
+//! | `duplicate-enum-value` | an enum value listed twice |
+//! | `multiple-stringifiers` | a second stringifier of an interface |
+//! | `multiple-iterable-declarations` | a second `iterable`, `maplike` or `setlike`, or a second `async iterable`, of an interface |
+//! | `callback-interface-operation` | a callback interface without exactly one regular operation |
+//! | `inheritance-cycle` | an interface or dictionary inheriting from itself |
+//! | `typedef-cycle` | typedefs referring to themselves |
+//! | `indistinguishable-overloads` | overloads which cannot be told apart, see the [`overload`](../overload/index.html) module |
+//!
+//! Members from partial definitions and included mixins count towards the interface, dictionary
+//! or namespace they extend. Names which are not defined are left to
+//! [`resolve`](../resolve/index.html).
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::validate::validate;
+//!
+//! let parsed = weedle::parse("
+//!     dictionary Options { required boolean fast = true; };
+//!     interface Runner { undefined run(optional long times, Options options); };
+//! ").unwrap();
+//!
+//! let rules: Vec<_> = validate(vec![&parsed[..]]).iter().map(|d| d.rule).collect();
+//! assert_eq!(rules, ["required-member-default", "required-after-optional"]);
+//! ```
+
+use std::collections::HashMap;
+
+use crate::argument::{Argument, ArgumentList, SingleArgument};
+use crate::diagnostic::Diagnostic;
+use crate::dictionary::DictionaryMember;
+use crate::interface::{
+    AsyncIterableInterfaceMember, AttributeInterfaceMember, ConstructorInterfaceMember,
+    InterfaceMember, OperationInterfaceMember, StringifierOrInheritOrStatic, StringifierOrStatic,
+};
+use crate::mixin::{AttributeMixinMember, MixinMember, OperationMixinMember};
+use crate::namespace::{AttributeNamespaceMember, NamespaceMember, OperationNamespaceMember};
+use crate::normalize::{Builtin, NormalType, Normalizer};
+use crate::overload::Overloads;
+use crate::semantic::{InterfaceMemberRef, Model};
+use crate::span::{Span, Spanned};
+use crate::types::{AttributedType, NonAnyType, RecordKeyType, Type};
+use crate::visit::{Accept, Visit, Walk};
+use crate::{CallbackDefinition, Definition, EnumDefinition};
+
+/// Checks the definitions of several files, which diagnostics refer to by their index
+///
+/// Diagnostics are ordered by file and position.
+pub fn validate<'a, I: IntoIterator<Item = &'a [Definition<'a>]>>(files: I) -> Vec<Diagnostic> {
+    let files: Vec<_> = files.into_iter().collect();
+    let model = Model::from_files(files.iter().copied());
+    let normalizer = Normalizer::new(&model);
+    let mut checker = Checker {
+        model: &model,
+        normalizer: &normalizer,
+        file: 0,
+        diagnostics: Vec::new(),
+    };
+
+    for (file, definitions) in files.iter().enumerate() {
+        checker.file = file;
+        for definition in definitions.iter() {
+            definition.accept(&mut checker);
+        }
+    }
+    checker.check_model();
+
+    let mut diagnostics = checker.diagnostics;
+    diagnostics.extend(Overloads::new(&model).check());
+    diagnostics.sort_by_key(|d| (d.file, d.span.start));
+    diagnostics
+}
+
+/// Checks single definitions with a visitor, and merged definitions through the model
+struct Checker<'m, 'a> {
+    model: &'m Model<'a>,
+    normalizer: &'m Normalizer<'a>,
+    file: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'m, 'a> Checker<'m, 'a> {
+    fn error(&mut self, rule: &'static str, message: String, span: Span) {
+        let file = self.file;
+        self.diagnostics
+            .push(Diagnostic::error(rule, message, file, span));
+    }
+
+    /// Normalizes a type, leaving typedef cycles to `check_model`
+    fn normalize(&self, ty: &'a Type<'a>) -> Option<NormalType<'a>> {
+        self.normalizer.normalize(ty).ok()
+    }
+
+    fn is_dictionary(&self, ty: &NormalType<'a>) -> bool {
+        match ty {
+            NormalType::Named(name) => self.model.dictionaries.contains_key(name),
+            _ => false,
+        }
+    }
+
+    /// Nullable unions containing dictionaries are reported where the union is made nullable
+    fn is_nullable_dictionary(&self, ty: &NormalType<'a>) -> bool {
+        match ty {
+            NormalType::Nullable(inner) => self.is_dictionary(inner),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the dictionary or one it inherits from has a required member
+    fn has_required_members(&self, name: &str) -> bool {
+        let mut seen = Vec::new();
+        let mut next = Some(name);
+        while let Some(name) = next.take() {
+            let dictionary = match self.model.dictionaries.get(name) {
+                Some(dictionary) if !seen.contains(&name) => dictionary,
+                _ => break,
+            };
+            seen.push(name);
+            if dictionary
+                .members
+                .iter()
+                .any(|m| m.member.required.is_some())
+            {
+                return true;
+            }
+            next = dictionary
+                .definition
+                .as_ref()
+                .and_then(|d| d.node.inheritance.as_ref())
+                .map(|inheritance| &*inheritance.identifier.0);
+        }
+        false
+    }
+
+    fn check_arguments(&mut self, args: &'a ArgumentList<'a>) {
+        let args = &args.list;
+        let mut optional = false;
+        for (i, arg) in args.iter().enumerate() {
+            match arg {
+                Argument::Single(arg) if arg.optional.is_some() => optional = true,
+                Argument::Single(arg) if optional => self.error(
+                    "required-after-optional",
+                    format!(
+                        "`{}` is required, but follows an optional argument",
+                        arg.identifier.0
+                    ),
+                    arg.span(),
+                ),
+                Argument::Single(_) => {}
+                Argument::Variadic(arg) if i + 1 < args.len() => self.error(
+                    "variadic-not-last",
+                    format!(
+                        "variadic argument `{}` must be the last argument",
+                        arg.identifier.0
+                    ),
+                    arg.span(),
+                ),
+                Argument::Variadic(_) => {}
+            }
+            if let Argument::Single(single) = arg {
+                let rest_optional = args[i + 1..].iter().all(|arg| match arg {
+                    Argument::Single(arg) => arg.optional.is_some(),
+                    Argument::Variadic(_) => true,
+                });
+                self.check_dictionary_argument(single, rest_optional);
+            }
+        }
+    }
+
+    fn check_dictionary_argument(&mut self, arg: &'a SingleArgument<'a>, rest_optional: bool) {
+        let normal = match self.normalize(&arg.type_.type_) {
+            Some(normal) => normal,
+            None => return,
+        };
+        if self.is_nullable_dictionary(&normal) {
+            self.error(
+                "nullable-dictionary",
+                format!(
+                    "argument `{}` cannot be a nullable dictionary",
+                    arg.identifier.0
+                ),
+                arg.type_.span(),
+            );
+        }
+        if !rest_optional || (arg.optional.is_some() && arg.default.is_some()) {
+            return;
+        }
+        let dictionary = normal.members().iter().find_map(|m| match m {
+            NormalType::Named(name) if self.model.dictionaries.contains_key(name) => Some(*name),
+            _ => None,
+        });
+        if let Some(dictionary) = dictionary {
+            if !self.has_required_members(dictionary) {
+                self.error(
+                    "dictionary-argument-optional",
+                    format!(
+                        "`{}` must be optional with a default value, as `{}` has no required members",
+                        arg.identifier.0, dictionary
+                    ),
+                    arg.span(),
+                );
+            }
+        }
+    }
+
+    fn check_attribute_type(&mut self, ty: &'a AttributedType<'a>) {
+        let normal = match self.normalize(&ty.type_) {
+            Some(normal) => normal,
+            None => return,
+        };
+        let forbidden = normal.members().iter().any(|m| {
+            matches!(m, NormalType::Sequence(_) | NormalType::Record(..)) || self.is_dictionary(m)
+        });
+        if forbidden {
+            self.error(
+                "attribute-type",
+                format!(
+                    "attributes cannot be of sequence, record or dictionary type, found `{}`",
+                    normal
+                ),
+                ty.span(),
+            );
+        }
+    }
+
+    /// Reports every name after the first, with a note pointing at the first
+    fn check_duplicates(
+        &mut self,
+        rule: &'static str,
+        what: &str,
+        names: impl IntoIterator<Item = (&'a str, usize, Span)>,
+    ) {
+        let mut first: HashMap<&str, (usize, Span)> = HashMap::new();
+        for (name, file, span) in names {
+            match first.get(name) {
+                Some(&(first_file, first_span)) => self.diagnostics.push(
+                    Diagnostic::error(
+                        rule,
+                        format!("{} `{}` is declared more than once", what, name),
+                        file,
+                        span,
+                    )
+                    .with_note(
+                        format!("`{}` is first declared here", name),
+                        first_file,
+                        first_span,
+                    ),
+                ),
+                None => {
+                    first.insert(name, (file, span));
+                }
+            }
+        }
+    }
+
+    /// Reports every member after the first one of a kind, with a note pointing at the first
+    fn check_at_most_one(
+        &mut self,
+        rule: &'static str,
+        message: String,
+        members: impl IntoIterator<Item = (usize, Span)>,
+    ) {
+        let mut members = members.into_iter();
+        let (first_file, first_span) = match members.next() {
+            Some(first) => first,
+            None => return,
+        };
+        for (file, span) in members {
+            self.diagnostics.push(
+                Diagnostic::error(rule, message.clone(), file, span).with_note(
+                    "the first one is declared here".to_string(),
+                    first_file,
+                    first_span,
+                ),
+            );
+        }
+    }
+
+    fn check_model(&mut self) {
+        let model = self.model;
+
+        for interface in model.interfaces.values() {
+            let located = |m: &crate::semantic::Member<'a, InterfaceMemberRef<'a>>| {
+                (m.origin.file, m.member.span())
+            };
+            self.check_at_most_one(
+                "multiple-stringifiers",
+                format!("`{}` has more than one stringifier", interface.name),
+                interface
+                    .members
+                    .iter()
+                    .filter(|m| is_stringifier(m.member))
+                    .map(located),
+            );
+            self.check_at_most_one(
+                "multiple-iterable-declarations",
+                format!(
+                    "`{}` has more than one iterable, maplike or setlike declaration",
+                    interface.name
+                ),
+                interface
+                    .members
+                    .iter()
+                    .filter(|m| {
+                        matches!(
+                            m.member,
+                            InterfaceMemberRef::Interface(
+                                InterfaceMember::Iterable(_)
+                                    | InterfaceMember::Maplike(_)
+                                    | InterfaceMember::Setlike(_)
+                            )
+                        )
+                    })
+                    .map(located),
+            );
+            self.check_at_most_one(
+                "multiple-iterable-declarations",
+                format!(
+                    "`{}` has more than one async iterable declaration",
+                    interface.name
+                ),
+                interface
+                    .members
+                    .iter()
+                    .filter(|m| {
+                        matches!(
+                            m.member,
+                            InterfaceMemberRef::Interface(InterfaceMember::AsyncIterable(_))
+                        )
+                    })
+                    .map(located),
+            );
+
+            // Operations can be overloaded, but share no name with constants or attributes
+            let mut names = Vec::new();
+            let mut operations = Vec::new();
+            for member in &interface.members {
+                let name = match member.member.identifier() {
+                    Some(name) => name,
+                    None => continue,
+                };
+                let entry = (name, member.origin.file, member.member.span());
+                match member.member {
+                    InterfaceMemberRef::Interface(InterfaceMember::Operation(_))
+                    | InterfaceMemberRef::Mixin(MixinMember::Operation(_)) => {
+                        if !operations.iter().any(|&(n, _, _)| n == name) {
+                            operations.push(entry);
+                        }
+                    }
+                    _ => names.push(entry),
+                }
+            }
+            names.extend(operations);
+            self.check_duplicates("duplicate-member", "member", names);
+        }
+
+        for dictionary in model.dictionaries.values() {
+            self.check_duplicates(
+                "duplicate-member",
+                "dictionary member",
+                dictionary.members.iter().map(|m| {
+                    (
+                        &*m.member.identifier.0,
+                        m.origin.file,
+                        m.member.identifier.span(),
+                    )
+                }),
+            );
+        }
+
+        for namespace in model.namespaces.values() {
+            self.check_duplicates(
+                "duplicate-member",
+                "member",
+                namespace.members.iter().filter_map(|m| match m.member {
+                    NamespaceMember::Const(c) => {
+                        Some((&*c.identifier.0, m.origin.file, c.identifier.span()))
+                    }
+                    NamespaceMember::Attribute(a) => {
+                        Some((&*a.identifier.0, m.origin.file, a.identifier.span()))
+                    }
+                    NamespaceMember::Operation(_) => None,
+                }),
+            );
+        }
+
+        for callback in model.callback_interfaces.values() {
+            let regular = callback
+                .node
+                .members
+                .body
+                .iter()
+                .filter(|m| match m {
+                    InterfaceMember::Operation(op) => {
+                        op.identifier.is_some() && op.special.is_none() && op.modifier.is_none()
+                    }
+                    _ => false,
+                })
+                .count();
+            if regular != 1 {
+                self.diagnostics.push(Diagnostic::error(
+                    "callback-interface-operation",
+                    format!(
+                        "callback interface `{}` must have exactly one regular operation, found {}",
+                        callback.node.identifier.0, regular
+                    ),
+                    callback.file,
+                    callback.node.identifier.span(),
+                ));
+            }
+        }
+
+        let interfaces = model.interfaces.values().filter_map(|i| {
+            let definition = i.definition.as_ref()?;
+            let parent = definition
+                .node
+                .inheritance
+                .as_ref()
+                .map(|i| &*i.identifier.0);
+            Some((
+                i.name,
+                parent,
+                definition.file,
+                definition.node.identifier.span(),
+            ))
+        });
+        let dictionaries = model.dictionaries.values().filter_map(|d| {
+            let definition = d.definition.as_ref()?;
+            let parent = definition
+                .node
+                .inheritance
+                .as_ref()
+                .map(|i| &*i.identifier.0);
+            Some((
+                d.name,
+                parent,
+                definition.file,
+                definition.node.identifier.span(),
+            ))
+        });
+        let parents: Vec<_> = interfaces.collect();
+        self.check_inheritance(&parents);
+        let parents: Vec<_> = dictionaries.collect();
+        self.check_inheritance(&parents);
+
+        for cycle in self.normalizer.cycles() {
+            let typedef = &model.typedefs[cycle.names[0]];
+            let chain: Vec<_> = cycle
+                .names
+                .iter()
+                .chain(cycle.names.first())
+                .map(|name| format!("`{}`", name))
+                .collect();
+            self.diagnostics.push(Diagnostic::error(
+                "typedef-cycle",
+                format!(
+                    "typedef `{}` refers to itself: {}",
+                    cycle.names[0],
+                    chain.join(" -> ")
+                ),
+                typedef.file,
+                typedef.node.identifier.span(),
+            ));
+        }
+    }
+
+    /// Reports each definition which inherits from itself
+    fn check_inheritance(&mut self, definitions: &[(&'a str, Option<&'a str>, usize, Span)]) {
+        let parents: HashMap<_, _> = definitions
+            .iter()
+            .map(|&(name, parent, _, _)| (name, parent))
+            .collect();
+        for &(name, _, file, span) in definitions {
+            let mut chain = vec![name];
+            let mut current = name;
+            while let Some(&Some(parent)) = parents.get(current) {
+                if parent == name {
+                    chain.push(parent);
+                    let chain: Vec<_> = chain.iter().map(|n| format!("`{}`", n)).collect();
+                    self.diagnostics.push(Diagnostic::error(
+                        "inheritance-cycle",
+                        format!("`{}` inherits from itself: {}", name, chain.join(" -> ")),
+                        file,
+                        span,
+                    ));
+                    break;
+                }
+                if chain.contains(&parent) {
+                    break;
+                }
+                chain.push(parent);
+                current = parent;
+            }
+        }
+    }
+}
+
+fn is_stringifier(member: InterfaceMemberRef) -> bool {
+    match member {
+        InterfaceMemberRef::Interface(member) => match member {
+            InterfaceMember::Attribute(m) => {
+                matches!(
+                    m.modifier,
+                    Some(StringifierOrInheritOrStatic::Stringifier(_))
+                )
+            }
+            InterfaceMember::Operation(m) => {
+                matches!(m.modifier, Some(StringifierOrStatic::Stringifier(_)))
+            }
+            InterfaceMember::Stringifier(_) => true,
+            _ => false,
+        },
+        InterfaceMemberRef::Mixin(member) => match member {
+            MixinMember::Attribute(m) => m.stringifier.is_some(),
+            MixinMember::Operation(m) => m.stringifier.is_some(),
+            MixinMember::Stringifier(_) => true,
+            MixinMember::Const(_) => false,
+        },
+    }
+}
+
+impl<'m, 'a> Visit<'a> for Checker<'m, 'a> {
+    fn visit_enum_definition(&mut self, node: &'a EnumDefinition<'a>) {
+        let file = self.file;
+        let values = node
+            .values
+            .body
+            .list
+            .iter()
+            .map(|v| (&*v.0, file, v.span()));
+        self.check_duplicates("duplicate-enum-value", "enum value", values);
+        node.walk(self);
+    }
+
+    fn visit_callback_definition(&mut self, node: &'a CallbackDefinition<'a>) {
+        self.check_arguments(&node.arguments.body);
+        node.walk(self);
+    }
+
+    fn visit_constructor_interface_member(&mut self, node: &'a ConstructorInterfaceMember<'a>) {
+        self.check_arguments(&node.args.body);
+        node.walk(self);
+    }
+
+    fn visit_operation_interface_member(&mut self, node: &'a OperationInterfaceMember<'a>) {
+        self.check_arguments(&node.args.body);
+        node.walk(self);
+    }
+
+    fn visit_operation_mixin_member(&mut self, node: &'a OperationMixinMember<'a>) {
+        self.check_arguments(&node.args.body);
+        node.walk(self);
+    }
+
+    fn visit_operation_namespace_member(&mut self, node: &'a OperationNamespaceMember<'a>) {
+        self.check_arguments(&node.args.body);
+        node.walk(self);
+    }
+
+    fn visit_async_iterable_interface_member(
+        &mut self,
+        node: &'a AsyncIterableInterfaceMember<'a>,
+    ) {
+        let args = match node {
+            AsyncIterableInterfaceMember::Single(m) => &m.args,
+            AsyncIterableInterfaceMember::Double(m) => &m.args,
+        };
+        if let Some(args) = args {
+            self.check_arguments(&args.body);
+        }
+        node.walk(self);
+    }
+
+    fn visit_attribute_interface_member(&mut self, node: &'a AttributeInterfaceMember<'a>) {
+        self.check_attribute_type(&node.type_);
+        node.walk(self);
+    }
+
+    fn visit_attribute_mixin_member(&mut self, node: &'a AttributeMixinMember<'a>) {
+        self.check_attribute_type(&node.type_);
+        node.walk(self);
+    }
+
+    fn visit_attribute_namespace_member(&mut self, node: &'a AttributeNamespaceMember<'a>) {
+        self.check_attribute_type(&node.type_);
+        node.walk(self);
+    }
+
+    fn visit_dictionary_member(&mut self, node: &'a DictionaryMember<'a>) {
+        if let (Some(_), Some(default)) = (&node.required, &node.default) {
+            self.error(
+                "required-member-default",
+                format!(
+                    "required member `{}` cannot have a default value",
+                    node.identifier.0
+                ),
+                default.span(),
+            );
+        }
+        if let Some(normal) = self.normalize(&node.type_) {
+            if self.is_nullable_dictionary(&normal) {
+                self.error(
+                    "nullable-dictionary",
+                    format!(
+                        "dictionary member `{}` cannot be a nullable dictionary",
+                        node.identifier.0
+                    ),
+                    node.type_.span(),
+                );
+            }
+        }
+        node.walk(self);
+    }
+
+    fn visit_type(&mut self, node: &'a Type<'a>) {
+        if let Type::Union(union) = node {
+            if let Ok(count) = self.normalizer.nullable_member_types(&union.type_) {
+                if count > 1 || (count > 0 && union.q_mark.is_some()) {
+                    self.error(
+                        "nullable-union-members",
+                        "a union can be nullable only once, by a member or by itself".to_string(),
+                        node.span(),
+                    );
+                }
+            }
+            if let Some(normal) = self.normalize(node) {
+                if normal.is_nullable() && normal.members().iter().any(|m| self.is_dictionary(m)) {
+                    self.error(
+                        "nullable-dictionary",
+                        format!("nullable union `{}` cannot contain a dictionary", normal),
+                        node.span(),
+                    );
+                }
+            }
+        }
+        node.walk(self);
+    }
+
+    fn visit_non_any_type(&mut self, node: &'a NonAnyType<'a>) {
+        if let NonAnyType::Identifier(identifier) = node {
+            let name = &*identifier.type_.0;
+            if let (Some(_), Some(Ok(expanded))) =
+                (&identifier.q_mark, self.normalizer.expand_typedef(name))
+            {
+                if expanded.is_nullable() || expanded == NormalType::Any {
+                    self.error(
+                        "nullable-nullable",
+                        format!(
+                            "`{}` is `{}`, which cannot be made nullable",
+                            name, expanded
+                        ),
+                        node.span(),
+                    );
+                } else if let NormalType::Union(members) = &expanded {
+                    if members.iter().any(|m| self.is_dictionary(m)) {
+                        self.error(
+                            "nullable-dictionary",
+                            format!("nullable union `{}` cannot contain a dictionary", expanded),
+                            node.span(),
+                        );
+                    }
+                }
+            }
+        }
+        node.walk(self);
+    }
+
+    fn visit_record_key_type(&mut self, node: &'a RecordKeyType<'a>) {
+        if let RecordKeyType::NonAny(ty) = node {
+            let valid = matches!(
+                self.normalizer.normalize_non_any(ty),
+                Ok(NormalType::Builtin(
+                    Builtin::DOMString | Builtin::USVString | Builtin::ByteString
+                )) | Err(_)
+            );
+            if !valid {
+                self.error(
+                    "record-key-type",
+                    "record keys must be DOMString, USVString or ByteString".to_string(),
+                    node.span(),
+                );
+            }
+        }
+        node.walk(self);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rules(source: &str) -> Vec<&'static str> {
+        let parsed = crate::parse(source).unwrap();
+        validate(vec![&parsed[..]]).iter().map(|d| d.rule).collect()
+    }
+
+    #[test]
+    fn should_accept_valid_definitions() {
+        let source = "
+            typedef (DOMString or long)? Key;
+            dictionary Init { required long size; boolean fast = false; };
+            dictionary Options { boolean fast = false; };
+            interface Store {
+                constructor(Init init, optional Options options = {});
+                stringifier attribute DOMString name;
+                attribute Key key;
+                iterable<Key>;
+                undefined put(record<DOMString, any> entries, long... keys);
+                undefined put(long key);
+            };
+            callback interface Listener { undefined handle(); const long MAX = 1; };
+        ";
+        assert_eq!(rules(source), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn should_check_arguments() {
+        let source = "
+            dictionary Options { boolean fast = false; };
+            callback Callback = undefined (optional long a, long b);
+            namespace Tasks {
+                undefined run(Options options);
+                undefined wait(optional Options options);
+                undefined stop(Options? options, long force);
+            };
+        ";
+        assert_eq!(
+            rules(source),
+            [
+                "required-after-optional",
+                "dictionary-argument-optional",
+                "dictionary-argument-optional",
+                "nullable-dictionary",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_check_types() {
+        let source = "
+            dictionary Options { required long size; };
+            typedef long? Maybe;
+            typedef (Options or long) Choice;
+            interface Store {
+                attribute sequence<long> list;
+                attribute Choice? choice;
+                undefined put(record<long, long> a, Maybe? b, (Options or Maybe) c);
+                undefined get((long? or boolean)? d);
+            };
+        ";
+        assert_eq!(
+            rules(source),
+            [
+                "attribute-type",
+                "attribute-type",
+                "nullable-dictionary",
+                "record-key-type",
+                "nullable-nullable",
+                "nullable-dictionary",
+                "nullable-union-members",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_check_merged_members() {
+        let source = "
+            interface Store { stringifier; iterable<long>; attribute long size; };
+            interface mixin Sized { attribute long size; stringifier DOMString name(); };
+            partial interface Store { setlike<long>; };
+            Store includes Sized;
+            dictionary D { long a; required long a; };
+            enum E { \"x\", \"y\", \"x\" };
+            callback interface Listener { undefined a(); undefined b(); };
+        ";
+        let parsed = crate::parse(source).unwrap();
+        let diagnostics = validate(vec![&parsed[..]]);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "error[duplicate-member]: member `size` is declared more than once",
+                "error[multiple-stringifiers]: `Store` has more than one stringifier",
+                "error[multiple-iterable-declarations]: `Store` has more than one iterable, \
+                 maplike or setlike declaration",
+                "error[duplicate-member]: dictionary member `a` is declared more than once",
+                "error[duplicate-enum-value]: enum value `x` is declared more than once",
+                "error[callback-interface-operation]: callback interface `Listener` must have \
+                 exactly one regular operation, found 2",
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].notes[0].message,
+            "`size` is first declared here"
+        );
+    }
+
+    #[test]
+    fn should_detect_cycles() {
+        let source = "
+            interface A : B {};
+            interface B : A {};
+            dictionary C : C {};
+            typedef sequence<E> D;
+            typedef D? E;
+        ";
+        let parsed = crate::parse(source).unwrap();
+        let messages: Vec<_> = validate(vec![&parsed[..]])
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "error[inheritance-cycle]: `A` inherits from itself: `A` -> `B` -> `A`",
+                "error[inheritance-cycle]: `B` inherits from itself: `B` -> `A` -> `B`",
+                "error[inheritance-cycle]: `C` inherits from itself: `C` -> `C`",
+                "error[typedef-cycle]: typedef `D` refers to itself: `D` -> `E` -> `D`",
+            ]
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Traversing parsed definitions
+//!
+//! [`Visit`](trait.Visit.html) has a method for every kind of node, which by default visits the
+//! children of the node. Override the methods of the nodes of interest and call
+//! [`Walk::walk`](trait.Walk.html#tymethod.walk) from them to keep going deeper.
+//! [`VisitMut`](trait.VisitMut.html) does the same with mutable access.
+//!
+//! Traversal starts with [`Accept::accept`](trait.Accept.html#tymethod.accept), which calls
+//! the method of the visitor matching the node.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::common::Identifier;
+//! use weedle::interface::OperationInterfaceMember;
+//! use weedle::visit::{Accept, Visit, VisitMut, Walk};
+//!
+//! let mut parsed = weedle::parse("
+//!     interface Storage {
+//!         getter DOMString? getItem(DOMString key);
+//!         setter undefined setItem(DOMString key, DOMString value);
+//!     };
+//! ").unwrap();
+//!
+//! struct Operations<'a>(Vec<&'a str>);
+//!
+//! impl<'a> Visit<'a> for Operations<'a> {
+//!     fn visit_operation_interface_member(&mut self, node: &'a OperationInterfaceMember<'a>) {
+//!         if let Some(identifier) = &node.identifier {
+//!             self.0.push(&identifier.0);
+//!         }
+//!         node.walk(self);
+//!     }
+//! }
+//!
+//! let mut operations = Operations(Vec::new());
+//! parsed.accept(&mut operations);
+//! assert_eq!(operations.0, ["getItem", "setItem"]);
+//!
+//! struct Rename;
+//!
+//! impl<'a> VisitMut<'a> for Rename {
+//!     fn visit_identifier(&mut self, node: &mut Identifier<'a>) {
+//!         if node.0 == "key" {
+//!             node.0 = "name".into();
+//!         }
+//!     }
+//! }
+//!
+//! parsed.accept_mut(&mut Rename);
+//! assert_eq!(
+//!     weedle::print::ToWebIdl::to_webidl(&parsed),
+//!     "interface Storage {
+//!     getter DOMString? getItem(DOMString name);
+//!     setter undefined setItem(DOMString name, DOMString value);
+//! };
+//! "
+//! );
+//! ```
+
+use crate::argument::*;
+use crate::attribute::*;
+use crate::common::{self, Identifier};
+use crate::dictionary::*;
+use crate::interface::*;
+use crate::literal::*;
+use crate::mixin::*;
+use crate::namespace::*;
+use crate::types::*;
+use crate::*;
+
+/// Implemented by every node, visits its children
+pub trait Walk<'a> {
+    /// Calls the visitor method of each child of the node
+    fn walk<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V);
+
+    /// Calls the mutable visitor method of each child of the node
+    fn walk_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V);
+}
+
+/// Implemented by every node, calls the visitor method matching the node
+///
+/// Wrappers without a method of their own, like `Vec` or
+/// [`Braced`](../common/struct.Braced.html), visit their contents instead.
+pub trait Accept<'a> {
+    fn accept<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V);
+
+    fn accept_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V);
+}
+
+macro_rules! visitors {
+    ($($method:ident: $node:ty,)*) => {
+        /// Visits nodes by reference
+        ///
+        /// Each method visits the children of its node unless overridden.
+        pub trait Visit<'a> {
+            $(
+                fn $method(&mut self, node: &'a $node) {
+                    node.walk(self);
+                }
+            )*
+        }
+
+        /// Visits nodes by mutable reference
+        ///
+        /// Each method visits the children of its node unless overridden.
+        pub trait VisitMut<'a> {
+            $(
+                fn $method(&mut self, node: &mut $node) {
+                    node.walk_mut(self);
+                }
+            )*
+        }
+
+        $(
+            impl<'a> Accept<'a> for $node {
+                fn accept<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+                    visitor.$method(self);
+                }
+
+                fn accept_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+                    visitor.$method(self);
+                }
+            }
+        )*
+    };
+}
+
+visitors! {
+    visit_definition: Definition<'a>,
+    visit_callback_definition: CallbackDefinition<'a>,
+    visit_callback_interface_definition: CallbackInterfaceDefinition<'a>,
+    visit_interface_definition: InterfaceDefinition<'a>,
+    visit_interface_mixin_definition: InterfaceMixinDefinition<'a>,
+    visit_namespace_definition: NamespaceDefinition<'a>,
+    visit_dictionary_definition: DictionaryDefinition<'a>,
+    visit_partial_interface_definition: PartialInterfaceDefinition<'a>,
+    visit_partial_interface_mixin_definition: PartialInterfaceMixinDefinition<'a>,
+    visit_partial_dictionary_definition: PartialDictionaryDefinition<'a>,
+    visit_partial_namespace_definition: PartialNamespaceDefinition<'a>,
+    visit_enum_definition: EnumDefinition<'a>,
+    visit_typedef_definition: TypedefDefinition<'a>,
+    visit_includes_statement_definition: IncludesStatementDefinition<'a>,
+    visit_implements_definition: ImplementsDefinition<'a>,
+
+    visit_interface_member: InterfaceMember<'a>,
+    visit_const_member: ConstMember<'a>,
+    visit_attribute_interface_member: AttributeInterfaceMember<'a>,
+    visit_constructor_interface_member: ConstructorInterfaceMember<'a>,
+    visit_operation_interface_member: OperationInterfaceMember<'a>,
+    visit_iterable_interface_member: IterableInterfaceMember<'a>,
+    visit_single_typed_iterable: SingleTypedIterable<'a>,
+    visit_double_typed_iterable: DoubleTypedIterable<'a>,
+    visit_async_iterable_interface_member: AsyncIterableInterfaceMember<'a>,
+    visit_single_typed_async_iterable: SingleTypedAsyncIterable<'a>,
+    visit_double_typed_async_iterable: DoubleTypedAsyncIterable<'a>,
+    visit_async_iterable_keyword: AsyncIterableKeyword,
+    visit_maplike_interface_member: MaplikeInterfaceMember<'a>,
+    visit_setlike_interface_member: SetlikeInterfaceMember<'a>,
+    visit_stringifier_member: StringifierMember<'a>,
+    visit_jsonifier_member: JsonifierMember<'a>,
+    visit_serializer_member: SerializerMember<'a>,
+    visit_serialization_pattern: SerializationPattern<'a>,
+    visit_inheritance: Inheritance<'a>,
+    visit_special: Special,
+    visit_stringifier_or_inherit_or_static: StringifierOrInheritOrStatic,
+    visit_stringifier_or_static: StringifierOrStatic,
+
+    visit_mixin_member: MixinMember<'a>,
+    visit_attribute_mixin_member: AttributeMixinMember<'a>,
+    visit_operation_mixin_member: OperationMixinMember<'a>,
+
+    visit_namespace_member: NamespaceMember<'a>,
+    visit_const_namespace_member: ConstNamespaceMember<'a>,
+    visit_attribute_namespace_member: AttributeNamespaceMember<'a>,
+    visit_operation_namespace_member: OperationNamespaceMember<'a>,
+
+    visit_dictionary_member: DictionaryMember<'a>,
+
+    visit_argument: Argument<'a>,
+    visit_single_argument: SingleArgument<'a>,
+    visit_variadic_argument: VariadicArgument<'a>,
+
+    visit_extended_attribute: ExtendedAttribute<'a>,
+    visit_extended_attribute_arg_list: ExtendedAttributeArgList<'a>,
+    visit_extended_attribute_named_arg_list: ExtendedAttributeNamedArgList<'a>,
+    visit_extended_attribute_ident_list: ExtendedAttributeIdentList<'a>,
+    visit_extended_attribute_string_list: ExtendedAttributeStringList<'a>,
+    visit_extended_attribute_integer_list: ExtendedAttributeIntegerList<'a>,
+    visit_extended_attribute_ident: ExtendedAttributeIdent<'a>,
+    visit_extended_attribute_integer: ExtendedAttributeInteger<'a>,
+    visit_extended_attribute_wild_card: ExtendedAttributeWildCard<'a>,
+    visit_extended_attribute_tokens: ExtendedAttributeTokens<'a>,
+    visit_extended_attribute_no_args: ExtendedAttributeNoArgs<'a>,
+    visit_identifier_or_string: IdentifierOrString<'a>,
+    visit_extended_attribute_token: ExtendedAttributeToken<'a>,
+
+    visit_type: Type<'a>,
+    visit_single_type: SingleType<'a>,
+    visit_non_any_type: NonAnyType<'a>,
+    visit_union_member_type: UnionMemberType<'a>,
+    visit_attributed_type: AttributedType<'a>,
+    visit_attributed_non_any_type: AttributedNonAnyType<'a>,
+    visit_return_type: ReturnType<'a>,
+    visit_const_type: ConstType<'a>,
+    visit_integer_type: IntegerType,
+    visit_long_long_type: LongLongType,
+    visit_long_type: LongType,
+    visit_short_type: ShortType,
+    visit_floating_point_type: FloatingPointType,
+    visit_float_type: FloatType,
+    visit_double_type: DoubleType,
+    visit_sequence_type: SequenceType<'a>,
+    visit_frozen_array_type: FrozenArrayType<'a>,
+    visit_observable_array_type: ObservableArrayType<'a>,
+    visit_promise_type: PromiseType<'a>,
+    visit_record_type: RecordType<'a>,
+    visit_record_key_type: RecordKeyType<'a>,
+
+    visit_identifier: Identifier<'a>,
+    visit_default: common::Default<'a>,
+    visit_default_value: DefaultValue<'a>,
+    visit_const_value: ConstValue<'a>,
+    visit_integer_lit: IntegerLit<'a>,
+    visit_dec_lit: DecLit<'a>,
+    visit_hex_lit: HexLit<'a>,
+    visit_oct_lit: OctLit<'a>,
+    visit_float_lit: FloatLit<'a>,
+    visit_float_value_lit: FloatValueLit<'a>,
+    visit_string_lit: StringLit<'a>,
+    visit_boolean_lit: BooleanLit,
+    visit_empty_array_lit: EmptyArrayLit,
+    visit_empty_dictionary_lit: EmptyDictionaryLit,
+}
+
+impl<'a, T: Accept<'a>> Accept<'a> for Option<T> {
+    fn accept<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        if let Some(inner) = self {
+            inner.accept(visitor);
+        }
+    }
+
+    fn accept_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        if let Some(inner) = self {
+            inner.accept_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T: Accept<'a>> Accept<'a> for Box<T> {
+    fn accept<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        (**self).accept(visitor);
+    }
+
+    fn accept_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        (**self).accept_mut(visitor);
+    }
+}
+
+impl<'a, T: Accept<'a>> Accept<'a> for Vec<T> {
+    fn accept<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        for item in self {
+            item.accept(visitor);
+        }
+    }
+
+    fn accept_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        for item in self {
+            item.accept_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T: Accept<'a>, U: Accept<'a>> Accept<'a> for (T, U) {
+    fn accept<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        self.0.accept(visitor);
+        self.1.accept(visitor);
+    }
+
+    fn accept_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        self.0.accept_mut(visitor);
+        self.1.accept_mut(visitor);
+    }
+}
+
+impl<'a, T: Accept<'a>, U: Accept<'a>, W: Accept<'a>> Accept<'a> for (T, U, W) {
+    fn accept<V: Visit<'a> + ?Sized>(&'a self, visitor: &mut V) {
+        self.0.accept(visitor);
+        self.1.accept(visitor);
+        self.2.accept(visitor);
+    }
+
+    fn accept_mut<V: VisitMut<'a> + ?Sized>(&mut self, visitor: &mut V) {
+        self.0.accept_mut(visitor);
+        self.1.accept_mut(visitor);
+        self.2.accept_mut(visitor);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Names<'a> {
+        types: Vec<&'a str>,
+        arguments: Vec<&'a str>,
+    }
+
+    impl<'a> Visit<'a> for Names<'a> {
+        fn visit_non_any_type(&mut self, node: &'a NonAnyType<'a>) {
+            if let NonAnyType::Identifier(identifier) = node {
+                self.types.push(&identifier.type_.0);
+            }
+            node.walk(self);
+        }
+
+        fn visit_single_argument(&mut self, node: &'a SingleArgument<'a>) {
+            self.arguments.push(&node.identifier.0);
+            // Skips the type and default value
+        }
+    }
+
+    #[test]
+    fn should_visit_nested_nodes() {
+        let parsed = crate::parse(
+            "[Exposed=Window] interface A : B {
+                constructor(C c, optional D d = {});
+                attribute (E or sequence<F>)? e;
+                Promise<G> g(H... h);
+            };
+            dictionary I { record<DOMString, J> j; };",
+        )
+        .unwrap();
+        let mut names = Names::default();
+        parsed.accept(&mut names);
+
+        assert_eq!(names.types, ["E", "F", "G", "H", "J"]);
+        assert_eq!(names.arguments, ["c", "d"]);
+    }
+
+    struct Nullable;
+
+    impl<'a> VisitMut<'a> for Nullable {
+        fn visit_dictionary_member(&mut self, node: &mut DictionaryMember<'a>) {
+            if let Type::Single(SingleType::NonAny(NonAnyType::Boolean(boolean))) = &mut node.type_
+            {
+                boolean.q_mark = Some(term!(?));
+            }
+        }
+    }
+
+    #[test]
+    fn should_change_nodes() {
+        let (_, mut parsed) =
+            Definition::parse("dictionary A { boolean a; sequence<boolean> b; };").unwrap();
+        parsed.accept_mut(&mut Nullable);
+
+        assert_eq!(
+            crate::print::ToWebIdl::to_webidl(&parsed),
+            "dictionary A {\n    boolean? a;\n    sequence<boolean> b;\n};\n"
+        );
+    }
+}
@@ -0,0 +1,1983 @@
+//! Conversion to and from the JSON syntax tree of [webidl2.js](https://github.com/w3c/webidl2.js)
+//!
+//! [`to_json`](fn.to_json.html) exports definitions in the shape the JavaScript `webidl2`
+//! library produces, `{ "type": "interface", "name": ..., "members": [...], "extAttrs": [...] }`,
+//! and [`from_json`](fn.from_json.html) imports such a tree back. Imported definitions borrow
+//! their names and values from the JSON.
+//!
+//! The shape is the one of webidl2.js 24. It does not keep every detail of the source, so a
+//! round trip loses some: an extended attribute or async iterable with empty parentheses,
+//! `[Foo()]`, comes back without them, a trailing comma after the last enum value is dropped,
+//! and locations are not kept at all.
+//!
+//! Extended attributes whose value is none of the shapes webidl2.js knows, like
+//! `[Foo=(a, "b")]`, are exported with an `rhs` of type `"tokens"` listing the tokens after
+//! their name.
+//!
+//! Requires the `webidl2` feature.
+//!
+//! ### Example
+//!
+//! ```
+//! use weedle::span::SpanlessEq;
+//! use weedle::webidl2;
+//!
+//! let parsed = weedle::parse("
+//!     [Exposed=Window]
+//!     interface Node : EventTarget {
+//!         readonly attribute DOMString? nodeValue;
+//!     };
+//! ").unwrap();
+//!
+//! let json = webidl2::to_json(&parsed);
+//! assert_eq!(json[0]["type"], "interface");
+//! assert_eq!(json[0]["inheritance"], "EventTarget");
+//! assert_eq!(json[0]["extAttrs"][0]["rhs"]["value"], "Window");
+//! assert_eq!(json[0]["members"][0]["idlType"]["idlType"], "DOMString");
+//! assert_eq!(json[0]["members"][0]["idlType"]["nullable"], true);
+//!
+//! assert!(webidl2::from_json(&json).unwrap().spanless_eq(&parsed));
+//! ```
+
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::argument::{Argument, ArgumentList, SingleArgument, VariadicArgument};
+use crate::attribute::{
+    ExtendedAttribute, ExtendedAttributeArgList, ExtendedAttributeIdent,
+    ExtendedAttributeIdentList, ExtendedAttributeInteger, ExtendedAttributeIntegerList,
+    ExtendedAttributeList, ExtendedAttributeNamedArgList, ExtendedAttributeNoArgs,
+    ExtendedAttributeStringList, ExtendedAttributeToken, ExtendedAttributeTokens,
+    ExtendedAttributeWildCard, IdentifierOrString,
+};
+use crate::common::{
+    Braced, Bracketed, Default, Generics, Identifier, Parenthesized, Punctuated, PunctuatedNonEmpty,
+};
+use crate::dictionary::DictionaryMember;
+use crate::interface::{
+    AsyncIterableInterfaceMember, AsyncIterableKeyword, AttributeInterfaceMember, ConstMember,
+    ConstructorInterfaceMember, DoubleTypedAsyncIterable, DoubleTypedIterable, Inheritance,
+    InterfaceMember, IterableInterfaceMember, JsonifierMember, MaplikeInterfaceMember,
+    OperationInterfaceMember, SerializationPattern, SerializerMember, SetlikeInterfaceMember,
+    SingleTypedAsyncIterable, SingleTypedIterable, Special, StringifierMember,
+    StringifierOrInheritOrStatic, StringifierOrStatic,
+};
+use crate::literal::{
+    BooleanLit, ConstValue, DefaultValue, EmptyArrayLit, EmptyDictionaryLit, FloatLit,
+    FloatValueLit, IntegerLit, StringLit,
+};
+use crate::mixin::{AttributeMixinMember, MixinMember, OperationMixinMember};
+use crate::namespace::{
+    AttributeNamespaceMember, ConstNamespaceMember, NamespaceMember, OperationNamespaceMember,
+};
+use crate::print::ToWebIdl;
+use crate::span::Span;
+use crate::types::{
+    AttributedNonAnyType, AttributedType, ConstType, FrozenArrayType, MayBeNull, NonAnyType,
+    ObservableArrayType, PromiseType, RecordKeyType, RecordType, ReturnType, SequenceType,
+    SingleType, Type, UnionMemberType, UnionType,
+};
+use crate::*;
+
+/// Exports `definitions` as a webidl2.js syntax tree
+pub fn to_json(definitions: &[Definition]) -> Value {
+    Value::Array(definitions.iter().map(definition).collect())
+}
+
+/// Imports definitions from a webidl2.js syntax tree
+///
+/// Fails if the JSON is not an array of definitions, or has a node of an unknown type. Fields
+/// webidl2.js always writes, like `extAttrs`, `partial` or `readonly`, may be left out.
+pub fn from_json(json: &Value) -> std::result::Result<Definitions<'_>, ImportError> {
+    json.as_array()
+        .ok_or_else(|| ImportError::new("expected an array of definitions"))?
+        .iter()
+        // webidl2.js ends the tree with the trivia after the last definition
+        .filter(|definition| definition["type"] != "eof")
+        .map(import_definition)
+        .collect()
+}
+
+/// A JSON tree which does not describe WebIDL definitions
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportError {
+    pub message: String,
+}
+
+impl ImportError {
+    fn new(message: impl Into<String>) -> Self {
+        ImportError {
+            message: message.into(),
+        }
+    }
+
+    fn expected(what: &str, key: &str) -> Self {
+        ImportError::new(format!("expected {} for `{}`", what, key))
+    }
+
+    fn unknown(what: &str, value: &str) -> Self {
+        ImportError::new(format!("unknown {} `{}`", what, value))
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+fn definition(definition: &Definition) -> Value {
+    match definition {
+        Definition::Callback(d) => json!({
+            "type": "callback",
+            "name": d.identifier.0,
+            "idlType": return_type(&d.return_type, Some("return-type")),
+            "arguments": arguments(&d.arguments.body),
+            "extAttrs": ext_attrs(&d.attributes),
+        }),
+        Definition::CallbackInterface(d) => container(
+            "callback interface",
+            &d.identifier,
+            d.inheritance.as_ref(),
+            d.members.body.iter().map(interface_member).collect(),
+            &d.attributes,
+            false,
+        ),
+        Definition::Interface(d) => container(
+            "interface",
+            &d.identifier,
+            d.inheritance.as_ref(),
+            d.members.body.iter().map(interface_member).collect(),
+            &d.attributes,
+            false,
+        ),
+        Definition::InterfaceMixin(d) => container(
+            "interface mixin",
+            &d.identifier,
+            None,
+            d.members.body.iter().map(mixin_member).collect(),
+            &d.attributes,
+            false,
+        ),
+        Definition::Namespace(d) => container(
+            "namespace",
+            &d.identifier,
+            None,
+            d.members.body.iter().map(namespace_member).collect(),
+            &d.attributes,
+            false,
+        ),
+        Definition::Dictionary(d) => container(
+            "dictionary",
+            &d.identifier,
+            d.inheritance.as_ref(),
+            d.members.body.iter().map(dictionary_member).collect(),
+            &d.attributes,
+            false,
+        ),
+        Definition::PartialInterface(d) => container(
+            "interface",
+            &d.identifier,
+            None,
+            d.members.body.iter().map(interface_member).collect(),
+            &d.attributes,
+            true,
+        ),
+        Definition::PartialInterfaceMixin(d) => container(
+            "interface mixin",
+            &d.identifier,
+            None,
+            d.members.body.iter().map(mixin_member).collect(),
+            &d.attributes,
+            true,
+        ),
+        Definition::PartialDictionary(d) => container(
+            "dictionary",
+            &d.identifier,
+            None,
+            d.members.body.iter().map(dictionary_member).collect(),
+            &d.attributes,
+            true,
+        ),
+        Definition::PartialNamespace(d) => container(
+            "namespace",
+            &d.identifier,
+            None,
+            d.members.body.iter().map(namespace_member).collect(),
+            &d.attributes,
+            true,
+        ),
+        Definition::Enum(d) => json!({
+            "type": "enum",
+            "name": d.identifier.0,
+            "values": d.values.body.list.iter().map(|value| json!({
+                "type": "enum-value",
+                "value": value.0,
+            })).collect::<Vec<_>>(),
+            "extAttrs": ext_attrs(&d.attributes),
+        }),
+        Definition::Typedef(d) => json!({
+            "type": "typedef",
+            "name": d.identifier.0,
+            "idlType": attributed_type(&d.type_, Some("typedef-type")),
+            "extAttrs": ext_attrs(&d.attributes),
+        }),
+        Definition::IncludesStatement(d) => json!({
+            "type": "includes",
+            "target": d.lhs_identifier.0,
+            "includes": d.rhs_identifier.0,
+            "extAttrs": ext_attrs(&d.attributes),
+        }),
+        Definition::Implements(d) => json!({
+            "type": "implements",
+            "target": d.lhs_identifier.0,
+            "implements": d.rhs_identifier.0,
+            "extAttrs": ext_attrs(&d.attributes),
+        }),
+    }
+}
+
+fn container(
+    kind: &str,
+    identifier: &Identifier,
+    inheritance: Option<&Inheritance>,
+    members: Vec<Value>,
+    attributes: &Option<ExtendedAttributeList>,
+    partial: bool,
+) -> Value {
+    json!({
+        "type": kind,
+        "name": identifier.0,
+        "inheritance": inheritance.map(|inheritance| &inheritance.identifier.0),
+        "members": members,
+        "extAttrs": ext_attrs(attributes),
+        "partial": partial,
+    })
+}
+
+fn interface_member(member: &InterfaceMember) -> Value {
+    match member {
+        InterfaceMember::Const(m) => {
+            const_member(&m.attributes, &m.const_type, &m.identifier, &m.const_value)
+        }
+        InterfaceMember::Attribute(m) => {
+            let special = match m.modifier {
+                Some(StringifierOrInheritOrStatic::Stringifier(_)) => "stringifier",
+                Some(StringifierOrInheritOrStatic::Inherit(_)) => "inherit",
+                Some(StringifierOrInheritOrStatic::Static(_)) => "static",
+                None => "",
+            };
+            attribute(
+                &m.attributes,
+                special,
+                m.readonly.is_some(),
+                &m.type_,
+                &m.identifier,
+            )
+        }
+        InterfaceMember::Constructor(m) => json!({
+            "type": "constructor",
+            "arguments": arguments(&m.args.body),
+            "extAttrs": ext_attrs(&m.attributes),
+        }),
+        InterfaceMember::Operation(m) => {
+            let special = match (&m.modifier, &m.special) {
+                (Some(StringifierOrStatic::Stringifier(_)), _) => "stringifier",
+                (Some(StringifierOrStatic::Static(_)), _) => "static",
+                (None, Some(Special::Getter(_))) => "getter",
+                (None, Some(Special::Setter(_))) => "setter",
+                (None, Some(Special::Deleter(_))) => "deleter",
+                (None, Some(Special::LegacyCaller(_))) => "legacycaller",
+                (None, None) => "",
+            };
+            operation(
+                &m.attributes,
+                special,
+                Some(&m.return_type),
+                m.identifier.as_ref(),
+                Some(&m.args.body),
+            )
+        }
+        InterfaceMember::Iterable(IterableInterfaceMember::Single(m)) => declaration(
+            "iterable",
+            &m.attributes,
+            vec![&m.generics.body],
+            false,
+            false,
+            None,
+        ),
+        InterfaceMember::Iterable(IterableInterfaceMember::Double(m)) => declaration(
+            "iterable",
+            &m.attributes,
+            vec![&m.generics.body.0, &m.generics.body.2],
+            false,
+            false,
+            None,
+        ),
+        InterfaceMember::AsyncIterable(AsyncIterableInterfaceMember::Single(m)) => declaration(
+            async_iterable(&m.async_iterable),
+            &m.attributes,
+            vec![&m.generics.body],
+            false,
+            true,
+            m.args.as_ref().map(|args| &args.body),
+        ),
+        InterfaceMember::AsyncIterable(AsyncIterableInterfaceMember::Double(m)) => declaration(
+            async_iterable(&m.async_iterable),
+            &m.attributes,
+            vec![&m.generics.body.0, &m.generics.body.2],
+            false,
+            true,
+            m.args.as_ref().map(|args| &args.body),
+        ),
+        InterfaceMember::Maplike(m) => declaration(
+            "maplike",
+            &m.attributes,
+            vec![&m.generics.body.0, &m.generics.body.2],
+            m.readonly.is_some(),
+            false,
+            None,
+        ),
+        InterfaceMember::Setlike(m) => declaration(
+            "setlike",
+            &m.attributes,
+            vec![&m.generics.body],
+            m.readonly.is_some(),
+            false,
+            None,
+        ),
+        InterfaceMember::Stringifier(m) => {
+            operation(&m.attributes, "stringifier", None, None, None)
+        }
+        InterfaceMember::Jsonifier(m) => json!({
+            "type": "jsonifier",
+            "extAttrs": ext_attrs(&m.attributes),
+        }),
+        InterfaceMember::Serializer(m) => serializer(m),
+    }
+}
+
+/// Exported in the shape of the webidl2.js versions which still knew serializers
+fn serializer(m: &SerializerMember) -> Value {
+    let names = |list: &Punctuated<Identifier, term!(,)>| {
+        json!(list
+            .list
+            .iter()
+            .map(|identifier| identifier.0.as_ref())
+            .collect::<Vec<_>>())
+    };
+    let (pattern_map, pattern_list, name, members) = match m.pattern.as_ref().map(|p| &p.1) {
+        None => (false, false, None, json!([])),
+        Some(SerializationPattern::Map(map)) => (true, false, None, names(&map.body)),
+        Some(SerializationPattern::List(list)) => (false, true, None, names(&list.body)),
+        Some(SerializationPattern::Identifier(identifier)) => {
+            (false, false, Some(identifier.0.as_ref()), json!([]))
+        }
+    };
+    json!({
+        "type": "serializer",
+        "patternMap": pattern_map,
+        "patternList": pattern_list,
+        "name": name,
+        "members": members,
+        "extAttrs": ext_attrs(&m.attributes),
+    })
+}
+
+fn mixin_member(member: &MixinMember) -> Value {
+    match member {
+        MixinMember::Const(m) => {
+            const_member(&m.attributes, &m.const_type, &m.identifier, &m.const_value)
+        }
+        MixinMember::Operation(m) => operation(
+            &m.attributes,
+            if m.stringifier.is_some() {
+                "stringifier"
+            } else {
+                ""
+            },
+            Some(&m.return_type),
+            m.identifier.as_ref(),
+            Some(&m.args.body),
+        ),
+        MixinMember::Attribute(m) => attribute(
+            &m.attributes,
+            if m.stringifier.is_some() {
+                "stringifier"
+            } else {
+                ""
+            },
+            m.readonly.is_some(),
+            &m.type_,
+            &m.identifier,
+        ),
+        MixinMember::Stringifier(m) => operation(&m.attributes, "stringifier", None, None, None),
+    }
+}
+
+fn namespace_member(member: &NamespaceMember) -> Value {
+    match member {
+        NamespaceMember::Const(m) => {
+            const_member(&m.attributes, &m.const_type, &m.identifier, &m.const_value)
+        }
+        NamespaceMember::Operation(m) => operation(
+            &m.attributes,
+            "",
+            Some(&m.return_type),
+            m.identifier.as_ref(),
+            Some(&m.args.body),
+        ),
+        NamespaceMember::Attribute(m) => {
+            attribute(&m.attributes, "", true, &m.type_, &m.identifier)
+        }
+    }
+}
+
+fn dictionary_member(member: &DictionaryMember) -> Value {
+    json!({
+        "type": "field",
+        "name": member.identifier.0,
+        "extAttrs": ext_attrs(&member.attributes),
+        "idlType": type_(&member.type_, Some("dictionary-type"), &None),
+        "default": default(&member.default),
+        "required": member.required.is_some(),
+    })
+}
+
+fn const_member(
+    attributes: &Option<ExtendedAttributeList>,
+    const_type: &ConstType,
+    identifier: &Identifier,
+    value: &ConstValue,
+) -> Value {
+    json!({
+        "type": "const",
+        "name": identifier.0,
+        "idlType": self::const_type(const_type),
+        "extAttrs": ext_attrs(attributes),
+        "value": const_value(value),
+    })
+}
+
+fn attribute(
+    attributes: &Option<ExtendedAttributeList>,
+    special: &str,
+    readonly: bool,
+    type_: &AttributedType,
+    identifier: &Identifier,
+) -> Value {
+    json!({
+        "type": "attribute",
+        "name": identifier.0,
+        "idlType": attributed_type(type_, Some("attribute-type")),
+        "extAttrs": ext_attrs(attributes),
+        "special": special,
+        "readonly": readonly,
+    })
+}
+
+fn operation(
+    attributes: &Option<ExtendedAttributeList>,
+    special: &str,
+    return_type: Option<&ReturnType>,
+    identifier: Option<&Identifier>,
+    args: Option<&ArgumentList>,
+) -> Value {
+    json!({
+        "type": "operation",
+        "name": identifier.map_or("", |identifier| &identifier.0),
+        "idlType": return_type.map(|return_type| self::return_type(return_type, Some("return-type"))),
+        "arguments": args.map_or_else(|| json!([]), arguments),
+        "extAttrs": ext_attrs(attributes),
+        "special": special,
+    })
+}
+
+/// webidl2.js names the current form `async_iterable` and the legacy one an async `iterable`
+fn async_iterable(keyword: &AsyncIterableKeyword) -> &'static str {
+    match keyword {
+        AsyncIterableKeyword::AsyncIterable(_) => "async_iterable",
+        AsyncIterableKeyword::Legacy(_) => "iterable",
+    }
+}
+
+fn declaration(
+    kind: &str,
+    attributes: &Option<ExtendedAttributeList>,
+    types: Vec<&AttributedType>,
+    readonly: bool,
+    async_: bool,
+    args: Option<&ArgumentList>,
+) -> Value {
+    json!({
+        "type": kind,
+        "idlType": types.into_iter().map(|type_| attributed_type(type_, None)).collect::<Vec<_>>(),
+        "arguments": args.map_or_else(|| json!([]), arguments),
+        "extAttrs": ext_attrs(attributes),
+        "readonly": readonly,
+        "async": async_,
+    })
+}
+
+fn ext_attrs(attributes: &Option<ExtendedAttributeList>) -> Value {
+    let attributes = attributes.iter().flat_map(|list| &list.body.list);
+    Value::Array(attributes.map(ext_attr).collect())
+}
+
+fn ext_attr(attribute: &ExtendedAttribute) -> Value {
+    let (rhs, args) = match attribute {
+        ExtendedAttribute::ArgList(a) => (Value::Null, arguments(&a.args.body)),
+        ExtendedAttribute::NamedArgList(a) => (
+            json!({ "type": "identifier", "value": a.rhs_identifier.0 }),
+            arguments(&a.args.body),
+        ),
+        ExtendedAttribute::IdentList(a) => (
+            json!({
+                "type": "identifier-list",
+                "value": a.list.body.list.iter().map(|identifier| json!({
+                    "value": identifier.0,
+                })).collect::<Vec<_>>(),
+            }),
+            json!([]),
+        ),
+        ExtendedAttribute::Ident(a) => (
+            match &a.rhs {
+                IdentifierOrString::Identifier(identifier) => {
+                    json!({ "type": "identifier", "value": identifier.0 })
+                }
+                IdentifierOrString::String(string) => {
+                    json!({ "type": "string", "value": string.0 })
+                }
+            },
+            json!([]),
+        ),
+        ExtendedAttribute::StringList(a) => (
+            json!({
+                "type": "string-list",
+                "value": a.list.body.list.iter().map(|string| json!({
+                    "value": string.0,
+                })).collect::<Vec<_>>(),
+            }),
+            json!([]),
+        ),
+        ExtendedAttribute::IntegerList(a) => (
+            json!({
+                "type": "integer-list",
+                "value": a.list.body.list.iter().map(|integer| json!({
+                    "value": integer_value(integer)["value"],
+                })).collect::<Vec<_>>(),
+            }),
+            json!([]),
+        ),
+        ExtendedAttribute::Integer(a) => (
+            json!({ "type": "integer", "value": integer_value(&a.rhs)["value"] }),
+            json!([]),
+        ),
+        ExtendedAttribute::Wildcard(_) => (json!({ "type": "*", "value": "*" }), json!([])),
+        ExtendedAttribute::Tokens(a) => (tokens(&a.tokens), json!([])),
+        ExtendedAttribute::NoArgs(_) => (Value::Null, json!([])),
+    };
+    json!({
+        "type": "extended-attribute",
+        "name": attribute.identifier().0,
+        "rhs": rhs,
+        "arguments": args,
+    })
+}
+
+/// `=1.5` and `=(1.5, 2)` are the decimals of webidl2.js, anything else is kept as tokens
+fn tokens(tokens: &[ExtendedAttributeToken]) -> Value {
+    let texts: Vec<&str> = tokens.iter().map(|token| &*token.0).collect();
+    let decimal = |text: &&str| parse_all::<FloatValueLit>(text).is_some();
+    match texts[..] {
+        ["=", value] if decimal(&value) => json!({ "type": "decimal", "value": value }),
+        ["=", "(", ref list @ .., ")"]
+            if list.iter().step_by(2).all(decimal)
+                && list.iter().skip(1).step_by(2).all(|text| *text == ",") =>
+        {
+            json!({
+                "type": "decimal-list",
+                "value": list.iter().step_by(2).map(|value| json!({
+                    "value": value,
+                })).collect::<Vec<_>>(),
+            })
+        }
+        _ => json!({ "type": "tokens", "value": texts }),
+    }
+}
+
+fn arguments(args: &ArgumentList) -> Value {
+    Value::Array(args.list.iter().map(argument).collect())
+}
+
+fn argument(argument: &Argument) -> Value {
+    match argument {
+        Argument::Single(a) => json!({
+            "type": "argument",
+            "name": a.identifier.0,
+            "extAttrs": ext_attrs(&a.attributes),
+            "idlType": attributed_type(&a.type_, Some("argument-type")),
+            "default": default(&a.default),
+            "optional": a.optional.is_some(),
+            "variadic": false,
+        }),
+        Argument::Variadic(a) => json!({
+            "type": "argument",
+            "name": a.identifier.0,
+            "extAttrs": ext_attrs(&a.attributes),
+            "idlType": type_(&a.type_, Some("argument-type"), &None),
+            "default": null,
+            "optional": false,
+            "variadic": true,
+        }),
+    }
+}
+
+fn idl_type(
+    kind: Option<&str>,
+    attributes: &Option<ExtendedAttributeList>,
+    generic: &str,
+    nullable: bool,
+    union: bool,
+    inner: Value,
+) -> Value {
+    json!({
+        "type": kind,
+        "extAttrs": ext_attrs(attributes),
+        "generic": generic,
+        "nullable": nullable,
+        "union": union,
+        "idlType": inner,
+    })
+}
+
+fn attributed_type(type_: &AttributedType, kind: Option<&str>) -> Value {
+    self::type_(&type_.type_, kind, &type_.attributes)
+}
+
+fn return_type(type_: &ReturnType, kind: Option<&str>) -> Value {
+    match type_ {
+        ReturnType::Undefined(_) => idl_type(kind, &None, "", false, false, json!("undefined")),
+        ReturnType::Type(type_) => self::type_(type_, kind, &None),
+    }
+}
+
+fn type_(type_: &Type, kind: Option<&str>, attributes: &Option<ExtendedAttributeList>) -> Value {
+    match type_ {
+        Type::Single(SingleType::Any(_)) => {
+            idl_type(kind, attributes, "", false, false, json!("any"))
+        }
+        Type::Single(SingleType::NonAny(type_)) => non_any_type(type_, kind, attributes),
+        Type::Union(type_) => union_type(type_, kind, attributes),
+    }
+}
+
+fn union_type(
+    type_: &MayBeNull<UnionType>,
+    kind: Option<&str>,
+    attributes: &Option<ExtendedAttributeList>,
+) -> Value {
+    let members = type_.type_.body.list.iter().map(|member| match member {
+        UnionMemberType::Single(member) => non_any_type(&member.type_, kind, &member.attributes),
+        UnionMemberType::Union(member) => union_type(member, kind, &None),
+    });
+    let members = members.collect::<Vec<_>>();
+    idl_type(
+        kind,
+        attributes,
+        "",
+        type_.q_mark.is_some(),
+        true,
+        json!(members),
+    )
+}
+
+fn non_any_type(
+    type_: &NonAnyType,
+    kind: Option<&str>,
+    attributes: &Option<ExtendedAttributeList>,
+) -> Value {
+    let named =
+        |name: String, nullable: bool| idl_type(kind, attributes, "", nullable, false, json!(name));
+    let generic = |generic: &str, nullable: bool, types: Vec<Value>| {
+        idl_type(kind, attributes, generic, nullable, false, json!(types))
+    };
+    macro_rules! named {
+        ($type_:expr) => {
+            named(type_name(&$type_.type_), $type_.q_mark.is_some())
+        };
+    }
+
+    match type_ {
+        NonAnyType::Promise(t) => {
+            generic("Promise", false, vec![return_type(&t.generics.body, kind)])
+        }
+        NonAnyType::Integer(t) => named!(t),
+        NonAnyType::FloatingPoint(t) => named!(t),
+        NonAnyType::Boolean(t) => named!(t),
+        NonAnyType::Byte(t) => named!(t),
+        NonAnyType::Octet(t) => named!(t),
+        NonAnyType::Bigint(t) => named!(t),
+        NonAnyType::ByteString(t) => named!(t),
+        NonAnyType::DOMString(t) => named!(t),
+        NonAnyType::USVString(t) => named!(t),
+        NonAnyType::Sequence(t) => generic(
+            "sequence",
+            t.q_mark.is_some(),
+            vec![self::type_(&t.type_.generics.body, kind, &None)],
+        ),
+        NonAnyType::Object(t) => named!(t),
+        NonAnyType::Symbol(t) => named!(t),
+        NonAnyType::Error(t) => named!(t),
+        NonAnyType::ArrayBuffer(t) => named!(t),
+        NonAnyType::DataView(t) => named!(t),
+        NonAnyType::Int8Array(t) => named!(t),
+        NonAnyType::Int16Array(t) => named!(t),
+        NonAnyType::Int32Array(t) => named!(t),
+        NonAnyType::Uint8Array(t) => named!(t),
+        NonAnyType::Uint16Array(t) => named!(t),
+        NonAnyType::Uint32Array(t) => named!(t),
+        NonAnyType::Uint8ClampedArray(t) => named!(t),
+        NonAnyType::Float16Array(t) => named!(t),
+        NonAnyType::Float32Array(t) => named!(t),
+        NonAnyType::Float64Array(t) => named!(t),
+        NonAnyType::BigInt64Array(t) => named!(t),
+        NonAnyType::BigUint64Array(t) => named!(t),
+        NonAnyType::ArrayBufferView(t) => named!(t),
+        NonAnyType::BufferSource(t) => named!(t),
+        NonAnyType::FrozenArrayType(t) => generic(
+            "FrozenArray",
+            t.q_mark.is_some(),
+            vec![self::type_(&t.type_.generics.body, kind, &None)],
+        ),
+        NonAnyType::ObservableArrayType(t) => generic(
+            "ObservableArray",
+            t.q_mark.is_some(),
+            vec![self::type_(&t.type_.generics.body, kind, &None)],
+        ),
+        NonAnyType::RecordType(t) => {
+            let (key, _, value) = &t.type_.generics.body;
+            let key = match &**key {
+                RecordKeyType::Byte(_) => named("ByteString".to_string(), false),
+                RecordKeyType::DOM(_) => named("DOMString".to_string(), false),
+                RecordKeyType::USV(_) => named("USVString".to_string(), false),
+                RecordKeyType::NonAny(key) => non_any_type(key, kind, &None),
+            };
+            generic(
+                "record",
+                t.q_mark.is_some(),
+                vec![key, self::type_(value, kind, &None)],
+            )
+        }
+        NonAnyType::Identifier(t) => named(t.type_.0.to_string(), t.q_mark.is_some()),
+    }
+}
+
+/// Name of a type spelled with keywords. Ex: `unsigned long`
+fn type_name(type_: &impl ToWebIdl) -> String {
+    type_.to_webidl().trim_end().to_string()
+}
+
+fn const_type(type_: &ConstType) -> Value {
+    let kind = Some("const-type");
+    let named =
+        |name: String, nullable: bool| idl_type(kind, &None, "", nullable, false, json!(name));
+    match type_ {
+        ConstType::Integer(t) => named(type_name(&t.type_), t.q_mark.is_some()),
+        ConstType::FloatingPoint(t) => named(type_name(&t.type_), t.q_mark.is_some()),
+        ConstType::Boolean(t) => named(type_name(&t.type_), t.q_mark.is_some()),
+        ConstType::Byte(t) => named(type_name(&t.type_), t.q_mark.is_some()),
+        ConstType::Octet(t) => named(type_name(&t.type_), t.q_mark.is_some()),
+        ConstType::Bigint(t) => named(type_name(&t.type_), t.q_mark.is_some()),
+        ConstType::Identifier(t) => named(t.type_.0.to_string(), t.q_mark.is_some()),
+    }
+}
+
+fn default(default: &Option<Default>) -> Value {
+    match default {
+        Some(default) => default_value(&default.value),
+        None => Value::Null,
+    }
+}
+
+fn default_value(value: &DefaultValue) -> Value {
+    match value {
+        DefaultValue::Boolean(b) => json!({ "type": "boolean", "value": b.0 }),
+        DefaultValue::EmptyArray(_) => json!({ "type": "sequence", "value": [] }),
+        DefaultValue::EmptyDictionary(_) => json!({ "type": "dictionary" }),
+        DefaultValue::Float(f) => float_value(f),
+        DefaultValue::Integer(i) => integer_value(i),
+        DefaultValue::Null(_) => json!({ "type": "null" }),
+        DefaultValue::String(s) => json!({ "type": "string", "value": s.0 }),
+    }
+}
+
+fn const_value(value: &ConstValue) -> Value {
+    match value {
+        ConstValue::Boolean(b) => json!({ "type": "boolean", "value": b.0 }),
+        ConstValue::Float(f) => float_value(f),
+        ConstValue::Integer(i) => integer_value(i),
+        ConstValue::Null(_) => json!({ "type": "null" }),
+    }
+}
+
+fn float_value(value: &FloatLit) -> Value {
+    match value {
+        FloatLit::Value(v) => json!({ "type": "number", "value": v.0 }),
+        FloatLit::NegInfinity(_) => json!({ "type": "Infinity", "negative": true }),
+        FloatLit::Infinity(_) => json!({ "type": "Infinity", "negative": false }),
+        FloatLit::NaN(_) => json!({ "type": "NaN" }),
+    }
+}
+
+fn integer_value(value: &IntegerLit) -> Value {
+    let text = match value {
+        IntegerLit::Dec(d) => &d.0,
+        IntegerLit::Hex(h) => &h.0,
+        IntegerLit::Oct(o) => &o.0,
+    };
+    json!({ "type": "number", "value": text })
+}
+
+type Result<T> = std::result::Result<T, ImportError>;
+
+fn string<'a>(value: &'a Value, key: &str) -> Result<&'a str> {
+    value[key]
+        .as_str()
+        .ok_or_else(|| ImportError::expected("a string", key))
+}
+
+fn flag(value: &Value, key: &str) -> bool {
+    value[key].as_bool().unwrap_or(false)
+}
+
+fn list<'a>(value: &'a Value, key: &str) -> Result<&'a [Value]> {
+    match &value[key] {
+        Value::Array(items) => Ok(items),
+        Value::Null => Ok(&[]),
+        _ => Err(ImportError::expected("an array", key)),
+    }
+}
+
+fn identifier<'a>(value: &'a Value, key: &str) -> Result<Identifier<'a>> {
+    string(value, key).map(Identifier::new)
+}
+
+/// Parses all of `text`, for the parts of the tree which are kept as WebIDL source
+fn parse_all<'a, T: Parse<'a>>(text: &'a str) -> Option<T> {
+    match T::parse(text) {
+        Ok((rest, parsed)) if rest.trim().is_empty() => Some(parsed),
+        _ => None,
+    }
+}
+
+fn parenthesized<T>(body: T) -> Parenthesized<T> {
+    Parenthesized {
+        open_paren: term!(OpenParen),
+        body,
+        close_paren: term!(CloseParen),
+        span: Span::default(),
+    }
+}
+
+fn bracketed<T>(body: T) -> Bracketed<T> {
+    Bracketed {
+        open_bracket: term!(OpenBracket),
+        body,
+        close_bracket: term!(CloseBracket),
+        span: Span::default(),
+    }
+}
+
+fn braced<T>(body: T) -> Braced<T> {
+    Braced {
+        open_brace: term!(OpenBrace),
+        body,
+        close_brace: term!(CloseBrace),
+        span: Span::default(),
+    }
+}
+
+fn generics<T>(body: T) -> Generics<T> {
+    Generics {
+        open_angle: term!(<),
+        body,
+        close_angle: term!(>),
+        span: Span::default(),
+    }
+}
+
+fn import_all<'a, T>(
+    items: &'a [Value],
+    import: impl Fn(&'a Value) -> Result<T>,
+) -> Result<Vec<T>> {
+    items.iter().map(import).collect()
+}
+
+fn import_definition(d: &Value) -> Result<Definition<'_>> {
+    let attributes = import_ext_attrs(d)?;
+    let partial = flag(d, "partial");
+    let kind = string(d, "type")?;
+    let definition = match kind {
+        "callback" => Definition::Callback(CallbackDefinition {
+            attributes,
+            callback: term!(callback),
+            identifier: identifier(d, "name")?,
+            assign: term!(=),
+            return_type: import_return_type(&d["idlType"])?,
+            arguments: parenthesized(import_arguments(d)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "callback interface" => Definition::CallbackInterface(CallbackInterfaceDefinition {
+            attributes,
+            callback: term!(callback),
+            interface: term!(interface),
+            identifier: identifier(d, "name")?,
+            inheritance: import_inheritance(d)?,
+            members: braced(import_all(list(d, "members")?, import_interface_member)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "interface" if partial => Definition::PartialInterface(PartialInterfaceDefinition {
+            attributes,
+            partial: term!(partial),
+            interface: term!(interface),
+            identifier: identifier(d, "name")?,
+            members: braced(import_all(list(d, "members")?, import_interface_member)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "interface" => Definition::Interface(InterfaceDefinition {
+            attributes,
+            interface: term!(interface),
+            identifier: identifier(d, "name")?,
+            inheritance: import_inheritance(d)?,
+            members: braced(import_all(list(d, "members")?, import_interface_member)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "interface mixin" if partial => {
+            Definition::PartialInterfaceMixin(PartialInterfaceMixinDefinition {
+                attributes,
+                partial: term!(partial),
+                interface: term!(interface),
+                mixin: term!(mixin),
+                identifier: identifier(d, "name")?,
+                members: braced(import_all(list(d, "members")?, import_mixin_member)?),
+                semi_colon: term!(;),
+                span: Span::default(),
+            })
+        }
+        "interface mixin" => Definition::InterfaceMixin(InterfaceMixinDefinition {
+            attributes,
+            interface: term!(interface),
+            mixin: term!(mixin),
+            identifier: identifier(d, "name")?,
+            members: braced(import_all(list(d, "members")?, import_mixin_member)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "namespace" if partial => Definition::PartialNamespace(PartialNamespaceDefinition {
+            attributes,
+            partial: term!(partial),
+            namespace: term!(namespace),
+            identifier: identifier(d, "name")?,
+            members: braced(import_all(list(d, "members")?, import_namespace_member)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "namespace" => Definition::Namespace(NamespaceDefinition {
+            attributes,
+            namespace: term!(namespace),
+            identifier: identifier(d, "name")?,
+            members: braced(import_all(list(d, "members")?, import_namespace_member)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "dictionary" if partial => Definition::PartialDictionary(PartialDictionaryDefinition {
+            attributes,
+            partial: term!(partial),
+            dictionary: term!(dictionary),
+            identifier: identifier(d, "name")?,
+            members: braced(import_all(list(d, "members")?, import_dictionary_member)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "dictionary" => Definition::Dictionary(DictionaryDefinition {
+            attributes,
+            dictionary: term!(dictionary),
+            identifier: identifier(d, "name")?,
+            inheritance: import_inheritance(d)?,
+            members: braced(import_all(list(d, "members")?, import_dictionary_member)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "enum" => {
+            let values = import_all(list(d, "values")?, |value| {
+                string(value, "value").map(StringLit::new)
+            })?;
+            if values.is_empty() {
+                return Err(ImportError::new("expected at least one enum value"));
+            }
+            Definition::Enum(EnumDefinition {
+                attributes,
+                enum_: term!(enum),
+                identifier: identifier(d, "name")?,
+                values: braced(PunctuatedNonEmpty::new(values)),
+                semi_colon: term!(;),
+                span: Span::default(),
+            })
+        }
+        "typedef" => Definition::Typedef(TypedefDefinition {
+            attributes,
+            typedef: term!(typedef),
+            type_: import_attributed_type(&d["idlType"])?,
+            identifier: identifier(d, "name")?,
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "includes" => Definition::IncludesStatement(IncludesStatementDefinition {
+            attributes,
+            lhs_identifier: identifier(d, "target")?,
+            includes: term!(includes),
+            rhs_identifier: identifier(d, "includes")?,
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "implements" => Definition::Implements(ImplementsDefinition {
+            attributes,
+            lhs_identifier: identifier(d, "target")?,
+            includes: term!(implements),
+            rhs_identifier: identifier(d, "implements")?,
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        _ => return Err(ImportError::unknown("definition type", kind)),
+    };
+    Ok(definition)
+}
+
+fn import_inheritance(d: &Value) -> Result<Option<Inheritance<'_>>> {
+    if d["inheritance"].is_null() {
+        return Ok(None);
+    }
+    Ok(Some(Inheritance {
+        colon: term!(:),
+        identifier: identifier(d, "inheritance")?,
+        span: Span::default(),
+    }))
+}
+
+fn import_interface_member(m: &Value) -> Result<InterfaceMember<'_>> {
+    let attributes = import_ext_attrs(m)?;
+    let kind = string(m, "type")?;
+    let special = m["special"].as_str().unwrap_or("");
+    let member = match kind {
+        "const" => {
+            let (const_type, identifier, const_value) = import_const(m)?;
+            InterfaceMember::Const(ConstMember {
+                attributes,
+                const_: term!(const),
+                const_type,
+                identifier,
+                assign: term!(=),
+                const_value,
+                semi_colon: term!(;),
+                span: Span::default(),
+            })
+        }
+        "attribute" => InterfaceMember::Attribute(AttributeInterfaceMember {
+            attributes,
+            modifier: match special {
+                "" => None,
+                "stringifier" => Some(StringifierOrInheritOrStatic::Stringifier(term!(
+                    stringifier
+                ))),
+                "inherit" => Some(StringifierOrInheritOrStatic::Inherit(term!(inherit))),
+                "static" => Some(StringifierOrInheritOrStatic::Static(term!(static))),
+                _ => return Err(ImportError::unknown("attribute special", special)),
+            },
+            readonly: import_readonly(m),
+            attribute: term!(attribute),
+            type_: import_attributed_type(&m["idlType"])?,
+            identifier: identifier(m, "name")?,
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "constructor" => InterfaceMember::Constructor(ConstructorInterfaceMember {
+            attributes,
+            constructor: term!(constructor),
+            args: parenthesized(import_arguments(m)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "jsonifier" => InterfaceMember::Jsonifier(JsonifierMember {
+            attributes,
+            jsonifier: term!(jsonifier),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "serializer" => InterfaceMember::Serializer(import_serializer(m, attributes)?),
+        "operation" if special == "stringifier" && m["idlType"].is_null() => {
+            InterfaceMember::Stringifier(import_stringifier(attributes))
+        }
+        "operation" => {
+            let (modifier, special) = match special {
+                "" => (None, None),
+                "stringifier" => (
+                    Some(StringifierOrStatic::Stringifier(term!(stringifier))),
+                    None,
+                ),
+                "static" => (Some(StringifierOrStatic::Static(term!(static))), None),
+                "getter" => (None, Some(Special::Getter(term!(getter)))),
+                "setter" => (None, Some(Special::Setter(term!(setter)))),
+                "deleter" => (None, Some(Special::Deleter(term!(deleter)))),
+                "legacycaller" => (None, Some(Special::LegacyCaller(term!(legacycaller)))),
+                _ => return Err(ImportError::unknown("operation special", special)),
+            };
+            InterfaceMember::Operation(OperationInterfaceMember {
+                attributes,
+                modifier,
+                special,
+                return_type: import_return_type(&m["idlType"])?,
+                identifier: import_operation_name(m),
+                args: parenthesized(import_arguments(m)?),
+                semi_colon: term!(;),
+                span: Span::default(),
+            })
+        }
+        "iterable" | "async iterable" | "async_iterable" => {
+            let types = import_all(list(m, "idlType")?, import_attributed_type)?;
+            let args = import_arguments(m)?;
+            let args = if args.list.is_empty() {
+                None
+            } else {
+                Some(parenthesized(args))
+            };
+            let async_iterable = if kind == "async_iterable" {
+                AsyncIterableKeyword::AsyncIterable(term!(async_iterable))
+            } else {
+                AsyncIterableKeyword::Legacy((term!(async), term!(iterable)))
+            };
+            let async_ = kind != "iterable" || flag(m, "async");
+            let mut types = types.into_iter();
+            match (types.next(), types.next(), types.next(), async_) {
+                (Some(value), None, None, false) => InterfaceMember::Iterable(
+                    IterableInterfaceMember::Single(SingleTypedIterable {
+                        attributes,
+                        iterable: term!(iterable),
+                        generics: generics(value),
+                        semi_colon: term!(;),
+                        span: Span::default(),
+                    }),
+                ),
+                (Some(key), Some(value), None, false) => InterfaceMember::Iterable(
+                    IterableInterfaceMember::Double(DoubleTypedIterable {
+                        attributes,
+                        iterable: term!(iterable),
+                        generics: generics((key, term!(,), value)),
+                        semi_colon: term!(;),
+                        span: Span::default(),
+                    }),
+                ),
+                (Some(value), None, None, true) => InterfaceMember::AsyncIterable(
+                    AsyncIterableInterfaceMember::Single(SingleTypedAsyncIterable {
+                        attributes,
+                        async_iterable,
+                        generics: generics(value),
+                        args,
+                        semi_colon: term!(;),
+                        span: Span::default(),
+                    }),
+                ),
+                (Some(key), Some(value), None, true) => InterfaceMember::AsyncIterable(
+                    AsyncIterableInterfaceMember::Double(DoubleTypedAsyncIterable {
+                        attributes,
+                        async_iterable,
+                        generics: generics((key, term!(,), value)),
+                        args,
+                        semi_colon: term!(;),
+                        span: Span::default(),
+                    }),
+                ),
+                _ => return Err(ImportError::expected("one or two types", "idlType")),
+            }
+        }
+        "maplike" => {
+            let mut types = import_all(list(m, "idlType")?, import_attributed_type)?;
+            if types.len() != 2 {
+                return Err(ImportError::expected("two types", "idlType"));
+            }
+            let value = types.pop().unwrap();
+            let key = types.pop().unwrap();
+            InterfaceMember::Maplike(MaplikeInterfaceMember {
+                attributes,
+                readonly: import_readonly(m),
+                maplike: term!(maplike),
+                generics: generics((key, term!(,), value)),
+                semi_colon: term!(;),
+                span: Span::default(),
+            })
+        }
+        "setlike" => {
+            let mut types = import_all(list(m, "idlType")?, import_attributed_type)?;
+            if types.len() != 1 {
+                return Err(ImportError::expected("one type", "idlType"));
+            }
+            InterfaceMember::Setlike(SetlikeInterfaceMember {
+                attributes,
+                readonly: import_readonly(m),
+                setlike: term!(setlike),
+                generics: generics(types.pop().unwrap()),
+                semi_colon: term!(;),
+                span: Span::default(),
+            })
+        }
+        _ => return Err(ImportError::unknown("interface member type", kind)),
+    };
+    Ok(member)
+}
+
+fn import_mixin_member(m: &Value) -> Result<MixinMember<'_>> {
+    let attributes = import_ext_attrs(m)?;
+    let kind = string(m, "type")?;
+    let stringifier = match m["special"].as_str().unwrap_or("") {
+        "" => None,
+        "stringifier" => Some(term!(stringifier)),
+        special => return Err(ImportError::unknown("mixin member special", special)),
+    };
+    let member = match kind {
+        "const" => {
+            let (const_type, identifier, const_value) = import_const(m)?;
+            MixinMember::Const(ConstMember {
+                attributes,
+                const_: term!(const),
+                const_type,
+                identifier,
+                assign: term!(=),
+                const_value,
+                semi_colon: term!(;),
+                span: Span::default(),
+            })
+        }
+        "operation" if stringifier.is_some() && m["idlType"].is_null() => {
+            MixinMember::Stringifier(import_stringifier(attributes))
+        }
+        "operation" => MixinMember::Operation(OperationMixinMember {
+            attributes,
+            stringifier,
+            return_type: import_return_type(&m["idlType"])?,
+            identifier: import_operation_name(m),
+            args: parenthesized(import_arguments(m)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "attribute" => MixinMember::Attribute(AttributeMixinMember {
+            attributes,
+            stringifier,
+            readonly: import_readonly(m),
+            attribute: term!(attribute),
+            type_: import_attributed_type(&m["idlType"])?,
+            identifier: identifier(m, "name")?,
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        _ => return Err(ImportError::unknown("mixin member type", kind)),
+    };
+    Ok(member)
+}
+
+fn import_namespace_member(m: &Value) -> Result<NamespaceMember<'_>> {
+    let attributes = import_ext_attrs(m)?;
+    let kind = string(m, "type")?;
+    let member = match kind {
+        "const" => {
+            let (const_type, identifier, const_value) = import_const(m)?;
+            NamespaceMember::Const(ConstNamespaceMember {
+                attributes,
+                const_: term!(const),
+                const_type,
+                identifier,
+                assign: term!(=),
+                const_value,
+                semi_colon: term!(;),
+                span: Span::default(),
+            })
+        }
+        "operation" => NamespaceMember::Operation(OperationNamespaceMember {
+            attributes,
+            return_type: import_return_type(&m["idlType"])?,
+            identifier: import_operation_name(m),
+            args: parenthesized(import_arguments(m)?),
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        "attribute" => NamespaceMember::Attribute(AttributeNamespaceMember {
+            attributes,
+            readonly: term!(readonly),
+            attribute: term!(attribute),
+            type_: import_attributed_type(&m["idlType"])?,
+            identifier: identifier(m, "name")?,
+            semi_colon: term!(;),
+            span: Span::default(),
+        }),
+        _ => return Err(ImportError::unknown("namespace member type", kind)),
+    };
+    Ok(member)
+}
+
+fn import_dictionary_member(m: &Value) -> Result<DictionaryMember<'_>> {
+    Ok(DictionaryMember {
+        attributes: import_ext_attrs(m)?,
+        required: if flag(m, "required") {
+            Some(term!(required))
+        } else {
+            None
+        },
+        type_: import_type(&m["idlType"])?,
+        identifier: identifier(m, "name")?,
+        default: import_default(&m["default"])?,
+        semi_colon: term!(;),
+        span: Span::default(),
+    })
+}
+
+fn import_const(m: &Value) -> Result<(ConstType<'_>, Identifier<'_>, ConstValue<'_>)> {
+    let const_type = match import_non_any_type(&m["idlType"])? {
+        NonAnyType::Integer(t) => ConstType::Integer(t),
+        NonAnyType::FloatingPoint(t) => ConstType::FloatingPoint(t),
+        NonAnyType::Boolean(t) => ConstType::Boolean(t),
+        NonAnyType::Byte(t) => ConstType::Byte(t),
+        NonAnyType::Octet(t) => ConstType::Octet(t),
+        NonAnyType::Bigint(t) => ConstType::Bigint(t),
+        NonAnyType::Identifier(t) => ConstType::Identifier(t),
+        _ => return Err(ImportError::expected("a const type", "idlType")),
+    };
+    Ok((
+        const_type,
+        identifier(m, "name")?,
+        import_const_value(&m["value"])?,
+    ))
+}
+
+fn import_stringifier(attributes: Option<ExtendedAttributeList>) -> StringifierMember {
+    StringifierMember {
+        attributes,
+        stringifier: term!(stringifier),
+        semi_colon: term!(;),
+        span: Span::default(),
+    }
+}
+
+fn import_serializer<'a>(
+    m: &'a Value,
+    attributes: Option<ExtendedAttributeList<'a>>,
+) -> Result<SerializerMember<'a>> {
+    let members = || {
+        import_all(list(m, "members")?, |member| {
+            member
+                .as_str()
+                .map(Identifier::new)
+                .ok_or_else(|| ImportError::expected("a string", "members"))
+        })
+        .map(Punctuated::new)
+    };
+    let pattern = if flag(m, "patternMap") {
+        Some(SerializationPattern::Map(braced(members()?)))
+    } else if flag(m, "patternList") {
+        Some(SerializationPattern::List(bracketed(members()?)))
+    } else if m["name"].is_string() {
+        Some(SerializationPattern::Identifier(identifier(m, "name")?))
+    } else {
+        None
+    };
+    Ok(SerializerMember {
+        attributes,
+        serializer: term!(serializer),
+        pattern: pattern.map(|pattern| (term!(=), pattern)),
+        semi_colon: term!(;),
+        span: Span::default(),
+    })
+}
+
+fn import_readonly(m: &Value) -> Option<term!(readonly)> {
+    if flag(m, "readonly") {
+        Some(term!(readonly))
+    } else {
+        None
+    }
+}
+
+fn import_operation_name(m: &Value) -> Option<Identifier<'_>> {
+    m["name"]
+        .as_str()
+        .filter(|name| !name.is_empty())
+        .map(Identifier::new)
+}
+
+fn import_ext_attrs(value: &Value) -> Result<Option<ExtendedAttributeList<'_>>> {
+    let attributes = import_all(list(value, "extAttrs")?, import_ext_attr)?;
+    if attributes.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(bracketed(Punctuated::new(attributes))))
+}
+
+fn import_ext_attr(a: &Value) -> Result<ExtendedAttribute<'_>> {
+    let name = identifier(a, "name")?;
+    let args = list(a, "arguments")?;
+    let rhs = &a["rhs"];
+    if rhs.is_null() {
+        if args.is_empty() {
+            return Ok(ExtendedAttribute::NoArgs(ExtendedAttributeNoArgs::new(
+                name,
+            )));
+        }
+        return Ok(ExtendedAttribute::ArgList(ExtendedAttributeArgList {
+            identifier: name,
+            args: parenthesized(import_argument_list(args)?),
+            span: Span::default(),
+        }));
+    }
+
+    let kind = string(rhs, "type")?;
+    let attribute = match kind {
+        "identifier" if !args.is_empty() => {
+            ExtendedAttribute::NamedArgList(ExtendedAttributeNamedArgList {
+                lhs_identifier: name,
+                assign: term!(=),
+                rhs_identifier: identifier(rhs, "value")?,
+                args: parenthesized(import_argument_list(args)?),
+                span: Span::default(),
+            })
+        }
+        "identifier" => ExtendedAttribute::Ident(ExtendedAttributeIdent {
+            lhs_identifier: name,
+            assign: term!(=),
+            rhs: IdentifierOrString::Identifier(identifier(rhs, "value")?),
+            span: Span::default(),
+        }),
+        "string" => ExtendedAttribute::Ident(ExtendedAttributeIdent {
+            lhs_identifier: name,
+            assign: term!(=),
+            rhs: IdentifierOrString::String(StringLit::new(unquote(string(rhs, "value")?))),
+            span: Span::default(),
+        }),
+        "string-list" => ExtendedAttribute::StringList(ExtendedAttributeStringList {
+            identifier: name,
+            assign: term!(=),
+            list: parenthesized(Punctuated::new(import_all(list(rhs, "value")?, |item| {
+                Ok(StringLit::new(unquote(string(item, "value")?)))
+            })?)),
+            span: Span::default(),
+        }),
+        "integer" => ExtendedAttribute::Integer(ExtendedAttributeInteger {
+            lhs_identifier: name,
+            assign: term!(=),
+            rhs: import_integer(rhs)?,
+            span: Span::default(),
+        }),
+        "integer-list" => ExtendedAttribute::IntegerList(ExtendedAttributeIntegerList {
+            identifier: name,
+            assign: term!(=),
+            list: parenthesized(Punctuated::new(import_all(
+                list(rhs, "value")?,
+                import_integer,
+            )?)),
+            span: Span::default(),
+        }),
+        "decimal" => ExtendedAttribute::Tokens(ExtendedAttributeTokens {
+            identifier: name,
+            tokens: vec![
+                ExtendedAttributeToken::new("="),
+                ExtendedAttributeToken::new(string(rhs, "value")?),
+            ],
+            span: Span::default(),
+        }),
+        "decimal-list" => {
+            let mut tokens = vec![
+                ExtendedAttributeToken::new("="),
+                ExtendedAttributeToken::new("("),
+            ];
+            for (i, item) in list(rhs, "value")?.iter().enumerate() {
+                if i > 0 {
+                    tokens.push(ExtendedAttributeToken::new(","));
+                }
+                tokens.push(ExtendedAttributeToken::new(string(item, "value")?));
+            }
+            tokens.push(ExtendedAttributeToken::new(")"));
+            ExtendedAttribute::Tokens(ExtendedAttributeTokens {
+                identifier: name,
+                tokens,
+                span: Span::default(),
+            })
+        }
+        "tokens" => ExtendedAttribute::Tokens(ExtendedAttributeTokens {
+            identifier: name,
+            tokens: list(rhs, "value")?
+                .iter()
+                .map(|token| {
+                    let token = token
+                        .as_str()
+                        .ok_or_else(|| ImportError::expected("a string", "value"))?;
+                    Ok(ExtendedAttributeToken::new(token))
+                })
+                .collect::<Result<_>>()?,
+            span: Span::default(),
+        }),
+        "identifier-list" => ExtendedAttribute::IdentList(ExtendedAttributeIdentList {
+            identifier: name,
+            assign: term!(=),
+            list: parenthesized(Punctuated::new(import_all(list(rhs, "value")?, |item| {
+                identifier(item, "value")
+            })?)),
+            span: Span::default(),
+        }),
+        "*" => ExtendedAttribute::Wildcard(ExtendedAttributeWildCard {
+            lhs_identifier: name,
+            assign: term!(=),
+            rhs: term!(*),
+            span: Span::default(),
+        }),
+        _ => return Err(ImportError::unknown("extended attribute value type", kind)),
+    };
+    Ok(attribute)
+}
+
+/// Older versions of webidl2.js keep the quotes of strings
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn import_integer(value: &Value) -> Result<IntegerLit<'_>> {
+    parse_all(string(value, "value")?).ok_or_else(|| ImportError::expected("an integer", "value"))
+}
+
+fn import_arguments(value: &Value) -> Result<ArgumentList<'_>> {
+    import_argument_list(list(value, "arguments")?)
+}
+
+fn import_argument_list(args: &[Value]) -> Result<ArgumentList<'_>> {
+    Ok(Punctuated::new(import_all(args, import_argument)?))
+}
+
+fn import_argument(a: &Value) -> Result<Argument<'_>> {
+    let attributes = import_ext_attrs(a)?;
+    let identifier = identifier(a, "name")?;
+    if flag(a, "variadic") {
+        return Ok(Argument::Variadic(VariadicArgument {
+            attributes,
+            type_: import_type(&a["idlType"])?,
+            ellipsis: term!(...),
+            identifier,
+            span: Span::default(),
+        }));
+    }
+    Ok(Argument::Single(SingleArgument {
+        attributes,
+        optional: if flag(a, "optional") {
+            Some(term!(optional))
+        } else {
+            None
+        },
+        type_: import_attributed_type(&a["idlType"])?,
+        identifier,
+        default: import_default(&a["default"])?,
+        span: Span::default(),
+    }))
+}
+
+fn import_default(value: &Value) -> Result<Option<Default<'_>>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let value = match string(value, "type")? {
+        "string" => DefaultValue::String(StringLit::new(string(value, "value")?)),
+        "sequence" => DefaultValue::EmptyArray(EmptyArrayLit::default()),
+        "dictionary" => DefaultValue::EmptyDictionary(EmptyDictionaryLit::default()),
+        _ => match import_const_value(value)? {
+            ConstValue::Boolean(b) => DefaultValue::Boolean(b),
+            ConstValue::Float(f) => DefaultValue::Float(f),
+            ConstValue::Integer(i) => DefaultValue::Integer(i),
+            ConstValue::Null(null) => DefaultValue::Null(null),
+        },
+    };
+    Ok(Some(Default {
+        assign: term!(=),
+        value,
+        span: Span::default(),
+    }))
+}
+
+fn import_const_value(value: &Value) -> Result<ConstValue<'_>> {
+    let kind = string(value, "type")?;
+    let value = match kind {
+        "boolean" => {
+            let b = value["value"]
+                .as_bool()
+                .ok_or_else(|| ImportError::expected("a boolean", "value"))?;
+            ConstValue::Boolean(BooleanLit::new(b))
+        }
+        "null" => ConstValue::Null(term!(null)),
+        "number" => {
+            let text = string(value, "value")?;
+            if let Some(float) = parse_all(text) {
+                ConstValue::Float(float)
+            } else if let Some(integer) = parse_all(text) {
+                ConstValue::Integer(integer)
+            } else {
+                return Err(ImportError::expected("a number", "value"));
+            }
+        }
+        "Infinity" if flag(value, "negative") => {
+            ConstValue::Float(FloatLit::NegInfinity(term!(-Infinity)))
+        }
+        "Infinity" => ConstValue::Float(FloatLit::Infinity(term!(Infinity))),
+        "NaN" => ConstValue::Float(FloatLit::NaN(term!(NaN))),
+        _ => return Err(ImportError::unknown("value type", kind)),
+    };
+    Ok(value)
+}
+
+fn import_attributed_type(t: &Value) -> Result<AttributedType<'_>> {
+    Ok(AttributedType {
+        attributes: import_ext_attrs(t)?,
+        type_: import_type(t)?,
+        span: Span::default(),
+    })
+}
+
+fn import_return_type(t: &Value) -> Result<ReturnType<'_>> {
+    if t["idlType"] == "undefined" && !flag(t, "union") {
+        return Ok(ReturnType::Undefined(term!(undefined)));
+    }
+    import_type(t).map(ReturnType::Type)
+}
+
+fn import_type(t: &Value) -> Result<Type<'_>> {
+    if flag(t, "union") {
+        return import_union_type(t).map(Type::Union);
+    }
+    if t["idlType"] == "any" {
+        return Ok(Type::Single(SingleType::Any(term!(any))));
+    }
+    import_non_any_type(t).map(|t| Type::Single(SingleType::NonAny(t)))
+}
+
+fn import_union_type(t: &Value) -> Result<MayBeNull<UnionType<'_>>> {
+    let members = import_all(list(t, "idlType")?, |member| {
+        if flag(member, "union") {
+            return import_union_type(member).map(UnionMemberType::Union);
+        }
+        Ok(UnionMemberType::Single(AttributedNonAnyType {
+            attributes: import_ext_attrs(member)?,
+            type_: import_non_any_type(member)?,
+            span: Span::default(),
+        }))
+    })?;
+    Ok(MayBeNull {
+        type_: parenthesized(Punctuated::new(members)),
+        q_mark: import_q_mark(t),
+        span: Span::default(),
+    })
+}
+
+fn import_q_mark(t: &Value) -> Option<term!(?)> {
+    if flag(t, "nullable") {
+        Some(term!(?))
+    } else {
+        None
+    }
+}
+
+fn import_non_any_type(t: &Value) -> Result<NonAnyType<'_>> {
+    let q_mark = import_q_mark(t);
+    let generic = t["generic"].as_str().unwrap_or("");
+    let types = || list(t, "idlType");
+    let single = || match types()? {
+        [type_] => Ok(type_),
+        _ => Err(ImportError::expected("one type", "idlType")),
+    };
+    let type_ = match generic {
+        "" => {
+            let name = string(t, "idlType")?;
+            let type_ = parse_all(name).ok_or_else(|| ImportError::unknown("type", name))?;
+            return Ok(with_q_mark(type_, q_mark));
+        }
+        "sequence" => NonAnyType::Sequence(MayBeNull {
+            type_: SequenceType {
+                sequence: term!(sequence),
+                generics: generics(Box::new(import_type(single()?)?)),
+                span: Span::default(),
+            },
+            q_mark,
+            span: Span::default(),
+        }),
+        "FrozenArray" => NonAnyType::FrozenArrayType(MayBeNull {
+            type_: FrozenArrayType {
+                frozen_array: term!(FrozenArray),
+                generics: generics(Box::new(import_type(single()?)?)),
+                span: Span::default(),
+            },
+            q_mark,
+            span: Span::default(),
+        }),
+        "ObservableArray" => NonAnyType::ObservableArrayType(MayBeNull {
+            type_: ObservableArrayType {
+                observable_array: term!(ObservableArray),
+                generics: generics(Box::new(import_type(single()?)?)),
+                span: Span::default(),
+            },
+            q_mark,
+            span: Span::default(),
+        }),
+        "Promise" => NonAnyType::Promise(PromiseType {
+            promise: term!(Promise),
+            generics: generics(Box::new(import_return_type(single()?)?)),
+            span: Span::default(),
+        }),
+        "record" => {
+            let (key, value) = match types()? {
+                [key, value] => (key, value),
+                _ => return Err(ImportError::expected("two types", "idlType")),
+            };
+            let key = match key["idlType"].as_str() {
+                Some("ByteString") if !flag(key, "nullable") => {
+                    RecordKeyType::Byte(term!(ByteString))
+                }
+                Some("DOMString") if !flag(key, "nullable") => RecordKeyType::DOM(term!(DOMString)),
+                Some("USVString") if !flag(key, "nullable") => RecordKeyType::USV(term!(USVString)),
+                _ => RecordKeyType::NonAny(import_non_any_type(key)?),
+            };
+            NonAnyType::RecordType(MayBeNull {
+                type_: RecordType {
+                    record: term!(record),
+                    generics: generics((Box::new(key), term!(,), Box::new(import_type(value)?))),
+                    span: Span::default(),
+                },
+                q_mark,
+                span: Span::default(),
+            })
+        }
+        _ => return Err(ImportError::unknown("generic type", generic)),
+    };
+    Ok(type_)
+}
+
+/// Makes a type parsed from its name nullable
+fn with_q_mark(type_: NonAnyType, q_mark: Option<term!(?)>) -> NonAnyType {
+    macro_rules! with_q_mark {
+        ($($variant:ident)*) => {
+            match type_ {
+                $(NonAnyType::$variant(t) => NonAnyType::$variant(MayBeNull { q_mark, ..t }),)*
+                NonAnyType::Promise(t) => NonAnyType::Promise(t),
+            }
+        };
+    }
+    with_q_mark!(
+        Integer FloatingPoint Boolean Byte Octet Bigint ByteString DOMString USVString Sequence
+        Object Symbol Error ArrayBuffer DataView Int8Array Int16Array Int32Array Uint8Array
+        Uint16Array Uint32Array Uint8ClampedArray Float16Array Float32Array Float64Array
+        BigInt64Array BigUint64Array ArrayBufferView BufferSource FrozenArrayType
+        ObservableArrayType RecordType Identifier
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(raw: &str) {
+        let parsed = crate::parse(raw).unwrap();
+        let json = to_json(&parsed);
+        assert_spanless_eq!(from_json(&json).unwrap(), parsed, "{}", json);
+    }
+
+    #[test]
+    fn should_export_interface_members() {
+        let parsed = crate::parse(
+            "interface Foo {
+                const unsigned long long MAX = 0xFF;
+                [SameObject] static readonly attribute record<DOMString, long?> map;
+                getter ([Clamp] octet or sequence<Node>)? item(optional long index = -1, Foo... rest);
+                stringifier;
+                async iterable<DOMString>(optional Options options = {});
+            };",
+        )
+        .unwrap();
+        let json = to_json(&parsed);
+        let members = &json[0]["members"];
+
+        assert_eq!(
+            members[0],
+            json!({
+                "type": "const",
+                "name": "MAX",
+                "idlType": {
+                    "type": "const-type",
+                    "extAttrs": [],
+                    "generic": "",
+                    "nullable": false,
+                    "union": false,
+                    "idlType": "unsigned long long",
+                },
+                "extAttrs": [],
+                "value": { "type": "number", "value": "0xFF" },
+            })
+        );
+
+        assert_eq!(members[1]["special"], "static");
+        assert_eq!(members[1]["readonly"], true);
+        assert_eq!(members[1]["extAttrs"][0]["name"], "SameObject");
+        assert_eq!(members[1]["idlType"]["generic"], "record");
+        assert_eq!(members[1]["idlType"]["idlType"][1]["nullable"], true);
+
+        let item = &members[2];
+        assert_eq!(item["special"], "getter");
+        assert_eq!(item["idlType"]["union"], true);
+        assert_eq!(item["idlType"]["nullable"], true);
+        assert_eq!(
+            item["idlType"]["idlType"][0]["extAttrs"][0]["name"],
+            "Clamp"
+        );
+        assert_eq!(item["idlType"]["idlType"][1]["generic"], "sequence");
+        assert_eq!(
+            item["idlType"]["idlType"][1]["idlType"][0]["idlType"],
+            "Node"
+        );
+        assert_eq!(item["arguments"][0]["optional"], true);
+        assert_eq!(
+            item["arguments"][0]["default"],
+            json!({ "type": "number", "value": "-1" })
+        );
+        assert_eq!(item["arguments"][1]["variadic"], true);
+
+        assert_eq!(members[3]["special"], "stringifier");
+        assert_eq!(members[3]["idlType"], Value::Null);
+
+        assert_eq!(members[4]["type"], "iterable");
+        assert_eq!(members[4]["async"], true);
+        let (_, current) = InterfaceMember::parse("async_iterable<long>(long a);").unwrap();
+        assert_eq!(interface_member(&current)["type"], "async_iterable");
+        assert_eq!(
+            members[4]["arguments"][0]["default"],
+            json!({ "type": "dictionary" })
+        );
+    }
+
+    #[test]
+    fn should_round_trip_definitions() {
+        round_trip(
+            r#"
+            [Exposed=(Window,Worker), LegacyFactoryFunction=Image(unsigned long width), Global=*]
+            interface Image : Element {
+                constructor(DOMString src);
+                attribute Promise<undefined> ready;
+                readonly maplike<DOMString, any>;
+                setlike<FrozenArray<long>>;
+                iterable<long, double?>;
+                async_iterable<DOMString>(optional long start);
+                legacycaller any (DOMString... values);
+            };
+            partial interface Image { [Reflect="alt"] attribute DOMString alt; };
+            interface mixin Mixin { stringifier attribute USVString href; stringifier; };
+            partial interface mixin Mixin { const boolean YES = true; };
+            callback interface Listener { undefined handle(Event event); };
+            namespace Console { undefined log(any... data); readonly attribute long level; };
+            partial namespace Console { const double LIMIT = -Infinity; };
+            dictionary Options : Base { required long count; DOMString name = "foo"; sequence<long> list = []; };
+            partial dictionary Options { boolean? flag = null; };
+            enum Mode { "open", "closed" };
+            typedef [EnforceRange] unsigned short Short;
+            callback Callback = Promise<any> (optional double x = NaN);
+            Image includes Mixin;
+            Window implements Image;
+            [Pref=("a", "b"), Sizes=(1, 2), Priority=3, Version=1.5, Ratios=(1.5, 2.5), Values=(a, "b")]
+            interface Prefs {};
+            "#,
+        );
+    }
+
+    #[test]
+    fn should_export_extended_attribute_values() {
+        let parsed = crate::parse(
+            r#"[Pref=("a", "b"), Sizes=(1, 0x2), Priority=3, Version=1.5, Ratios=(1.5, 2.5), Values=(a, "b")]
+            interface Prefs {};"#,
+        )
+        .unwrap();
+        let json = to_json(&parsed);
+        let rhs: Vec<_> = json[0]["extAttrs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|attribute| &attribute["rhs"])
+            .collect();
+
+        assert_eq!(
+            rhs[0],
+            &json!({ "type": "string-list", "value": [{ "value": "a" }, { "value": "b" }] })
+        );
+        assert_eq!(
+            rhs[1],
+            &json!({ "type": "integer-list", "value": [{ "value": "1" }, { "value": "0x2" }] })
+        );
+        assert_eq!(rhs[2], &json!({ "type": "integer", "value": "3" }));
+        assert_eq!(rhs[3], &json!({ "type": "decimal", "value": "1.5" }));
+        assert_eq!(rhs[4]["type"], "decimal-list");
+        assert_eq!(
+            rhs[5],
+            &json!({ "type": "tokens", "value": ["=", "(", "a", ",", "\"b\"", ")"] })
+        );
+    }
+
+    #[test]
+    fn should_round_trip_dialect_members() {
+        let options = ParseOptions::new().dialect(Dialect::Gecko);
+        let parsed = crate::parse_with_options(
+            "interface Foo {
+                [Default] jsonifier;
+                serializer;
+                serializer = { attribute, inherit };
+                serializer = [name, getter];
+                serializer = name;
+            };",
+            options,
+        )
+        .unwrap();
+        let json = to_json(&parsed);
+        let members = &json[0]["members"];
+        assert_eq!(members[0]["type"], "jsonifier");
+        assert_eq!(members[2]["patternMap"], true);
+        assert_eq!(members[2]["members"], json!(["attribute", "inherit"]));
+        assert_eq!(members[3]["patternList"], true);
+        assert_eq!(members[4]["name"], "name");
+        assert_spanless_eq!(from_json(&json).unwrap(), parsed, "{}", json);
+    }
+
+    #[test]
+    fn should_report_unknown_nodes() {
+        let json =
+            json!([{ "type": "interface", "name": "Foo", "members": [{ "type": "field" }] }]);
+        assert_eq!(
+            from_json(&json).unwrap_err().to_string(),
+            "unknown interface member type `field`"
+        );
+
+        let json =
+            json!([{ "type": "typedef", "name": "T", "idlType": { "idlType": "not a type" } }]);
+        assert_eq!(
+            from_json(&json).unwrap_err().to_string(),
+            "unknown type `not a type`"
+        );
+
+        let json = json!([{ "type": "eof", "value": "" }]);
+        assert_eq!(from_json(&json).unwrap(), vec![]);
+    }
+}
@@ -1,31 +1,41 @@
 use crate::IResult;
 
+/// Skips whitespace, line comments and block comments
+///
+/// Written out by hand, as it runs before and after every token.
 pub(crate) fn sp(input: &str) -> IResult<&str, &str> {
-    recognize!(
-        input,
-        many0!(alt!(
-            // ignores line comments
-            do_parse!(tag!("//") >> take_until!("\n") >> char!('\n') >> (()))
-            |
-            // ignores whitespace
-            map!(take_while1!(|c| c == '\t' || c == '\n' || c == '\r' || c == ' '), |_| ())
-            |
-            // ignores block comments
-            do_parse!(tag!("/*") >> take_until!("*/") >> tag!("*/") >> (()))
-        ))
-    )
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start_matches(['\t', '\n', '\r', ' ']);
+        if let Some(comment) = rest.strip_prefix("//") {
+            rest = &comment[comment.find('\n').unwrap_or(comment.len())..];
+        } else if let Some(end) = rest.strip_prefix("/*").and_then(|c| c.find("*/")) {
+            rest = &rest[2 + end + 2..];
+        } else {
+            break;
+        }
+    }
+    Ok((rest, &input[..input.len() - rest.len()]))
 }
 
 /// ws! also ignores line & block comments
 macro_rules! ws (
     ($i:expr, $($args:tt)*) => ({
         use $crate::whitespace::sp;
+        use $crate::nom::lib::std::result::Result::*;
 
-        do_parse!($i,
-            sp >>
-            s: $($args)* >>
-            sp >>
-            (s)
-        )
+        match sp($i) {
+            Err(e) => Err(e),
+            Ok((start, _)) => match do_parse!(start, s: $($args)* >> (s)) {
+                Err(e) => Err(e),
+                Ok((i, s)) => match sp(i) {
+                    Err(e) => Err(e),
+                    Ok((rest, _)) => {
+                        $crate::state::record_token(start, i, rest);
+                        Ok((rest, s))
+                    }
+                },
+            },
+        }
     });
 );
@@ -3,6 +3,8 @@ extern crate weedle;
 use std::fs;
 use std::io::Read;
 
+use weedle::print::ToWebIdl;
+use weedle::span::SpanlessEq;
 use weedle::*;
 
 fn read_file(path: &str) -> String {
@@ -20,6 +22,15 @@ pub fn should_parse_dom_webidl() {
     assert_eq!(parsed.len(), 62);
 }
 
+#[test]
+fn should_recover_nothing_in_dom_webidl() {
+    let content = read_file("./tests/defs/dom.webidl");
+    let (parsed, errors) = weedle::parse_recovering(&content);
+
+    assert!(errors.is_empty());
+    assert_eq!(parsed, weedle::parse(&content).unwrap());
+}
+
 #[test]
 fn should_parse_html_webidl() {
     let content = read_file("./tests/defs/html.webidl");
@@ -28,6 +39,17 @@ fn should_parse_html_webidl() {
     assert_eq!(parsed.len(), 325);
 }
 
+#[test]
+fn should_own_html_webidl_after_the_source_is_dropped() {
+    let owned: Definitions<'static> = {
+        let content = read_file("./tests/defs/html.webidl");
+        weedle::parse(&content).unwrap().into_owned()
+    };
+    let content = read_file("./tests/defs/html.webidl");
+
+    assert_eq!(owned, weedle::parse(&content).unwrap());
+}
+
 #[test]
 fn should_parse_mediacapture_streams_webidl() {
     let content = read_file("./tests/defs/mediacapture-streams.webidl");
@@ -64,7 +86,7 @@ fn interface_constructor() {
     match definition {
         Definition::Interface(mut interface) => {
             assert!(interface.attributes.is_none());
-            assert_eq!(interface.interface, term!(interface));
+            assert!(interface.interface.spanless_eq(&term!(interface)));
             assert_eq!(interface.identifier.0, "InterfaceWithConstructor");
             assert_eq!(interface.inheritance, None);
 
@@ -105,7 +127,7 @@ fn interface_constructor() {
                         _ => unreachable!(),
                     };
 
-                    assert_eq!(constructor.constructor, term::Constructor);
+                    assert!(constructor.constructor.spanless_eq(&term::Constructor));
                 }
                 _ => unreachable!(),
             }
@@ -113,3 +135,585 @@ fn interface_constructor() {
         _ => unreachable!(),
     }
 }
+
+#[test]
+fn should_round_trip_through_printer() {
+    use weedle::print::ToWebIdl;
+    use weedle::span::SpanlessEq;
+
+    for name in &[
+        "bigint",
+        "dom",
+        "html",
+        "interface-constructor",
+        "mediacapture-streams",
+        "observable-array",
+        "streams",
+        "webgpu",
+    ] {
+        let content = read_file(&format!("./tests/defs/{}.webidl", name));
+        let parsed = weedle::parse(&content).unwrap();
+        let printed = parsed.to_webidl();
+
+        let reparsed = weedle::parse(&printed).unwrap();
+        assert!(reparsed.spanless_eq(&parsed), "{}", name);
+        assert_eq!(
+            identifiers(&printed, &reparsed),
+            identifiers(&content, &parsed),
+            "{}",
+            name
+        );
+        assert_eq!(reparsed.to_webidl(), printed);
+    }
+}
+
+/// The source text of every identifier in `parsed`, with the `_` of escaped ones
+fn identifiers<'s>(source: &'s str, parsed: &Definitions<'_>) -> Vec<&'s str> {
+    use weedle::common::Identifier;
+    use weedle::span::Spanned;
+    use weedle::visit::{Accept, Visit};
+
+    struct Identifiers<'s>(&'s str, Vec<&'s str>);
+
+    impl<'a, 's> Visit<'a> for Identifiers<'s> {
+        fn visit_identifier(&mut self, node: &'a Identifier<'a>) {
+            self.1.push(node.span().source_text(self.0));
+        }
+    }
+
+    let mut identifiers = Identifiers(source, Vec::new());
+    parsed.accept(&mut identifiers);
+    identifiers.1
+}
+
+#[test]
+fn should_reproduce_sources_with_trivia() {
+    for name in &[
+        "dom",
+        "html",
+        "interface-constructor",
+        "mediacapture-streams",
+        "observable-array",
+        "streams",
+        "webgpu",
+    ] {
+        let content = read_file(&format!("./tests/defs/{}.webidl", name));
+        let (parsed, end) = weedle::parse_with_trivia(&content).unwrap();
+
+        assert!(weedle::trivia::print(&parsed) + end == content, "{}", name);
+    }
+}
+
+#[test]
+fn should_format_idempotently() {
+    use weedle::format::{format, FormatOptions, MemberOrder};
+
+    for name in &[
+        "dom",
+        "html",
+        "interface-constructor",
+        "mediacapture-streams",
+        "observable-array",
+        "streams",
+        "webgpu",
+    ] {
+        let content = read_file(&format!("./tests/defs/{}.webidl", name));
+        let options = FormatOptions::default();
+        let formatted = format(&content, &options).unwrap();
+
+        assert!(
+            weedle::parse(&formatted)
+                .unwrap()
+                .spanless_eq(&weedle::parse(&content).unwrap()),
+            "{}",
+            name
+        );
+        assert!(
+            format(&formatted, &options).unwrap() == formatted,
+            "{}",
+            name
+        );
+
+        let options = FormatOptions {
+            member_order: MemberOrder::Sorted,
+            ..options
+        };
+        let sorted = format(&content, &options).unwrap();
+        assert!(format(&sorted, &options).unwrap() == sorted, "{}", name);
+    }
+}
+
+#[test]
+fn should_keep_identifiers_when_formatting_html_webidl() {
+    use weedle::format::{format, FormatOptions};
+
+    let content = read_file("./tests/defs/html.webidl");
+    let formatted = format(&content, &FormatOptions::default()).unwrap();
+
+    assert_eq!(
+        identifiers(&formatted, &weedle::parse(&formatted).unwrap()),
+        identifiers(&content, &weedle::parse(&content).unwrap())
+    );
+}
+
+#[test]
+fn should_visit_every_operation_in_html_webidl() {
+    use weedle::interface::OperationInterfaceMember;
+    use weedle::visit::{Accept, Visit, Walk};
+
+    #[derive(Default)]
+    struct Count {
+        operations: usize,
+        arguments: usize,
+    }
+
+    impl<'a> Visit<'a> for Count {
+        fn visit_operation_interface_member(&mut self, node: &'a OperationInterfaceMember<'a>) {
+            self.operations += 1;
+            node.walk(self);
+        }
+
+        fn visit_argument(&mut self, node: &'a argument::Argument<'a>) {
+            self.arguments += 1;
+            node.walk(self);
+        }
+    }
+
+    let content = read_file("./tests/defs/html.webidl");
+    let parsed = weedle::parse(&content).unwrap();
+    let mut count = Count::default();
+    parsed.accept(&mut count);
+
+    let mut operations = 0;
+    let mut arguments = 0;
+    for definition in &parsed {
+        let members = match definition {
+            Definition::Interface(interface) => &interface.members.body,
+            Definition::PartialInterface(interface) => &interface.members.body,
+            Definition::CallbackInterface(interface) => &interface.members.body,
+            _ => continue,
+        };
+        for member in members {
+            if let interface::InterfaceMember::Operation(operation) = member {
+                operations += 1;
+                arguments += operation.args.body.list.len();
+            }
+        }
+    }
+    assert_eq!(count.operations, operations);
+    assert!(count.arguments >= arguments);
+}
+
+#[test]
+fn should_merge_document_across_dom_and_html_webidl() {
+    use weedle::semantic::{Model, OriginKind};
+
+    let dom_content = read_file("./tests/defs/dom.webidl");
+    let html_content = read_file("./tests/defs/html.webidl");
+    let dom = weedle::parse(&dom_content).unwrap();
+    let html = weedle::parse(&html_content).unwrap();
+    let model = Model::from_files(vec![&dom[..], &html[..]]);
+    let document = &model.interfaces["Document"];
+
+    assert_eq!(document.definition.unwrap().file, 0);
+    assert!(document.partials.iter().all(|partial| partial.file == 1));
+    let has = |name: &str, kind: OriginKind| {
+        document
+            .members
+            .iter()
+            .any(|m| m.member.identifier() == Some(name) && m.origin.kind == kind)
+    };
+    assert!(has("URL", OriginKind::Definition));
+    assert!(has("title", OriginKind::Partial));
+    assert!(has(
+        "children",
+        OriginKind::Mixin {
+            name: "ParentNode",
+            partial: false
+        }
+    ));
+}
+
+#[test]
+fn should_resolve_names_across_dom_and_html_webidl() {
+    use weedle::resolve::{resolve, DefinitionKind, ReferenceKind};
+
+    let dom_content = read_file("./tests/defs/dom.webidl");
+    let html_content = read_file("./tests/defs/html.webidl");
+    let dom = weedle::parse(&dom_content).unwrap();
+    let html = weedle::parse(&html_content).unwrap();
+    let resolution = resolve(vec![&dom[..], &html[..]]);
+
+    // html.webidl extends `NavigatorID` with `partial interface` although it is a mixin
+    let wrong: Vec<_> = resolution
+        .diagnostics
+        .iter()
+        .filter(|d| d.rule != "unknown-name")
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        wrong,
+        ["error[wrong-kind]: `NavigatorID` is an interface mixin, but an interface is expected here"]
+    );
+
+    // `Document` is defined in dom.webidl and extended in html.webidl
+    let partial = resolution
+        .references
+        .iter()
+        .find(|r| {
+            r.identifier.0 == "Document"
+                && r.kind == ReferenceKind::Partial(DefinitionKind::Interface)
+        })
+        .unwrap();
+    assert_eq!(partial.file, 1);
+    assert_eq!(partial.target.unwrap().file, 0);
+}
+
+#[test]
+fn should_expand_typedefs_in_html_webidl() {
+    use weedle::normalize::Normalizer;
+    use weedle::semantic::Model;
+
+    let content = read_file("./tests/defs/html.webidl");
+    let parsed = weedle::parse(&content).unwrap();
+    let model = Model::new(&parsed);
+    let normalizer = Normalizer::new(&model);
+
+    assert!(normalizer.cycles().is_empty());
+    let source = normalizer.expand_typedef("ImageBitmapSource").unwrap();
+    assert_eq!(
+        source.unwrap().to_string(),
+        "(HTMLImageElement or SVGImageElement or HTMLVideoElement or HTMLCanvasElement or \
+         ImageBitmap or OffscreenCanvas or Blob or ImageData)"
+    );
+    let handler = normalizer.expand_typedef("EventHandler").unwrap();
+    assert_eq!(handler.unwrap().to_string(), "EventHandlerNonNull?");
+}
+
+#[test]
+fn should_validate_spec_webidl() {
+    use weedle::validate::validate;
+
+    for name in &[
+        "html",
+        "interface-constructor",
+        "mediacapture-streams",
+        "observable-array",
+        "streams",
+        "webgpu",
+    ] {
+        let content = read_file(&format!("./tests/defs/{}.webidl", name));
+        let parsed = weedle::parse(&content).unwrap();
+        assert_eq!(validate(vec![&parsed[..]]), [], "{}", name);
+    }
+
+    // This copy of dom.webidl predates the `= {}` defaults of dictionary arguments
+    let content = read_file("./tests/defs/dom.webidl");
+    let parsed = weedle::parse(&content).unwrap();
+    let diagnostics = validate(vec![&parsed[..]]);
+    assert_eq!(diagnostics.len(), 6);
+    assert!(diagnostics
+        .iter()
+        .all(|d| d.rule == "dictionary-argument-optional"));
+}
+
+#[test]
+fn should_check_extended_attributes_of_spec_webidl() {
+    use weedle::known_attribute::check;
+
+    for name in &["dom", "html", "mediacapture-streams", "streams", "webgpu"] {
+        let content = read_file(&format!("./tests/defs/{}.webidl", name));
+        let parsed = weedle::parse(&content).unwrap();
+        assert_eq!(check(vec![&parsed[..]]), [], "{}", name);
+    }
+
+    let content = read_file("./tests/defs/interface-constructor.webidl");
+    let parsed = weedle::parse(&content).unwrap();
+    let messages: Vec<_> = check(vec![&parsed[..]])
+        .iter()
+        .map(|d| d.to_string())
+        .collect();
+    assert_eq!(
+        messages,
+        ["warning[unknown-attribute]: unknown extended attribute `Throws`"]
+    );
+}
+
+#[test]
+fn should_filter_html_webidl_by_global() {
+    use weedle::exposure::Exposures;
+    use weedle::semantic::Model;
+
+    let dom_content = read_file("./tests/defs/dom.webidl");
+    let html_content = read_file("./tests/defs/html.webidl");
+    let dom = weedle::parse(&dom_content).unwrap();
+    let html = weedle::parse(&html_content).unwrap();
+    let model = Model::from_files(vec![&dom[..], &html[..]]);
+    let exposures = Exposures::new(&model);
+
+    let interfaces = |definitions: &[Definition]| -> Vec<String> {
+        definitions
+            .iter()
+            .filter_map(|d| match d {
+                Definition::Interface(d) => Some(d.identifier.0.to_string()),
+                Definition::InterfaceMixin(d) => Some(d.identifier.0.to_string()),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let worker = interfaces(&exposures.filter(&html, "Worker"));
+    assert!(worker.contains(&"WorkerGlobalScope".to_string()));
+    assert!(worker.contains(&"WindowOrWorkerGlobalScope".to_string()));
+    assert!(!worker.contains(&"Window".to_string()));
+    assert!(!worker.contains(&"HTMLElement".to_string()));
+
+    let window = interfaces(&exposures.filter(&html, "Window"));
+    assert!(window.contains(&"HTMLElement".to_string()));
+    assert!(!window.contains(&"WorkerGlobalScope".to_string()));
+
+    let event_target = exposures.interface("EventTarget").unwrap();
+    assert!(event_target.is_exposed_in("DedicatedWorkerGlobalScope"));
+}
+
+#[test]
+fn should_find_partials_across_spec_webidl() {
+    use weedle::project::{Project, SourceMap};
+    use weedle::span::Spanned;
+
+    let mut sources = SourceMap::new();
+    sources.load_dir("./tests/defs").unwrap();
+    let project = Project::parse(&sources).unwrap();
+
+    let navigator: Vec<_> = project
+        .definitions_named("Navigator")
+        .map(|d| sources.location(d.file, d.node.span().start).to_string())
+        .collect();
+    assert_eq!(
+        navigator,
+        [
+            "./tests/defs/html.webidl:1765:1",
+            "./tests/defs/mediacapture-streams.webidl:146:1",
+            "./tests/defs/mediacapture-streams.webidl:176:1",
+        ]
+    );
+    assert_eq!(project.partials("GPUDevice").count(), 3);
+    assert!(sources
+        .files()
+        .all(|(_, file)| file.path.as_ref().unwrap().to_str() == Some(&file.name)));
+
+    let messages: Vec<_> = project
+        .validate()
+        .iter()
+        .map(|d| sources.display(d))
+        .collect();
+    assert!(messages.iter().any(|message| message
+        .starts_with("./tests/defs/dom.webidl:205:20: error[dictionary-argument-optional]")));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn should_serialize_html_webidl_to_json() {
+    let content = read_file("./tests/defs/html.webidl");
+    let parsed = weedle::parse(&content).unwrap();
+    let json = serde_json::to_value(&parsed).unwrap();
+
+    let definitions = json.as_array().unwrap();
+    assert_eq!(definitions.len(), parsed.len());
+    let window = definitions
+        .iter()
+        .filter_map(|definition| definition.get("Interface"))
+        .find(|interface| interface["identifier"] == "Window")
+        .unwrap();
+    assert_eq!(window["inheritance"]["identifier"], "EventTarget");
+    assert!(window["members"].as_array().unwrap().len() > 10);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn should_deserialize_spec_webidl_from_json() {
+    for name in &[
+        "bigint",
+        "dom",
+        "html",
+        "interface-constructor",
+        "mediacapture-streams",
+        "observable-array",
+        "streams",
+        "webgpu",
+    ] {
+        let content = read_file(&format!("./tests/defs/{}.webidl", name));
+        let mut parsed = weedle::parse(&content).unwrap();
+        let json = serde_json::to_string(&parsed).unwrap();
+
+        // Separators are not serialized, so neither is a trailing comma after the last enum value
+        for definition in &mut parsed {
+            if let Definition::Enum(definition) = definition {
+                definition.values.body.trailing = None;
+            }
+        }
+        let deserialized: Definitions = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.spanless_eq(&parsed), "{}", name);
+    }
+}
+
+#[test]
+fn should_parse_bigint_webidl() {
+    let content = read_file("./tests/defs/bigint.webidl");
+    let parsed = weedle::parse(&content).unwrap();
+
+    assert_eq!(parsed.len(), 9);
+    match &parsed[1] {
+        Definition::Typedef(typedef) => assert_eq!(
+            typedef.type_.to_webidl().trim_end(),
+            "(bigint or unrestricted double)"
+        ),
+        _ => panic!("expected a typedef"),
+    }
+    match &parsed[4] {
+        Definition::Typedef(typedef) => assert_eq!(
+            typedef.type_.to_webidl().trim_end(),
+            "([EnforceRange] unsigned long long or bigint)"
+        ),
+        _ => panic!("expected a typedef"),
+    }
+}
+
+#[test]
+fn should_parse_observable_array_webidl() {
+    let content = read_file("./tests/defs/observable-array.webidl");
+    let parsed = weedle::parse(&content).unwrap();
+
+    assert_eq!(parsed.len(), 7);
+    match &parsed[0] {
+        Definition::PartialInterfaceMixin(mixin) => match &mixin.members.body[0] {
+            mixin::MixinMember::Attribute(attribute) => assert!(matches!(
+                attribute.type_.type_,
+                types::Type::Single(types::SingleType::NonAny(
+                    types::NonAnyType::ObservableArrayType(_)
+                ))
+            )),
+            _ => panic!("expected an attribute"),
+        },
+        _ => panic!("expected a partial interface mixin"),
+    }
+    match &parsed[1] {
+        Definition::Typedef(typedef) => assert_eq!(
+            typedef.type_.to_webidl().trim_end(),
+            "(Uint8ClampedArray or Float16Array)"
+        ),
+        _ => panic!("expected a typedef"),
+    }
+}
+
+#[test]
+fn should_parse_browser_extended_attributes() {
+    // From Chromium's html_track_element.idl
+    let content = r#"
+        [Exposed=Window, HTMLConstructor]
+        interface HTMLTrackElement : HTMLElement {
+            [CEReactions, Reflect, ReflectOnly=("subtitles","captions","descriptions","chapters","metadata"), ReflectMissing="subtitles", ReflectInvalid="metadata"] attribute DOMString kind;
+            [CEReactions, Reflect, URL, RaisesException=Setter] attribute URLString src;
+        };
+    "#;
+    let parsed = weedle::parse(content).unwrap();
+
+    match &parsed[0] {
+        Definition::Interface(interface) => match &interface.members.body[0] {
+            interface::InterfaceMember::Attribute(member) => {
+                let attributes = member.attributes.as_ref().unwrap();
+                match attributes.find("ReflectOnly") {
+                    Some(attribute::ExtendedAttribute::StringList(reflect_only)) => {
+                        assert_eq!(reflect_only.list.body.list.len(), 5)
+                    }
+                    other => panic!("expected a string list, found {:?}", other),
+                }
+            }
+            _ => panic!("expected an attribute"),
+        },
+        _ => panic!("expected an interface"),
+    }
+    assert!(weedle::parse(&parsed.to_webidl())
+        .unwrap()
+        .spanless_eq(&parsed));
+}
+
+#[cfg(feature = "webidl2")]
+#[test]
+fn should_round_trip_spec_webidl_through_webidl2_json() {
+    for name in &[
+        "bigint",
+        "dom",
+        "html",
+        "interface-constructor",
+        "mediacapture-streams",
+        "observable-array",
+        "streams",
+        "webgpu",
+    ] {
+        let content = read_file(&format!("./tests/defs/{}.webidl", name));
+        let mut parsed = weedle::parse(&content).unwrap();
+        let json = weedle::webidl2::to_json(&parsed);
+
+        // The JSON has no trace of a trailing comma after the last enum value
+        for definition in &mut parsed {
+            if let Definition::Enum(definition) = definition {
+                definition.values.body.trailing = None;
+            }
+        }
+        assert!(
+            weedle::webidl2::from_json(&json)
+                .unwrap()
+                .spanless_eq(&parsed),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn should_parse_browser_dialects() {
+    // From Gecko's dom/webidl, written before jsonifiers became [Default] toJSON operations
+    let gecko = r#"
+        [ChromeOnly, Exposed=Window, Func="nsContentUtils::IsCallerChromeOrFuzzingEnabled"]
+        interface Performance {
+            [Throws, ChromeOnly, NeedsCallerType] readonly attribute DOMHighResTimeStamp now;
+            jsonifier;
+        };
+        interface HTMLAllCollection {
+            legacycaller (HTMLCollection or Element)? (optional DOMString nameOrIndex);
+        };
+    "#;
+    // From Chromium's IDL before `includes` and [Default] toJSON operations
+    let chromium = r#"
+        [RuntimeEnabled=PaymentRequest, Custom=(VisitDOMWrapper, "LegacyCallAsFunction")]
+        interface PaymentAddress {
+            serializer = { attribute };
+            readonly attribute DOMString country;
+        };
+        Document implements ParentNode;
+    "#;
+
+    assert!(weedle::parse(gecko).is_err());
+    assert!(weedle::parse(chromium).is_err());
+
+    let strict = ParseOptions::new().grammar(Grammar::Strict);
+    let gecko_options = strict.dialect(Dialect::Gecko);
+    let parsed = weedle::parse_with_options(gecko, gecko_options).unwrap();
+    assert_eq!(parsed.len(), 2);
+    let printed = parsed.to_webidl();
+    assert!(weedle::parse_with_options(&printed, gecko_options)
+        .unwrap()
+        .spanless_eq(&parsed));
+
+    let chromium_options = strict.dialect(Dialect::Chromium);
+    let parsed = weedle::parse_with_options(chromium, chromium_options).unwrap();
+    assert_eq!(parsed.len(), 2);
+    let printed = parsed.to_webidl();
+    assert!(weedle::parse_with_options(&printed, chromium_options)
+        .unwrap()
+        .spanless_eq(&parsed));
+
+    assert!(weedle::parse_with_options(gecko, chromium_options).is_err());
+    assert!(weedle::parse_with_options(chromium, gecko_options).is_err());
+}